@@ -0,0 +1,278 @@
+use anyhow::{Result, anyhow};
+use dotenvy::dotenv;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signature};
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+use pumpfun_monitor::{
+    config::{
+        TradingProfile, init_bot_config, rpc_client, spawn_config_reloader, spawn_profile_switcher,
+        target_wallet,
+    },
+    copy_trade::{CopyBuy, CopySell, CopyTradeConfig, CopyTrader, TargetMonitor},
+    farm::{FarmWallet, WalletFarm},
+    ledger::Ledger,
+    service::ConfirmationTracker,
+    utils::{TxLifetime, spawn_blockhash_refresher, wait_for_blockhash},
+};
+
+/// Our copy trade, handed back to the main loop once it landed.
+enum Copied {
+    Buy(CopyBuy),
+    Sell(CopySell),
+}
+
+// Sends `ixs` from `wallet` through `profile` right away, the confirmation and the ledger entry are
+// awaited in `confirmations` so the next target trade is not held up
+async fn send(
+    confirmations: &mut JoinSet<Option<Copied>>,
+    profile: &TradingProfile,
+    tracker: &ConfirmationTracker,
+    ledger: &Arc<Ledger>,
+    wallet: &FarmWallet,
+    ixs: Vec<Instruction>,
+    copied: Copied,
+) -> Result<()> {
+    let sender = profile.tx_sender().await?;
+    let blockhash = wait_for_blockhash().await;
+    let txn = wallet.sign(
+        &[],
+        &profile.with_tip(sender.as_ref(), wallet.pubkey(), ixs),
+        &[],
+        &TxLifetime::Blockhash(blockhash.blockhash),
+    )?;
+    let encoded_tx = sender.encode(&txn)?;
+
+    let tip = profile.tip_lamports(sender.as_ref());
+    let rpc_client = rpc_client()?;
+    let tracker = tracker.clone();
+    let ledger = ledger.clone();
+    let signature = txn.signatures[0];
+    let wallet = wallet.pubkey();
+    confirmations.spawn(async move {
+        match sender
+            .send_and_confirm(
+                &encoded_tx,
+                &tracker,
+                Some(blockhash.last_valid_block_height),
+            )
+            .await
+        {
+            Ok(outcome) => {
+                println!("Outcome of {}: {:?}", signature, outcome);
+                if !outcome.is_landed() {
+                    return None;
+                }
+            }
+            Err(err) => {
+                eprintln!("Copy trade {} failed: {}", signature, err);
+                return None;
+            }
+        }
+
+        record(&rpc_client, &ledger, wallet, &signature, tip).await;
+        Some(copied)
+    });
+
+    Ok(())
+}
+
+// Books the fills of our landed trade in the ledger
+async fn record(
+    rpc_client: &RpcClient,
+    ledger: &Ledger,
+    wallet: Pubkey,
+    signature: &Signature,
+    tip: u64,
+) {
+    match ledger
+        .record_transaction(rpc_client, signature, &[wallet], tip)
+        .await
+    {
+        Ok(fills) => println!("Recorded {} fills of {}", fills.len(), signature),
+        Err(err) => eprintln!("Could not record {} in the ledger: {}", signature, err),
+    }
+}
+
+// Books a landed copy trade on the trader
+fn book(trader: &mut CopyTrader, copied: Option<Copied>) {
+    let mint = match copied {
+        Some(Copied::Buy(buy)) => {
+            trader.record_buy(&buy);
+            buy.mint
+        }
+        Some(Copied::Sell(sell)) => {
+            trader.record_sell(&sell);
+            sell.mint
+        }
+        None => return,
+    };
+
+    print_position(trader, &mint);
+}
+
+fn print_position(trader: &CopyTrader, mint: &Pubkey) {
+    if let Some(position) = trader.positions.get(mint) {
+        println!(
+            "Position {}: we hold {} tokens, target {}",
+            mint, position.our_tokens, position.target_tokens
+        );
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    env_logger::init();
+    init_bot_config(&[
+        "grpc_endpoint",
+        "grpc_token",
+        "confirm_service",
+        "target_wallet",
+    ])?;
+
+    println!("🪞 PumpFun Copy Trader");
+    let target_wallet = target_wallet()?;
+    let farm = WalletFarm::from_env()?;
+    let wallet = farm
+        .buyers()
+        .next()
+        .ok_or_else(|| anyhow!("The farm has no buyer wallets"))?
+        .clone();
+    let mut trader = CopyTrader::new(CopyTradeConfig::from_env()?)?;
+    let ledger = Arc::new(Ledger::from_env()?);
+    let resumed: Vec<_> = ledger
+        .open_positions()
+        .into_iter()
+        .filter(|position| position.wallet == wallet.pubkey().to_string())
+        .collect();
+    trader.resume(&resumed)?;
+    println!(
+        "Ledger {}: resumed {} open positions",
+        ledger.path().display(),
+        resumed.len()
+    );
+    println!(
+        "Copying {} from {} with {:?}",
+        target_wallet,
+        wallet.pubkey(),
+        trader.config.sizing
+    );
+
+    let profile = trader.config.profile()?;
+    println!("Profile {}", profile);
+    println!("Connected to {}", profile.tx_sender().await?.region());
+    if trader.config.profile.is_none() {
+        spawn_profile_switcher();
+    }
+    spawn_config_reloader()?;
+    let rpc_client = rpc_client()?;
+    spawn_blockhash_refresher(rpc_client.clone())?;
+    let tracker = ConfirmationTracker::new(rpc_client);
+
+    let mut confirmations = JoinSet::new();
+    let mut monitor = TargetMonitor::spawn(target_wallet)?;
+    loop {
+        let trade = tokio::select! {
+            Some(result) = confirmations.join_next() => {
+                match result {
+                    Ok(copied) => book(&mut trader, copied),
+                    Err(err) => eprintln!("Copy confirmation task failed: {}", err),
+                }
+                continue;
+            }
+            trade = monitor.recv() => match trade {
+                Some(trade) => trade,
+                None => break,
+            },
+        };
+
+        let event = &trade.event;
+        println!(
+            "\n👀 Target {:?} {} tokens of {} for {} lamports ({})",
+            trade.side, event.token_amount, event.mint, event.sol_amount, trade.signature
+        );
+
+        if trade.is_buy() {
+            let buy = match trader.on_target_buy(&trade) {
+                Ok(Some(buy)) => buy,
+                Ok(None) => {
+                    println!("Not mirrored");
+                    continue;
+                }
+                Err(err) => {
+                    eprintln!("Could not size the buy: {}", err);
+                    continue;
+                }
+            };
+
+            println!(
+                "Buying {} tokens for at most {} lamports",
+                buy.token_amount, buy.max_sol_cost
+            );
+            let ixs = trader.buy_instructions(&buy, wallet.pubkey());
+            let sent = match trader.config.profile() {
+                Ok(profile) => {
+                    send(
+                        &mut confirmations,
+                        &profile,
+                        &tracker,
+                        &ledger,
+                        &wallet,
+                        ixs,
+                        Copied::Buy(buy),
+                    )
+                    .await
+                }
+                Err(err) => Err(err.into()),
+            };
+            if let Err(err) = sent {
+                eprintln!("Copy buy failed: {}", err);
+            }
+        } else if let Some(sell) = trader.on_target_sell(&trade) {
+            println!(
+                "Selling {} tokens{}",
+                sell.token_amount,
+                if sell.close_ata {
+                    ", closing the position"
+                } else {
+                    ""
+                }
+            );
+            let sent = match (
+                trader.config.profile(),
+                trader.sell_instructions(&sell, &trade, wallet.pubkey()),
+            ) {
+                (Ok(profile), Ok(ixs)) => {
+                    send(
+                        &mut confirmations,
+                        &profile,
+                        &tracker,
+                        &ledger,
+                        &wallet,
+                        ixs,
+                        Copied::Sell(sell),
+                    )
+                    .await
+                }
+                (Err(err), _) => Err(err.into()),
+                (_, Err(err)) => Err(err),
+            };
+            if let Err(err) = sent {
+                eprintln!("Copy sell failed: {}", err);
+            }
+        }
+
+        print_position(&trader, &event.mint);
+    }
+
+    while let Some(result) = confirmations.join_next().await {
+        match result {
+            Ok(copied) => book(&mut trader, copied),
+            Err(err) => eprintln!("Copy confirmation task failed: {}", err),
+        }
+    }
+
+    Ok(())
+}
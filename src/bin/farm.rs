@@ -0,0 +1,225 @@
+use anyhow::{Context, Result, anyhow, bail};
+use dotenvy::dotenv;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use std::{str::FromStr, time::Duration};
+
+use pumpfun_monitor::{
+    config::{active_profile, init_bot_config, rpc_client, tx_signer},
+    farm::{DistributionConfig, DistributionPlan, SweepPlan, WalletFarm},
+    instructions::{pumpfun_accounts::fetch_curve_and_global, pumpfun_sell::PumpFunSell},
+    ledger::{Ledger, PnlSummary},
+    utils::{BondingCurveQuote, TxLifetime},
+};
+
+const USAGE: &str = "Usage:
+  farm balances [MINT...]
+  farm distribute MIN_SOL MAX_SOL [--delay-ms MIN-MAX] [--per-tx N] [--dry-run]
+  farm sweep [--keep-sol SOL] [--dry-run] [MINT...]
+  farm sell MINT [PERCENT] [--dry-run]
+  farm pnl [MINT...]
+
+The treasury is the main key (PRIVATE_KEY, KEYSTORE_FILE or REMOTE_SIGNER), the wallets come
+from FARM_WALLETS.";
+
+struct Args {
+    positional: Vec<String>,
+    dry_run: bool,
+    delay_ms: (u64, u64),
+    per_tx: usize,
+    keep_sol: f64,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args> {
+    let mut parsed = Args {
+        positional: Vec::new(),
+        dry_run: false,
+        delay_ms: (0, 0),
+        per_tx: 1,
+        keep_sol: 0.0,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--dry-run" => parsed.dry_run = true,
+            "--delay-ms" => {
+                let range = value()?;
+                let (min, max) = range.split_once('-').unwrap_or((&range, &range));
+                parsed.delay_ms = (min.parse()?, max.parse()?);
+            }
+            "--per-tx" => parsed.per_tx = value()?.parse()?,
+            "--keep-sol" => parsed.keep_sol = value()?.parse()?,
+            _ => parsed.positional.push(arg),
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn to_lamports(sol: f64) -> u64 {
+    (sol * LAMPORTS_PER_SOL as f64) as u64
+}
+
+fn to_sol(lamports: i64) -> f64 {
+    lamports as f64 / LAMPORTS_PER_SOL as f64
+}
+
+fn print_summaries(title: &str, summaries: impl IntoIterator<Item = (String, PnlSummary)>) {
+    println!("\n{}", title);
+    for (key, summary) in summaries {
+        println!("  {}: {}", key, summary);
+    }
+}
+
+fn parse_mints(args: &[String]) -> Result<Vec<Pubkey>> {
+    args.iter()
+        .map(|mint| Pubkey::from_str(mint).with_context(|| format!("invalid mint {}", mint)))
+        .collect()
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    init_bot_config(&[])?;
+
+    let mut args = std::env::args().skip(1);
+    let command = args.next().unwrap_or_default();
+    let args = parse_args(args)?;
+
+    let farm = WalletFarm::from_env()?;
+    let treasury = tx_signer()?;
+    let treasury = &*treasury;
+    let rpc_client = rpc_client()?;
+
+    match command.as_str() {
+        "balances" => {
+            let mints = parse_mints(&args.positional)?;
+            farm.refresh_balances(&rpc_client, &mints).await?;
+            farm.print_summary();
+            for mint in &mints {
+                println!("{}: {} tokens", mint, farm.total_token_balance(mint));
+            }
+        }
+        "distribute" => {
+            let [min_sol, max_sol] = args.positional.as_slice() else {
+                bail!(USAGE);
+            };
+            let config = DistributionConfig {
+                min_lamports: to_lamports(min_sol.parse()?),
+                max_lamports: to_lamports(max_sol.parse()?),
+                min_delay: Duration::from_millis(args.delay_ms.0),
+                max_delay: Duration::from_millis(args.delay_ms.1),
+                transfers_per_tx: args.per_tx,
+            };
+
+            let plan = DistributionPlan::new(treasury.pubkey(), &farm.pubkeys(), &config)?;
+            plan.print_preview(rpc_client.get_balance(&treasury.pubkey()).await?);
+            if !args.dry_run {
+                plan.execute(&rpc_client, treasury).await?;
+            }
+        }
+        "sweep" => {
+            let mints = parse_mints(&args.positional)?;
+            let plan = SweepPlan::build(
+                &rpc_client,
+                treasury.pubkey(),
+                &farm.wallets,
+                &mints,
+                to_lamports(args.keep_sol),
+            )
+            .await?;
+            plan.print_preview();
+            if !args.dry_run {
+                plan.execute(&rpc_client, treasury).await?;
+            }
+        }
+        "sell" => {
+            let (mint, percentage) = match args.positional.as_slice() {
+                [mint] => (mint, 100.0),
+                [mint, percentage] => (mint, percentage.parse()?),
+                _ => bail!(USAGE),
+            };
+            let mint = parse_mints(std::slice::from_ref(mint))?[0];
+            farm.refresh_balances(&rpc_client, &[mint]).await?;
+
+            // Works for any PumpFun mint, each seller wallet signs and pays for its own sell
+            let slippage_pct = active_profile()?.slippage_pct();
+            let pumpfun_sell = PumpFunSell::new(rpc_client.clone());
+            for wallet in farm.sellers() {
+                let balance = wallet.token_balance(&mint);
+                if balance == 0 {
+                    continue;
+                }
+
+                println!(
+                    "{} ({}) sells {}% of {} tokens",
+                    wallet.label,
+                    wallet.pubkey(),
+                    percentage,
+                    balance
+                );
+                if args.dry_run {
+                    continue;
+                }
+
+                let ixs = pumpfun_sell
+                    .sell_percentage(mint, wallet.pubkey(), percentage, slippage_pct)
+                    .await?;
+                let blockhash = rpc_client.get_latest_blockhash().await?;
+                let txn = wallet.sign(&[], &ixs, &[], &TxLifetime::Blockhash(blockhash))?;
+                let signature = rpc_client.send_and_confirm_transaction(&txn).await?;
+                println!("Sold ({})", signature);
+            }
+        }
+        "pnl" => {
+            let mints: Vec<String> = parse_mints(&args.positional)?
+                .iter()
+                .map(Pubkey::to_string)
+                .collect();
+            let ledger = Ledger::from_env()?;
+            let positions: Vec<_> = ledger
+                .positions()
+                .into_iter()
+                .filter(|position| mints.is_empty() || mints.contains(&position.mint))
+                .collect();
+            println!("{}: {} positions", ledger.path().display(), positions.len());
+
+            for position in &positions {
+                // Open positions are valued at what selling them on the curve would return now
+                let unrealized = if position.is_open() {
+                    match fetch_curve_and_global(&rpc_client, &position.mint.parse()?).await {
+                        Ok((curve, global)) => position
+                            .unrealized_pnl(&BondingCurveQuote::new(&curve, &global))
+                            .map(|pnl| format!("{:+.4} SOL", to_sol(pnl)))
+                            .unwrap_or_else(|err| err.to_string()),
+                        Err(err) => err.to_string(),
+                    }
+                } else {
+                    "closed".to_string()
+                };
+
+                println!(
+                    "{} {}: {} tokens, cost basis {:.4} SOL, realized {:+.4} SOL, unrealized {}",
+                    position.wallet,
+                    position.mint,
+                    position.token_amount,
+                    to_sol(position.cost_basis as i64),
+                    to_sol(position.realized_pnl),
+                    unrealized
+                );
+            }
+
+            print_summaries(
+                "Per mint",
+                PnlSummary::group_by(&positions, |position| position.mint.clone()),
+            );
+            print_summaries(
+                "Per wallet",
+                PnlSummary::group_by(&positions, |position| position.wallet.clone()),
+            );
+        }
+        _ => bail!(USAGE),
+    }
+
+    Ok(())
+}
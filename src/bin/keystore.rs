@@ -0,0 +1,231 @@
+use anyhow::{Result, anyhow, bail};
+use dotenvy::dotenv;
+use serde_json::{Value, json};
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::{collections::BTreeSet, env, fs, io::Write, path::Path};
+use zeroize::Zeroizing;
+
+use pumpfun_monitor::{
+    config::{read_passphrase, try_parse_keypair},
+    farm::{WalletFarm, WalletRole},
+    keystore::{Keystore, UnlockedKeystore},
+};
+
+const DEFAULT_KEYSTORE_FILE: &str = "keystore.json";
+const USAGE: &str = "Usage:
+  keystore list
+  keystore import [PATH] [--label LABEL] [--roles ROLE,...]
+  keystore export [LABEL|PUBKEY...] [--out FILE]
+  keystore rotate [--new-passphrase-file FILE]
+
+PATH is a keypair file, a directory of keypair files or a wallets file, without it the secret
+key is prompted for. The keystore is --file, KEYSTORE_FILE or keystore.json, its passphrase
+comes from --passphrase-file, KEYSTORE_PASSPHRASE_FILE or a prompt.";
+
+struct Args {
+    positional: Vec<String>,
+    file: String,
+    passphrase_file: Option<String>,
+    new_passphrase_file: Option<String>,
+    label: Option<String>,
+    roles: BTreeSet<WalletRole>,
+    out: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args> {
+    let setting = |key: &str| env::var(key).ok().filter(|value| !value.trim().is_empty());
+    let mut parsed = Args {
+        positional: Vec::new(),
+        file: setting("KEYSTORE_FILE").unwrap_or_else(|| DEFAULT_KEYSTORE_FILE.to_string()),
+        passphrase_file: setting("KEYSTORE_PASSPHRASE_FILE"),
+        new_passphrase_file: None,
+        label: None,
+        roles: BTreeSet::new(),
+        out: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--file" => parsed.file = value()?,
+            "--passphrase-file" => parsed.passphrase_file = Some(value()?),
+            "--new-passphrase-file" => parsed.new_passphrase_file = Some(value()?),
+            "--label" => parsed.label = Some(value()?),
+            "--roles" => {
+                parsed.roles = value()?
+                    .split(',')
+                    .map(|role| role.parse())
+                    .collect::<Result<_, _>>()?
+            }
+            "--out" => parsed.out = Some(value()?),
+            _ => parsed.positional.push(arg),
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn unlock(args: &Args) -> Result<UnlockedKeystore> {
+    let keystore = Keystore::load(&args.file)?;
+    let passphrase = read_passphrase(
+        args.passphrase_file.as_deref(),
+        &format!("Passphrase of {}: ", args.file),
+    )?;
+
+    Ok(keystore.unlock(&passphrase)?)
+}
+
+// A new passphrase is typed twice, a typo would lock the keys away for good
+fn new_passphrase(file: Option<&str>) -> Result<Zeroizing<String>> {
+    if file.is_some() {
+        return Ok(read_passphrase(file, "")?);
+    }
+
+    let passphrase = read_passphrase(None, "New passphrase: ")?;
+    if *passphrase != *read_passphrase(None, "Repeat the passphrase: ")? {
+        bail!("The passphrases do not match");
+    }
+
+    Ok(passphrase)
+}
+
+// Keypairs to import with their label and roles, from `path` or the prompt
+fn import_sources(
+    args: &Args,
+    count: usize,
+) -> Result<Vec<(String, BTreeSet<WalletRole>, Keypair)>> {
+    let default_label = || {
+        args.label.clone().unwrap_or_else(|| match count {
+            0 => "main".to_string(),
+            n => format!("wallet-{}", n),
+        })
+    };
+
+    let Some(path) = args.positional.get(1) else {
+        let secret = Zeroizing::new(rpassword::prompt_password(
+            "Secret key (base58, base64 or JSON bytes): ",
+        )?);
+        let keypair = try_parse_keypair(secret.trim()).map_err(|why| anyhow!(why))?;
+        return Ok(vec![(default_label(), args.roles.clone(), keypair)]);
+    };
+
+    // A keypair file is base58, base64 or a byte array, any other JSON is a wallets file
+    let path = Path::new(path);
+    if path.is_file() {
+        let contents = Zeroizing::new(fs::read_to_string(path)?);
+        let contents = contents.trim();
+        if serde_json::from_str::<Vec<u8>>(contents).is_ok()
+            || serde_json::from_str::<Value>(contents).is_err()
+        {
+            let keypair = try_parse_keypair(contents)
+                .map_err(|why| anyhow!("{}: {}", path.display(), why))?;
+            let label = args.label.clone().unwrap_or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_else(default_label)
+            });
+            return Ok(vec![(label, args.roles.clone(), keypair)]);
+        }
+    }
+
+    Ok(WalletFarm::read_wallets(path)?)
+}
+
+fn main() -> Result<()> {
+    dotenv().ok();
+    let args = parse_args(env::args().skip(1))?;
+
+    match args.positional.first().map(String::as_str) {
+        Some("list") => {
+            let keystore = Keystore::load(&args.file)?;
+            println!("{}: {} keys", args.file, keystore.entries.len());
+            for entry in &keystore.entries {
+                let roles: Vec<_> = entry.roles()?.iter().map(WalletRole::as_str).collect();
+                println!("  {} [{}] {}", entry.label, roles.join(","), entry.pubkey);
+            }
+        }
+        Some("import") => {
+            let mut keystore = if Path::new(&args.file).exists() {
+                unlock(&args)?
+            } else {
+                println!("Creating {}", args.file);
+                Keystore::create(&new_passphrase(args.passphrase_file.as_deref())?)?
+            };
+
+            let sources = import_sources(&args, keystore.keystore.entries.len())?;
+            if sources.is_empty() {
+                bail!("No keypairs to import");
+            }
+            for (label, roles, keypair) in &sources {
+                keystore.add(label, roles, keypair)?;
+                println!("  {} {}", label, keypair.pubkey());
+            }
+            keystore.save(&args.file)?;
+            println!("Imported {} keys into {}", sources.len(), args.file);
+            if let Some(path) = args.positional.get(1) {
+                println!(
+                    "Delete the plaintext keys in {} once you checked the keystore",
+                    path
+                );
+            }
+        }
+        Some("export") => {
+            let keystore = unlock(&args)?;
+            let entries: Vec<_> = match &args.positional[1..] {
+                [] => keystore.keystore.entries.iter().collect(),
+                wanted => wanted
+                    .iter()
+                    .map(|key| {
+                        keystore
+                            .keystore
+                            .find(key)
+                            .ok_or_else(|| anyhow!("No keystore entry '{}'", key))
+                    })
+                    .collect::<Result<_>>()?,
+            };
+
+            // The wallets file format, so FARM_WALLETS can load the export
+            let wallets = entries
+                .iter()
+                .map(|entry| {
+                    let keypair = keystore.keypair(entry)?;
+                    Ok(json!({
+                        "label": entry.label,
+                        "roles": entry.roles()?.iter().map(WalletRole::as_str).collect::<Vec<_>>(),
+                        "keypair": keypair.to_base58_string(),
+                    }))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let output = Zeroizing::new(serde_json::to_string_pretty(&wallets)?);
+
+            eprintln!(
+                "Warning: the export holds {} plaintext secret keys",
+                wallets.len()
+            );
+            match &args.out {
+                Some(path) => {
+                    let mut options = fs::OpenOptions::new();
+                    options.write(true).create_new(true);
+                    #[cfg(unix)]
+                    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+                    options.open(path)?.write_all(output.as_bytes())?;
+                    eprintln!("Wrote {}", path);
+                }
+                None => println!("{}", *output),
+            }
+        }
+        Some("rotate") => {
+            let keystore = unlock(&args)?;
+            let rotated = keystore.rotate(&new_passphrase(args.new_passphrase_file.as_deref())?)?;
+            rotated.save(&args.file)?;
+            println!(
+                "Re-encrypted {} keys of {} under the new passphrase",
+                rotated.keystore.entries.len(),
+                args.file
+            );
+        }
+        _ => bail!(USAGE),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use anyhow::{Result, anyhow, bail};
+use dotenvy::dotenv;
+use solana_sdk::signer::Signer;
+use std::{env, sync::Arc};
+
+use pumpfun_monitor::{
+    config::{read_passphrase, try_parse_keypair},
+    keystore::Keystore,
+    signer::{KeystoreSigner, SignerEndpoint, SignerServer, TxSigner},
+};
+
+const DEFAULT_LISTEN: &str = "http://127.0.0.1:7070";
+const USAGE: &str = "Usage:
+  signer [--listen http://HOST:PORT|unix:PATH] [--file KEYSTORE]
+
+Serves REMOTE_SIGNER requests with every key of the keystore (--file or KEYSTORE_FILE), or with
+PRIVATE_KEY. Listens on --listen, REMOTE_SIGNER or http://127.0.0.1:7070, requests must carry
+REMOTE_SIGNER_TOKEN if it is set.";
+
+fn setting(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|value| !value.trim().is_empty())
+}
+
+// Every keystore entry signs from the keystore, the keys stay encrypted between signatures
+fn load_signers(keystore_file: Option<String>) -> Result<Vec<Arc<dyn TxSigner>>> {
+    let Some(path) = keystore_file else {
+        let private_key = setting("PRIVATE_KEY")
+            .ok_or_else(|| anyhow!("Set KEYSTORE_FILE or PRIVATE_KEY\n\n{}", USAGE))?;
+        let keypair = try_parse_keypair(private_key.trim()).map_err(|why| anyhow!(why))?;
+        return Ok(vec![Arc::new(keypair)]);
+    };
+
+    let passphrase = read_passphrase(
+        setting("KEYSTORE_PASSPHRASE_FILE").as_deref(),
+        &format!("Passphrase of {}: ", path),
+    )?;
+    let keystore = Arc::new(Keystore::load(&path)?.unlock(&passphrase)?);
+
+    keystore
+        .keystore
+        .entries
+        .iter()
+        .map(|entry| {
+            let signer: Arc<dyn TxSigner> =
+                Arc::new(KeystoreSigner::new(keystore.clone(), &entry.pubkey)?);
+            Ok(signer)
+        })
+        .collect()
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+
+    let mut listen = setting("REMOTE_SIGNER").unwrap_or_else(|| DEFAULT_LISTEN.to_string());
+    let mut keystore_file = setting("KEYSTORE_FILE");
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--listen" => listen = value()?,
+            "--file" => keystore_file = Some(value()?),
+            _ => bail!(USAGE),
+        }
+    }
+
+    let endpoint: SignerEndpoint = listen.parse()?;
+    let token = setting("REMOTE_SIGNER_TOKEN");
+    if let SignerEndpoint::Tcp(addr) = &endpoint
+        && token.is_none()
+        && !["127.0.0.1:", "localhost:", "[::1]:"]
+            .iter()
+            .any(|local| addr.starts_with(local))
+    {
+        bail!(
+            "Set REMOTE_SIGNER_TOKEN to listen on {} beyond localhost",
+            addr
+        );
+    }
+
+    let signers = load_signers(keystore_file)?;
+    for signer in &signers {
+        println!("🔑 {} ({})", signer.pubkey(), signer.location());
+    }
+    println!("✍️ Signing on {}", endpoint);
+
+    SignerServer::new(signers, token)?.serve(&endpoint).await?;
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use anyhow::{Result, anyhow};
+use dotenvy::dotenv;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+use pumpfun_monitor::{
+    config::{init_bot_config, rpc_client, spawn_config_reloader, spawn_profile_switcher},
+    farm::{FarmWallet, WalletFarm},
+    instructions::pumpfun_accounts::fetch_global,
+    ledger::Ledger,
+    monitor::{CreateMonitor, NewToken},
+    service::ConfirmationTracker,
+    sniper::{Sniper, SniperConfig},
+    utils::{TxLifetime, spawn_blockhash_refresher, wait_for_blockhash},
+};
+
+// Signs against the cached blockhash and sends right away through the profile of the sniper, the
+// confirmation is awaited in `confirmations` so the next create is not held up
+async fn snipe(
+    sniper: &Sniper,
+    confirmations: &mut JoinSet<()>,
+    tracker: &ConfirmationTracker,
+    ledger: &Arc<Ledger>,
+    buyer: &FarmWallet,
+    token: &NewToken,
+) -> Result<()> {
+    let profile = sniper.config.profile()?;
+    let sender = profile.tx_sender().await?;
+    let ixs = sniper.buy_instructions(token, buyer.pubkey())?;
+    let blockhash = wait_for_blockhash().await;
+    let txn = buyer.sign(
+        &[],
+        &profile.with_tip(sender.as_ref(), buyer.pubkey(), ixs),
+        &[],
+        &TxLifetime::Blockhash(blockhash.blockhash),
+    )?;
+    let encoded_tx = sender.encode(&txn)?;
+
+    let tip = profile.tip_lamports(sender.as_ref());
+    let rpc_client = rpc_client()?;
+    let tracker = tracker.clone();
+    let ledger = ledger.clone();
+    let mint = token.mint;
+    let signature = txn.signatures[0];
+    let wallets = [buyer.pubkey()];
+    confirmations.spawn(async move {
+        match sender
+            .send_and_confirm(
+                &encoded_tx,
+                &tracker,
+                Some(blockhash.last_valid_block_height),
+            )
+            .await
+        {
+            Ok(outcome) => {
+                println!("Snipe of {} outcome: {:?}", mint, outcome);
+                if !outcome.is_landed() {
+                    return;
+                }
+            }
+            Err(err) => {
+                eprintln!("Snipe of {} failed: {}", mint, err);
+                return;
+            }
+        }
+
+        if let Err(err) = ledger
+            .record_transaction(&rpc_client, &signature, &wallets, tip)
+            .await
+        {
+            eprintln!(
+                "Could not record snipe {} in the ledger: {}",
+                signature, err
+            );
+        }
+    });
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    env_logger::init();
+    init_bot_config(&[
+        "grpc_endpoint",
+        "grpc_token",
+        "confirm_service",
+        "sniper_config_file",
+    ])?;
+
+    println!("🎯 PumpFun Sniper");
+    let rpc_client = rpc_client()?;
+    let farm = WalletFarm::from_env()?;
+    let mut sniper = Sniper::new(SniperConfig::from_env()?, fetch_global(&rpc_client).await?)?;
+    let ledger = Arc::new(Ledger::from_env()?);
+    let buyers: Vec<_> = farm.buyers().cloned().collect();
+    if buyers.is_empty() {
+        return Err(anyhow!("The farm has no buyer wallets"));
+    }
+    println!(
+        "Buying {} SOL per token with {} wallets",
+        sniper.config.buy_sol,
+        buyers.len()
+    );
+
+    let profile = sniper.config.profile()?;
+    println!("Profile {}", profile);
+    println!("Connected to {}", profile.tx_sender().await?.region());
+    if sniper.config.profile.is_none() {
+        spawn_profile_switcher();
+    }
+    spawn_config_reloader()?;
+    spawn_blockhash_refresher(rpc_client.clone())?;
+    let tracker = ConfirmationTracker::new(rpc_client);
+
+    let mut confirmations = JoinSet::new();
+    let mut monitor = CreateMonitor::spawn()?;
+    println!("👀 Watching PumpFun creates...");
+    while let Some(token) = monitor.recv().await {
+        println!(
+            "\n🆕 {} ({}) {} by {} in slot {}, dev buy {} lamports",
+            token.name,
+            token.symbol,
+            token.mint,
+            token.creator,
+            token.slot,
+            token.dev_buy_sol()
+        );
+        if let Err(reason) = sniper.filter.check(&token) {
+            println!("Skipped: {}", reason);
+            continue;
+        }
+
+        // Buyers take turns so every snipe comes from a different wallet
+        let buyer = &buyers[sniper.buys % buyers.len()];
+        // Reap finished confirmations so the set only holds pending ones
+        while confirmations.try_join_next().is_some() {}
+        match snipe(
+            &sniper,
+            &mut confirmations,
+            &tracker,
+            &ledger,
+            buyer,
+            &token,
+        )
+        .await
+        {
+            Ok(()) => {
+                println!("🚀 Sent buy of {} from {}", token.mint, buyer.pubkey());
+                sniper.record_buy();
+            }
+            Err(err) => eprintln!("Could not snipe {}: {}", token.mint, err),
+        }
+
+        if sniper.is_done() {
+            println!("Reached max_buys, stopping");
+            break;
+        }
+    }
+
+    if !confirmations.is_empty() {
+        println!("Waiting for {} snipes to confirm", confirmations.len());
+    }
+    while let Some(result) = confirmations.join_next().await {
+        if let Err(err) = result {
+            eprintln!("Snipe confirmation task failed: {}", err);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,816 @@
+use dotenvy::dotenv;
+use once_cell::sync::OnceCell;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signer::keypair::Keypair};
+use std::{collections::BTreeMap, env, fmt::Display, fs, path::Path, str::FromStr};
+use toml::Table;
+use url::Url;
+
+use crate::{
+    config::{ConfirmService, DEFAULT_PROFILE, TokenMetadata, TradingProfile, try_parse_keypair},
+    error::{ClientError, ClientResult},
+    instructions::pumpfun_launch::MAX_LAUNCH_BUYS,
+    keystore::Keystore,
+    signer::SignerEndpoint,
+};
+
+/// Read when `BOT_CONFIG_FILE` is not set and the file exists.
+pub const DEFAULT_BOT_CONFIG_FILE: &str = "bot.toml";
+pub const DEFAULT_LEDGER_FILE: &str = "ledger.jsonl";
+/// Keystore entry used as `PRIVATE_KEY` when `KEYSTORE_KEY` is not set.
+pub const DEFAULT_KEYSTORE_KEY: &str = "main";
+
+/// Settings of the bot, `key` in the TOML file and `KEY` in the environment.
+pub const BOT_CONFIG_KEYS: &[&str] = &[
+    "private_key",
+    "keystore_file",
+    "keystore_key",
+    "keystore_passphrase_file",
+    "remote_signer",
+    "remote_signer_token",
+    "remote_signer_pubkey",
+    "farm_wallets",
+    "target_wallet",
+    "rpc_endpoint",
+    "grpc_endpoint",
+    "grpc_token",
+    "laser_endpoint",
+    "laser_token_key",
+    "confirm_service",
+    "nozomi_api_key",
+    "zero_slot_key",
+    "jito_auth_key",
+    "cu",
+    "priority_fee_micro_lamport",
+    "third_party_fee",
+    "buy_sol_amount",
+    "slippage",
+    "fan_out_sell",
+    "blockhash_from_grpc",
+    "durable_nonce",
+    "launch_buys",
+    "sell_strategy_file",
+    "copy_trade_file",
+    "sniper_config_file",
+    "ledger_file",
+    "token_name",
+    "token_symbol",
+    "token_uri",
+    "token_description",
+    "profile",
+];
+
+/// Settings a `[profiles.NAME]` table can override.
+pub const PROFILE_KEYS: &[&str] = &[
+    "buy_sol_amount",
+    "slippage",
+    "cu",
+    "priority_fee_micro_lamport",
+    "confirm_service",
+    "third_party_fee",
+    "sell_strategy_file",
+];
+
+// Keys every binary needs
+const ALWAYS_REQUIRED: &[&str] = &["rpc_endpoint"];
+
+/// Largest compute unit limit of a transaction.
+pub const MAX_COMPUTE_UNITS: u64 = 1_400_000;
+/// Priority fees above this are taken for a typo.
+pub const MAX_PRIORITY_FEE_SOL: f64 = 0.1;
+/// Relayer tips above this are taken for a typo.
+pub const MAX_TIP_SOL: f64 = 1.0;
+
+static BOT_CONFIG: OnceCell<BotConfig> = OnceCell::new();
+// Keys the binary required at startup, a reload must still provide them
+static REQUIRED_KEYS: OnceCell<Vec<String>> = OnceCell::new();
+
+/// Loads the config once, failing with every problem if it is invalid or misses a key of
+/// `required`. Binaries call this first thing so a typo never surfaces mid-trade.
+pub fn init_bot_config(required: &[&str]) -> ClientResult<&'static BotConfig> {
+    let config = BOT_CONFIG.get_or_try_init(|| BotConfig::from_env(required))?;
+    REQUIRED_KEYS.get_or_init(|| required.iter().map(|key| key.to_string()).collect());
+
+    Ok(config)
+}
+
+/// Loads the config again with the requirements of `init_bot_config`, for a reload.
+pub fn load_bot_config() -> ClientResult<BotConfig> {
+    let required: Vec<&str> = REQUIRED_KEYS
+        .get()
+        .map(|keys| keys.iter().map(String::as_str).collect())
+        .unwrap_or_default();
+
+    BotConfig::from_env(&required)
+}
+
+/// The config loaded by `init_bot_config`.
+pub fn bot_config() -> ClientResult<&'static BotConfig> {
+    BOT_CONFIG.get().ok_or(ClientError::InvalidInput(
+        "the bot config is not loaded, call init_bot_config first",
+    ))
+}
+
+/// Error for a setting a binary uses without requiring it in `init_bot_config`.
+pub(crate) fn missing_setting(key: &str) -> ClientError {
+    ClientError::InvalidData(format!(
+        "{} must be set in the environment or the bot config file",
+        key.to_uppercase()
+    ))
+}
+
+/// Every setting of the bot, validated. Not `Debug` so the keys never end up in a log.
+pub struct BotConfig {
+    /// File the settings were read from, if any.
+    pub source: Option<String>,
+    /// Unset when the key is in `keystore_file` or `remote_signer`, see `tx_signer`.
+    pub private_key: Option<Keypair>,
+    /// Encrypted keystore holding the `PRIVATE_KEY` entry.
+    pub keystore_file: Option<String>,
+    /// Label or pubkey of the keystore entry used as `PRIVATE_KEY`.
+    pub keystore_key: String,
+    /// File with the keystore passphrase, unset prompts for it.
+    pub keystore_passphrase_file: Option<String>,
+    /// Signer server holding the main key instead of this process, see `RemoteSigner`.
+    pub remote_signer: Option<SignerEndpoint>,
+    pub remote_signer_token: Option<String>,
+    /// Key of `remote_signer` to use, unset takes its only key.
+    pub remote_signer_pubkey: Option<Pubkey>,
+    /// Directory of keypair files or a single wallets file, see `WalletFarm::load`.
+    pub farm_wallets: Option<String>,
+    pub target_wallet: Option<Pubkey>,
+    pub rpc_endpoint: String,
+    pub grpc_endpoint: Option<String>,
+    pub grpc_token: Option<String>,
+    pub laser_endpoint: Option<String>,
+    pub laser_token_key: Option<String>,
+    pub nozomi_api_key: Option<String>,
+    pub zero_slot_key: Option<String>,
+    pub jito_auth_key: Option<String>,
+    /// Submit the sell to every confirm service at once instead of only `CONFIRM_SERVICE`.
+    pub fan_out_sell: bool,
+    /// Take blockhashes from the Yellowstone blocks meta stream instead of polling the RPC.
+    pub blockhash_from_grpc: bool,
+    /// Pre-sign the sell against a durable nonce account owned by `PRIVATE_KEY`.
+    pub durable_nonce: bool,
+    /// Lamports of each farm buy bundled with the create, one per buyer wallet. Empty sends the
+    /// create on its own.
+    pub launch_buys: Vec<u64>,
+    /// JSON file with the copy-trade sizing, see `CopyTradeConfig`. Unset buys `BUY_SOL_AMOUNT`.
+    pub copy_trade_file: Option<String>,
+    /// JSON file with the sniper settings and filters, see `SniperConfig`.
+    pub sniper_config_file: Option<String>,
+    /// Append-only JSONL file the fills of our own trades are recorded in, see `Ledger`.
+    pub ledger_file: String,
+    pub token_metadata: Option<TokenMetadata>,
+    /// The base settings as `default` and every `[profiles.NAME]` on top of them.
+    pub profiles: BTreeMap<String, TradingProfile>,
+    /// Profile active at startup.
+    pub profile: String,
+    // Every setting as given, to tell which ones a reload changed
+    pub(crate) settings: BTreeMap<&'static str, String>,
+    // Values in the file that the environment overrides, to tell when editing them does nothing
+    pub(crate) shadowed: BTreeMap<&'static str, String>,
+}
+
+impl BotConfig {
+    /// Loads `BOT_CONFIG_FILE` (or `bot.toml` if present) with the environment on top.
+    pub fn from_env(required: &[&str]) -> ClientResult<Self> {
+        dotenv().ok();
+
+        let path = env::var("BOT_CONFIG_FILE")
+            .ok()
+            .filter(|path| !path.trim().is_empty())
+            .or_else(|| {
+                Path::new(DEFAULT_BOT_CONFIG_FILE)
+                    .exists()
+                    .then(|| DEFAULT_BOT_CONFIG_FILE.to_string())
+            });
+        let toml = match &path {
+            Some(path) => fs::read_to_string(path)
+                .map_err(|err| ClientError::Other(format!("{}: {}", path, err)))?,
+            None => String::new(),
+        };
+
+        Self::from_toml(&toml, |key| env::var(key).ok(), required).map(|config| Self {
+            source: path,
+            ..config
+        })
+    }
+
+    /// Parses `toml`, with `env(KEY)` overriding `key` when it returns a non-empty value.
+    pub fn from_toml(
+        toml: &str,
+        env: impl Fn(&str) -> Option<String>,
+        required: &[&str],
+    ) -> ClientResult<Self> {
+        let mut values = Values::default();
+
+        match toml.parse::<toml::Table>() {
+            Ok(table) => {
+                for (key, value) in table {
+                    match BOT_CONFIG_KEYS.iter().find(|known| **known == key) {
+                        Some(known) => values.insert_toml(known, value),
+                        None if key == "profiles" => values.insert_profiles(value),
+                        None => values.errors.push(format!("unknown setting '{}'", key)),
+                    }
+                }
+            }
+            Err(err) => values.errors.push(format!("invalid TOML: {}", err)),
+        }
+        for key in BOT_CONFIG_KEYS {
+            if let Some(value) = env(&key.to_uppercase()).filter(|value| !value.trim().is_empty())
+                && let Some(file_value) = values.values.insert(key, value)
+            {
+                values.shadowed.insert(key, file_value);
+            }
+        }
+
+        values.build(required)
+    }
+
+    /// The base settings.
+    pub fn default_profile(&self) -> &TradingProfile {
+        &self.profiles[DEFAULT_PROFILE]
+    }
+
+    pub fn token_metadata(&self) -> ClientResult<&TokenMetadata> {
+        self.token_metadata
+            .as_ref()
+            .ok_or_else(|| missing_setting("token_name"))
+    }
+}
+
+#[derive(Default)]
+struct Values {
+    values: BTreeMap<&'static str, String>,
+    shadowed: BTreeMap<&'static str, String>,
+    profiles: Vec<(String, Table)>,
+    errors: Vec<String>,
+    /// Set while checking a profile, whose errors name its table instead of the env var.
+    profile: Option<String>,
+}
+
+impl Values {
+    fn insert_toml(&mut self, key: &'static str, value: toml::Value) {
+        let value = match value {
+            toml::Value::String(value) => value,
+            toml::Value::Integer(value) => value.to_string(),
+            toml::Value::Float(value) => value.to_string(),
+            toml::Value::Boolean(value) => value.to_string(),
+            // Lists like `launch_buys = [0.5, 0.3]` read the same as the env var
+            toml::Value::Array(items) => items
+                .iter()
+                .map(|item| match item {
+                    toml::Value::String(item) => item.clone(),
+                    item => item.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+            other => {
+                self.errors.push(format!(
+                    "{}: expected a string, number, boolean or list, got {}",
+                    key,
+                    other.type_str()
+                ));
+                return;
+            }
+        };
+
+        self.values.insert(key, value);
+    }
+
+    fn insert_profiles(&mut self, value: toml::Value) {
+        let toml::Value::Table(profiles) = value else {
+            self.errors
+                .push("profiles: expected [profiles.NAME] tables".to_string());
+            return;
+        };
+
+        for (name, profile) in profiles {
+            match profile {
+                toml::Value::Table(profile) => self.profiles.push((name, profile)),
+                _ => self
+                    .errors
+                    .push(format!("profiles.{}: expected a table", name)),
+            }
+        }
+    }
+
+    fn string(&self, key: &str) -> Option<String> {
+        self.values.get(key).map(|value| value.trim().to_string())
+    }
+
+    fn error(&mut self, key: &str, message: impl Display) {
+        let key = match &self.profile {
+            Some(profile) => format!("profiles.{}.{}", profile, key),
+            None => key.to_uppercase(),
+        };
+        self.errors.push(format!("{}: {}", key, message));
+    }
+
+    fn parse<T: FromStr>(&mut self, key: &str) -> Option<T>
+    where
+        T::Err: Display,
+    {
+        let value = self.string(key)?;
+        match value.parse() {
+            Ok(parsed) => Some(parsed),
+            Err(err) => {
+                self.error(key, format_args!("invalid value '{}': {}", value, err));
+                None
+            }
+        }
+    }
+
+    fn flag(&mut self, key: &str) -> bool {
+        match self
+            .string(key)
+            .map(|value| value.to_lowercase())
+            .as_deref()
+        {
+            None | Some("0" | "false" | "no") => false,
+            Some("1" | "true" | "yes") => true,
+            Some(other) => {
+                self.error(key, format_args!("expected true or false, got '{}'", other));
+                false
+            }
+        }
+    }
+
+    fn url(&mut self, key: &str) -> Option<String> {
+        let value = self.string(key)?;
+        match Url::parse(&value) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => Some(value),
+            Ok(url) => {
+                self.error(
+                    key,
+                    format_args!("expected an http(s) URL, got {}", url.scheme()),
+                );
+                None
+            }
+            Err(err) => {
+                self.error(key, format_args!("invalid URL '{}': {}", value, err));
+                None
+            }
+        }
+    }
+
+    fn sol(&mut self, key: &str) -> Option<f64> {
+        let sol = self.parse::<f64>(key)?;
+        if !sol.is_finite() || sol <= 0.0 {
+            self.error(
+                key,
+                format_args!("must be a positive amount of SOL, got {}", sol),
+            );
+            return None;
+        }
+
+        Some(sol)
+    }
+
+    fn existing_path(&mut self, key: &str) -> Option<String> {
+        let path = self.string(key)?;
+        if !Path::new(&path).exists() {
+            self.error(key, format_args!("{} does not exist", path));
+        }
+
+        Some(path)
+    }
+
+    fn build(mut self, required: &[&str]) -> ClientResult<BotConfig> {
+        let mut checked = Vec::new();
+        for key in ALWAYS_REQUIRED.iter().chain(required) {
+            if checked.contains(key) {
+                continue;
+            }
+            checked.push(*key);
+            if !BOT_CONFIG_KEYS.contains(key) {
+                return Err(ClientError::InvalidData(format!(
+                    "unknown required setting '{}'",
+                    key
+                )));
+            }
+            if !self.values.contains_key(key) {
+                self.error(key, "must be set");
+            }
+        }
+
+        let private_key =
+            self.string("private_key")
+                .and_then(|value| match try_parse_keypair(&value) {
+                    Ok(keypair) => Some(keypair),
+                    Err(err) => {
+                        self.error("private_key", err);
+                        None
+                    }
+                });
+        let keystore_file = self.existing_path("keystore_file");
+        let keystore_key = self
+            .string("keystore_key")
+            .unwrap_or_else(|| DEFAULT_KEYSTORE_KEY.to_string());
+        let keystore_passphrase_file = self.existing_path("keystore_passphrase_file");
+        let remote_signer = self.parse::<SignerEndpoint>("remote_signer");
+        let remote_signer_pubkey = self.parse::<Pubkey>("remote_signer_pubkey");
+        let key_sources = ["private_key", "keystore_file", "remote_signer"]
+            .into_iter()
+            .filter(|key| self.values.contains_key(key))
+            .count();
+        match key_sources {
+            0 => self.error(
+                "private_key",
+                "must be set, or KEYSTORE_FILE or REMOTE_SIGNER",
+            ),
+            1 => {}
+            _ => self.error(
+                "private_key",
+                "set only one of PRIVATE_KEY, KEYSTORE_FILE and REMOTE_SIGNER",
+            ),
+        }
+        // The entry list is in the clear, a typo in the label fails before the passphrase
+        if key_sources == 1
+            && let Some(path) = &keystore_file
+            && Path::new(path).exists()
+        {
+            match Keystore::load(path) {
+                Ok(keystore) if keystore.find(&keystore_key).is_none() => self.error(
+                    "keystore_key",
+                    format_args!("{} has no entry '{}'", path, keystore_key),
+                ),
+                Ok(_) => {}
+                Err(err) => self.error("keystore_file", err),
+            }
+        }
+        let target_wallet = self.parse::<Pubkey>("target_wallet");
+        let rpc_endpoint = self.url("rpc_endpoint");
+        let grpc_endpoint = self.url("grpc_endpoint");
+        let laser_endpoint = self.url("laser_endpoint");
+        let fan_out_sell = self.flag("fan_out_sell");
+        let blockhash_from_grpc = self.flag("blockhash_from_grpc");
+        let durable_nonce = self.flag("durable_nonce");
+
+        let profiles = self.trading_profiles();
+        let profile = self
+            .string("profile")
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        if !profiles.contains_key(&profile) {
+            self.error("profile", format_args!("no profile named '{}'", profile));
+        }
+
+        // The services of every profile, or all of them when the sell fans out, need their key
+        let services: Vec<_> = if fan_out_sell {
+            ConfirmService::ALL.to_vec()
+        } else {
+            profiles
+                .values()
+                .filter_map(|profile| profile.confirm_service)
+                .collect()
+        };
+        for service in services {
+            let key = match service {
+                ConfirmService::Nozomi => "nozomi_api_key",
+                ConfirmService::ZeroSlot => "zero_slot_key",
+                // The auth key only raises the rate limit
+                ConfirmService::Jito => continue,
+            };
+            let message = format!("must be set to send through {}", service.as_str());
+            if !self.values.contains_key(key)
+                && !self.errors.iter().any(|err| err.ends_with(&message))
+            {
+                self.error(key, message);
+            }
+        }
+
+        let launch_buys: Vec<u64> = self
+            .string("launch_buys")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .filter_map(|value| match value.parse::<f64>() {
+                Ok(sol) if sol.is_finite() && sol > 0.0 => Some(to_lamports(sol)),
+                _ => {
+                    self.error("launch_buys", format_args!("invalid amount '{}'", value));
+                    None
+                }
+            })
+            .collect();
+        if launch_buys.len() > MAX_LAUNCH_BUYS {
+            self.error(
+                "launch_buys",
+                format_args!(
+                    "at most {} buys fit into the launch bundle, got {}",
+                    MAX_LAUNCH_BUYS,
+                    launch_buys.len()
+                ),
+            );
+        }
+
+        let farm_wallets = self.existing_path("farm_wallets");
+        let copy_trade_file = self.existing_path("copy_trade_file");
+        let sniper_config_file = self.existing_path("sniper_config_file");
+
+        // Name, symbol and URI come together or not at all
+        let token_keys = ["token_name", "token_symbol", "token_uri"];
+        let token_uri = self.url("token_uri");
+        let token_metadata = match (
+            self.string("token_name"),
+            self.string("token_symbol"),
+            token_uri,
+        ) {
+            (Some(name), Some(symbol), Some(uri)) => Some(TokenMetadata {
+                name,
+                symbol,
+                uri,
+                description: self.string("token_description").unwrap_or_default(),
+            }),
+            _ => {
+                let unset: Vec<_> = token_keys
+                    .into_iter()
+                    .filter(|key| !self.values.contains_key(key))
+                    .collect();
+                if unset.len() < token_keys.len() {
+                    for key in unset {
+                        self.error(key, "must be set with the other token settings");
+                    }
+                }
+                None
+            }
+        };
+
+        let (Some(rpc_endpoint), true) = (rpc_endpoint, self.errors.is_empty())
+        else {
+            return Err(ClientError::InvalidData(format!(
+                "invalid bot config:\n  - {}",
+                self.errors.join("\n  - ")
+            )));
+        };
+
+        Ok(BotConfig {
+            source: None,
+            private_key,
+            keystore_file,
+            keystore_key,
+            keystore_passphrase_file,
+            remote_signer,
+            remote_signer_token: self.string("remote_signer_token"),
+            remote_signer_pubkey,
+            farm_wallets,
+            target_wallet,
+            rpc_endpoint,
+            grpc_endpoint,
+            grpc_token: self.string("grpc_token"),
+            laser_endpoint,
+            laser_token_key: self.string("laser_token_key"),
+            nozomi_api_key: self.string("nozomi_api_key"),
+            zero_slot_key: self.string("zero_slot_key"),
+            jito_auth_key: self.string("jito_auth_key"),
+            fan_out_sell,
+            blockhash_from_grpc,
+            durable_nonce,
+            launch_buys,
+            copy_trade_file,
+            sniper_config_file,
+            ledger_file: self
+                .string("ledger_file")
+                .unwrap_or_else(|| DEFAULT_LEDGER_FILE.to_string()),
+            token_metadata,
+            profiles,
+            profile,
+            settings: self.values.clone(),
+            shadowed: std::mem::take(&mut self.shadowed),
+        })
+    }
+
+    /// The base settings as the default profile, then every profile table on top of them.
+    fn trading_profiles(&mut self) -> BTreeMap<String, TradingProfile> {
+        let mut profiles = BTreeMap::new();
+        profiles.insert(
+            DEFAULT_PROFILE.to_string(),
+            self.trading_profile(DEFAULT_PROFILE),
+        );
+
+        for (name, table) in std::mem::take(&mut self.profiles) {
+            if profiles.contains_key(&name) {
+                self.errors.push(format!(
+                    "profiles.{}: the name is reserved for the base settings",
+                    name
+                ));
+                continue;
+            }
+
+            let mut values = Values {
+                values: self.values.clone(),
+                profile: Some(name.clone()),
+                ..Default::default()
+            };
+            for (key, value) in table {
+                match PROFILE_KEYS.iter().find(|known| **known == key) {
+                    Some(known) => values.insert_toml(known, value),
+                    None => values.error(&key, "not a profile setting"),
+                }
+            }
+
+            let profile = values.trading_profile(&name);
+            self.errors.append(&mut values.errors);
+            profiles.insert(name, profile);
+        }
+
+        profiles
+    }
+
+    fn trading_profile(&mut self, name: &str) -> TradingProfile {
+        let confirm_service = self.parse::<ConfirmService>("confirm_service");
+
+        let cu = self.parse::<u64>("cu").unwrap_or(0);
+        if cu > MAX_COMPUTE_UNITS {
+            self.error(
+                "cu",
+                format_args!("must be at most {}, got {}", MAX_COMPUTE_UNITS, cu),
+            );
+        }
+        let priority_fee_micro_lamport =
+            self.parse::<u64>("priority_fee_micro_lamport").unwrap_or(0);
+        if priority_fee_micro_lamport > 0 {
+            let fee_sol = cu as f64 * priority_fee_micro_lamport as f64
+                / 1_000_000.0
+                / LAMPORTS_PER_SOL as f64;
+            if cu == 0 {
+                self.error(
+                    "priority_fee_micro_lamport",
+                    "needs CU, the fee is paid per compute unit of the limit",
+                );
+            } else if fee_sol > MAX_PRIORITY_FEE_SOL {
+                self.error(
+                    "priority_fee_micro_lamport",
+                    format_args!(
+                        "pays {} SOL per transaction at CU {}, more than {} SOL",
+                        fee_sol, cu, MAX_PRIORITY_FEE_SOL
+                    ),
+                );
+            }
+        }
+        let third_party_fee = self.parse::<f64>("third_party_fee").unwrap_or(0.0);
+        if !third_party_fee.is_finite() || !(0.0..=MAX_TIP_SOL).contains(&third_party_fee) {
+            self.error(
+                "third_party_fee",
+                format_args!(
+                    "must be between 0 and {} SOL, got {}",
+                    MAX_TIP_SOL, third_party_fee
+                ),
+            );
+        }
+
+        let buy_sol_amount = self.sol("buy_sol_amount").map(to_lamports);
+        let slippage = self.parse::<f64>("slippage").unwrap_or(1.0);
+        if !(0.0..100.0).contains(&slippage) {
+            self.error(
+                "slippage",
+                format_args!("must be in [0, 100) percent, got {}", slippage),
+            );
+        }
+
+        TradingProfile {
+            name: name.to_string(),
+            buy_sol_amount,
+            slippage: slippage / 100.0,
+            cu,
+            priority_fee_micro_lamport,
+            confirm_service,
+            third_party_fee,
+            sell_strategy_file: self.existing_path("sell_strategy_file"),
+        }
+    }
+}
+
+fn to_lamports(sol: f64) -> u64 {
+    (sol * LAMPORTS_PER_SOL as f64) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signer::Signer;
+
+    const RPC: &str = "https://api.mainnet-beta.solana.com";
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    fn base_toml() -> String {
+        format!(
+            "private_key = \"{}\"\nrpc_endpoint = \"{}\"\n",
+            Keypair::new().to_base58_string(),
+            RPC
+        )
+    }
+
+    fn errors(toml: &str, required: &[&str]) -> String {
+        match BotConfig::from_toml(toml, no_env, required) {
+            Ok(_) => panic!("expected an invalid config"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_settings_and_profiles() {
+        let toml = format!(
+            r#"{}
+buy_sol_amount = 0.5
+slippage = 2.0
+launch_buys = [0.5, 0.25]
+durable_nonce = true
+profile = "fast"
+
+[profiles.fast]
+slippage = 10
+cu = 200000
+priority_fee_micro_lamport = 1000
+"#,
+            base_toml()
+        );
+        let config = BotConfig::from_toml(&toml, no_env, &[]).unwrap();
+
+        assert_eq!(config.rpc_endpoint, RPC);
+        assert!(config.private_key.is_some());
+        assert_eq!(config.launch_buys, [500_000_000, 250_000_000]);
+        assert!(config.durable_nonce);
+        assert!(!config.fan_out_sell);
+        assert_eq!(config.profile, "fast");
+
+        let default = config.default_profile();
+        assert_eq!(default.buy_sol_amount, Some(500_000_000));
+        assert_eq!(default.slippage, 0.02);
+        assert_eq!(default.cu, 0);
+
+        // Profiles start from the base settings
+        let fast = &config.profiles["fast"];
+        assert_eq!(fast.buy_sol_amount, Some(500_000_000));
+        assert_eq!(fast.slippage, 0.1);
+        assert_eq!(fast.cu, 200_000);
+        assert_eq!(fast.priority_fee_micro_lamport, 1000);
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let toml = format!(
+            "{}rpc_endpont = \"{}\"\n\n[profiles.fast]\nfan_out_sell = true\n",
+            base_toml(),
+            RPC
+        );
+        let err = errors(&toml, &[]);
+
+        assert!(err.contains("unknown setting 'rpc_endpont'"), "{}", err);
+        assert!(
+            err.contains("profiles.fast.fan_out_sell: not a profile setting"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn reports_every_missing_required_key() {
+        let err = errors("", &["grpc_endpoint", "confirm_service"]);
+
+        for key in ["RPC_ENDPOINT", "GRPC_ENDPOINT", "CONFIRM_SERVICE"] {
+            assert!(err.contains(&format!("{}: must be set", key)), "{}", err);
+        }
+        assert!(
+            err.contains("PRIVATE_KEY: must be set, or KEYSTORE_FILE or REMOTE_SIGNER"),
+            "{}",
+            err
+        );
+
+        let err = errors(&base_toml(), &["rpc_endpont"]);
+        assert!(
+            err.contains("unknown required setting 'rpc_endpont'"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn env_overrides_the_file() {
+        let keypair = Keypair::new();
+        let env = |key: &str| match key {
+            "SLIPPAGE" => Some("5".to_string()),
+            "PRIVATE_KEY" => Some(keypair.to_base58_string()),
+            // Blank values leave the file setting in place
+            "RPC_ENDPOINT" => Some("  ".to_string()),
+            _ => None,
+        };
+        let toml = format!("{}slippage = 2.0\n", base_toml());
+        let config = BotConfig::from_toml(&toml, env, &[]).unwrap();
+
+        assert_eq!(config.default_profile().slippage, 0.05);
+        assert_eq!(
+            config.private_key.as_ref().map(Keypair::pubkey),
+            Some(keypair.pubkey())
+        );
+        assert_eq!(config.rpc_endpoint, RPC);
+        assert_eq!(config.settings["slippage"], "5");
+        assert_eq!(config.shadowed["slippage"], "2");
+        assert!(config.shadowed.contains_key("private_key"));
+        assert!(!config.shadowed.contains_key("rpc_endpoint"));
+    }
+}
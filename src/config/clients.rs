@@ -1,34 +1,97 @@
-use dotenvy::dotenv;
-use std::env;
+use std::{str::FromStr, sync::Arc};
 use tokio::sync::OnceCell;
 
-use crate::service::{Jito, Nozomi, ZeroSlot};
+use crate::{
+    config::{bot_config, missing_setting},
+    error::{ClientError, ClientResult},
+    service::{Jito, Nozomi, TxSender, ZeroSlot},
+};
 
-pub static NOZOMI_CLIENT: OnceCell<Nozomi> = OnceCell::const_new();
-pub static ZSLOT_CLIENT: OnceCell<ZeroSlot> = OnceCell::const_new();
-pub static JITO_CLIENT: OnceCell<Jito> = OnceCell::const_new();
+pub static NOZOMI_CLIENT: OnceCell<Arc<Nozomi>> = OnceCell::const_new();
+pub static ZSLOT_CLIENT: OnceCell<Arc<ZeroSlot>> = OnceCell::const_new();
+pub static JITO_CLIENT: OnceCell<Arc<Jito>> = OnceCell::const_new();
 
-pub async fn init_nozomi() {
-    dotenv().ok();
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfirmService {
+    Nozomi,
+    ZeroSlot,
+    Jito,
+}
+
+impl ConfirmService {
+    pub const ALL: [ConfirmService; 3] = [Self::Jito, Self::Nozomi, Self::ZeroSlot];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Nozomi => "NOZOMI",
+            Self::ZeroSlot => "ZERO_SLOT",
+            Self::Jito => "JITO",
+        }
+    }
+}
 
-    let nozomi_api_key = env::var("NOZOMI_API_KEY").expect("NOZOMI_API_KEY not set in .env");
+impl FromStr for ConfirmService {
+    type Err = ClientError;
 
-    let nozomi = Nozomi::new_auto(nozomi_api_key).await;
-    nozomi.health_check(50);
-    NOZOMI_CLIENT.set(nozomi).unwrap();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "NOZOMI" => Ok(Self::Nozomi),
+            "ZERO_SLOT" => Ok(Self::ZeroSlot),
+            "JITO" => Ok(Self::Jito),
+            other => Err(ClientError::InvalidData(format!(
+                "unsupported CONFIRM_SERVICE '{}', expected NOZOMI, JITO or ZERO_SLOT",
+                other
+            ))),
+        }
+    }
 }
 
-pub async fn init_zslot() {
-    dotenv().ok();
+pub async fn init_nozomi() -> ClientResult<Arc<Nozomi>> {
+    NOZOMI_CLIENT
+        .get_or_try_init(|| async {
+            let nozomi_api_key = bot_config()?
+                .nozomi_api_key
+                .clone()
+                .ok_or_else(|| missing_setting("nozomi_api_key"))?;
 
-    let zslot_api_key = env::var("ZERO_SLOT_KEY").expect("ZERO_SLOT_KEY not set in .env");
+            let nozomi = Nozomi::new_auto(nozomi_api_key).await;
+            nozomi.health_check(50);
+            Ok(Arc::new(nozomi))
+        })
+        .await
+        .cloned()
+}
+
+pub async fn init_zslot() -> ClientResult<Arc<ZeroSlot>> {
+    ZSLOT_CLIENT
+        .get_or_try_init(|| async {
+            let zslot_api_key = bot_config()?
+                .zero_slot_key
+                .clone()
+                .ok_or_else(|| missing_setting("zero_slot_key"))?;
 
-    let zslot = ZeroSlot::new_auto(zslot_api_key).await;
-    ZSLOT_CLIENT.set(zslot).unwrap();
+            Ok(Arc::new(ZeroSlot::new_auto(zslot_api_key).await))
+        })
+        .await
+        .cloned()
 }
 
-pub async fn init_jito() {
-    let jito = Jito::new_auto(None).await;
-    JITO_CLIENT.set(jito).unwrap();
+pub async fn init_jito() -> ClientResult<Arc<Jito>> {
+    JITO_CLIENT
+        .get_or_try_init(|| async {
+            let auth_key = bot_config()?.jito_auth_key.clone();
+
+            Ok(Arc::new(Jito::new_auto(auth_key).await))
+        })
+        .await
+        .cloned()
 }
 
+/// Initializes (once) and returns the client for `service`.
+pub async fn tx_sender(service: ConfirmService) -> ClientResult<Arc<dyn TxSender>> {
+    Ok(match service {
+        ConfirmService::Nozomi => init_nozomi().await?,
+        ConfirmService::ZeroSlot => init_zslot().await?,
+        ConfirmService::Jito => init_jito().await?,
+    })
+}
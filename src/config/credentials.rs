@@ -1,126 +1,192 @@
-use dotenvy::dotenv;
-use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     pubkey::Pubkey,
     signer::{keypair::Keypair, Signer},
 };
-use std::{env, panic, str::FromStr, sync::Arc};
+use std::{fs, panic, path::Path, sync::Arc};
+use zeroize::Zeroizing;
+
+use crate::{
+    config::{bot_config, missing_setting},
+    error::{ClientError, ClientResult},
+    keystore::{Keystore, UnlockedKeystore},
+    signer::{KeystoreSigner, RemoteSigner, TxSigner},
+};
 
-fn try_parse_keypair(input: &str) -> Result<Keypair, String> {
+/// Parses a keypair given as JSON bytes, base64 or base58.
+pub fn try_parse_keypair(input: &str) -> Result<Keypair, String> {
     // Try JSON array of bytes
     if let Ok(bytes) = serde_json::from_str::<Vec<u8>>(input) {
         return Keypair::from_bytes(&bytes).map_err(|e| format!("invalid keypair bytes: {}", e));
     }
 
-    // Try base64-encoded bytes
-    if let Ok(bytes) = base64::decode(input) {
-        return Keypair::from_bytes(&bytes).map_err(|e| format!("invalid base64 keypair: {}", e));
+    // Try base64-encoded bytes. Base58 strings often decode as base64 too, so only a valid
+    // keypair counts
+    if let Ok(Ok(keypair)) = base64::decode(input).map(|bytes| Keypair::from_bytes(&bytes)) {
+        return Ok(keypair);
     }
 
     // Try base58-encoded keypair (guard against panics)
     match panic::catch_unwind(|| Keypair::from_base58_string(input)) {
         Ok(kp) => Ok(kp),
         Err(_) => Err(
-            "unsupported PRIVATE_KEY format; expected base58 (64-byte), JSON [u8;64], or base64"
+            "unsupported keypair format; expected base58 (64-byte), JSON [u8;64], or base64"
                 .to_string(),
         ),
     }
 }
 
-fn load_keypair_from_env() -> Keypair {
-    dotenv().ok();
-
-    let private_key = match env::var("PRIVATE_KEY") {
-        Ok(v) => v,
-        Err(_) => {
-            eprintln!(
-                "Error: PRIVATE_KEY must be set (base58 64-byte keypair, JSON [u8;64], or base64)"
+/// Reads a passphrase from the first line of `file`, or prompts for it on the terminal.
+pub fn read_passphrase(file: Option<&str>, prompt: &str) -> ClientResult<Zeroizing<String>> {
+    let passphrase = match file {
+        Some(path) => {
+            warn_if_shared(Path::new(path));
+            let contents = Zeroizing::new(
+                fs::read_to_string(path)
+                    .map_err(|err| ClientError::Other(format!("{}: {}", path, err)))?,
             );
-            std::process::exit(1);
+            Zeroizing::new(contents.lines().next().unwrap_or_default().to_string())
         }
+        None => Zeroizing::new(rpassword::prompt_password(prompt).map_err(|err| {
+            ClientError::Other(format!(
+                "could not read the passphrase, set KEYSTORE_PASSPHRASE_FILE without a terminal: {}",
+                err
+            ))
+        })?),
     };
-
-    match try_parse_keypair(&private_key) {
-        Ok(kp) => kp,
-        Err(why) => {
-            eprintln!("Error: Invalid PRIVATE_KEY: {}", why);
-            std::process::exit(1);
-        }
+    if passphrase.is_empty() {
+        return Err(ClientError::InvalidInput(
+            "the keystore passphrase is empty",
+        ));
     }
-}
-
-pub static PRIVATE_KEY: Lazy<Keypair> = Lazy::new(load_keypair_from_env);
-pub static PUBKEY: Lazy<Pubkey> = Lazy::new(|| PRIVATE_KEY.pubkey());
-
-pub static TARGET_WALLET: Lazy<Pubkey> = Lazy::new(|| {
-    dotenv().ok();
 
-    let target_wallet = match env::var("TARGET_WALLET") {
-        Ok(v) => v,
-        Err(_) => {
-            eprintln!("Error: TARGET_WALLET must be set (base58 pubkey)");
-            std::process::exit(1);
-        }
-    };
+    Ok(passphrase)
+}
 
-    match Pubkey::from_str(&target_wallet) {
-        Ok(pk) => pk,
-        Err(_) => {
-            eprintln!("Error: TARGET_WALLET must be a valid base58 pubkey");
-            std::process::exit(1);
-        }
+#[cfg(unix)]
+fn warn_if_shared(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Ok(meta) = fs::metadata(path)
+        && meta.permissions().mode() & 0o077 != 0
+    {
+        eprintln!(
+            "Warning: {} is readable by other users, chmod 600 it",
+            path.display()
+        );
     }
-});
-
-pub static RPC_ENDPOINT: Lazy<String> = Lazy::new(|| {
-    dotenv().ok();
-
-    let rpc_endpoint = env::var("RPC_ENDPOINT").expect("RPC_ENDPOINT must be set");
-
-    rpc_endpoint
-});
-
-pub static RPC_CLIENT: Lazy<Arc<RpcClient>> = Lazy::new(|| {
-    dotenv().ok();
-
-    let rpc_endpoint = env::var("RPC_ENDPOINT").expect("RPC_ENDPOINT must be set");
+}
 
-    Arc::new(RpcClient::new_with_commitment(
-        rpc_endpoint,
-        CommitmentConfig::processed(),
-    ))
-});
+#[cfg(not(unix))]
+fn warn_if_shared(_path: &Path) {}
 
-pub static LASER_ENDPOINT: Lazy<String> = Lazy::new(|| {
-    dotenv().ok();
+static KEYSTORE_PASSPHRASE: OnceCell<Zeroizing<String>> = OnceCell::new();
 
-    let laser_endpoint = env::var("LASER_ENDPOINT").expect("LASER_ENDPOINT must be set");
+/// Unlocks the keystore at `path` with the passphrase of `KEYSTORE_PASSPHRASE_FILE` or, without
+/// one, a prompt. The passphrase is asked for once and reused for every keystore.
+pub fn unlock_keystore(path: impl AsRef<Path>) -> ClientResult<UnlockedKeystore> {
+    let path = path.as_ref();
+    let keystore = Keystore::load(path)?;
+    let passphrase = KEYSTORE_PASSPHRASE.get_or_try_init(|| {
+        read_passphrase(
+            bot_config()?.keystore_passphrase_file.as_deref(),
+            &format!("Passphrase of {}: ", path.display()),
+        )
+    })?;
 
-    laser_endpoint
-});
+    keystore.unlock(passphrase)
+}
 
-pub static LASER_TOKEN_KEY: Lazy<String> = Lazy::new(|| {
-    dotenv().ok();
+/// The main key: `PRIVATE_KEY`, the `KEYSTORE_KEY` entry of `KEYSTORE_FILE` or the key of the
+/// `REMOTE_SIGNER` server.
+pub fn load_tx_signer() -> ClientResult<Arc<dyn TxSigner>> {
+    let config = bot_config()?;
+    if let Some(keypair) = &config.private_key {
+        return Ok(Arc::new(keypair.insecure_clone()));
+    }
+    if let Some(endpoint) = &config.remote_signer {
+        return Ok(Arc::new(RemoteSigner::connect(
+            endpoint.clone(),
+            config.remote_signer_token.clone(),
+            config.remote_signer_pubkey,
+        )?));
+    }
 
-    let laser_token_key = env::var("LASER_TOKEN_KEY").expect("LASER_TOKEN_KEY must be set");
+    let path = config
+        .keystore_file
+        .as_deref()
+        .ok_or(ClientError::InvalidInput(
+            "set PRIVATE_KEY, KEYSTORE_FILE or REMOTE_SIGNER",
+        ))?;
+    let keystore = Arc::new(unlock_keystore(path)?);
+
+    Ok(Arc::new(KeystoreSigner::new(
+        keystore,
+        &config.keystore_key,
+    )?))
+}
 
-    laser_token_key
-});
+static TX_SIGNER: OnceCell<Arc<dyn TxSigner>> = OnceCell::new();
+
+/// The main key, loaded on first use. Binaries load it at startup so a prompt never comes
+/// mid-trade.
+pub fn tx_signer() -> ClientResult<Arc<dyn TxSigner>> {
+    TX_SIGNER
+        .get_or_try_init(|| {
+            let signer = load_tx_signer()?;
+            println!("🔑 Signing as {} ({})", signer.pubkey(), signer.location());
+            Ok(signer)
+        })
+        .cloned()
+}
 
-pub static GRPC_ENDPOINT: Lazy<String> = Lazy::new(|| {
-    dotenv().ok();
+/// Wallet the copy_trade binary mirrors.
+pub fn target_wallet() -> ClientResult<Pubkey> {
+    bot_config()?
+        .target_wallet
+        .ok_or_else(|| missing_setting("target_wallet"))
+}
 
-    let grpc_endpoint = env::var("GRPC_ENDPOINT").expect("GRPC_ENDPOINT must be set");
+static RPC_CLIENT: OnceCell<Arc<RpcClient>> = OnceCell::new();
+
+/// Client of `RPC_ENDPOINT`, created on first use.
+pub fn rpc_client() -> ClientResult<Arc<RpcClient>> {
+    RPC_CLIENT
+        .get_or_try_init(|| {
+            Ok(Arc::new(RpcClient::new_with_commitment(
+                bot_config()?.rpc_endpoint.clone(),
+                CommitmentConfig::processed(),
+            )))
+        })
+        .cloned()
+}
 
-    grpc_endpoint
-});
+pub fn laser_endpoint() -> ClientResult<String> {
+    bot_config()?
+        .laser_endpoint
+        .clone()
+        .ok_or_else(|| missing_setting("laser_endpoint"))
+}
 
-pub static GRPC_TOKEN: Lazy<String> = Lazy::new(|| {
-    dotenv().ok();
+pub fn laser_token_key() -> ClientResult<String> {
+    bot_config()?
+        .laser_token_key
+        .clone()
+        .ok_or_else(|| missing_setting("laser_token_key"))
+}
 
-    let grpc_token = env::var("GRPC_TOKEN").expect("GRPC_TOKEN must be set");
+pub fn grpc_endpoint() -> ClientResult<String> {
+    bot_config()?
+        .grpc_endpoint
+        .clone()
+        .ok_or_else(|| missing_setting("grpc_endpoint"))
+}
 
-    grpc_token
-});
+pub fn grpc_token() -> ClientResult<String> {
+    bot_config()?
+        .grpc_token
+        .clone()
+        .ok_or_else(|| missing_setting("grpc_token"))
+}
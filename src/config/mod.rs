@@ -1,7 +1,13 @@
+pub mod bot_config;
 pub mod clients;
 pub mod credentials;
+pub mod profiles;
+pub mod reload;
 pub mod trade_setting;
 
+pub use bot_config::*;
 pub use clients::*;
 pub use credentials::*;
+pub use profiles::*;
+pub use reload::*;
 pub use trade_setting::*;
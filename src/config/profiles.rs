@@ -0,0 +1,260 @@
+use once_cell::sync::OnceCell;
+use solana_sdk::{instruction::Instruction, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, RwLock},
+};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use crate::{
+    config::{ConfirmService, bot_config, notify_settings_changed, tx_sender},
+    error::{ClientError, ClientResult},
+    service::{Tips, TxSender},
+};
+
+/// Name of the profile made of the base settings.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Trade settings bundled under a name, the base settings with a `[profiles.NAME]` table on top.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradingProfile {
+    pub name: String,
+    /// Lamports of a buy.
+    pub buy_sol_amount: Option<u64>,
+    /// Fraction, `slippage` is given in percent.
+    pub slippage: f64,
+    pub cu: u64,
+    pub priority_fee_micro_lamport: u64,
+    pub confirm_service: Option<ConfirmService>,
+    /// Relayer tip in SOL.
+    pub third_party_fee: f64,
+    pub sell_strategy_file: Option<String>,
+}
+
+impl TradingProfile {
+    pub fn slippage_pct(&self) -> f64 {
+        self.slippage * 100.0
+    }
+
+    pub fn buy_sol_amount(&self) -> ClientResult<u64> {
+        self.buy_sol_amount.ok_or_else(|| {
+            ClientError::InvalidData(format!("profile '{}' has no buy_sol_amount", self.name))
+        })
+    }
+
+    /// Compute budget and tip of the profile around `ixs`, paid by `payer`.
+    pub fn tips(&self, payer: Pubkey, ixs: Vec<Instruction>) -> Tips {
+        Tips {
+            tip_sol_amount: self.third_party_fee,
+            tip_addr_idx: 0,
+            cu: (self.cu > 0).then_some(self.cu),
+            priority_fee_micro_lamport: (self.priority_fee_micro_lamport > 0)
+                .then_some(self.priority_fee_micro_lamport),
+            payer,
+            pure_ix: ixs,
+        }
+    }
+
+    /// Wraps `ixs` with the compute budget and the tip `sender` takes.
+    pub fn with_tip(
+        &self,
+        sender: &dyn TxSender,
+        payer: Pubkey,
+        ixs: Vec<Instruction>,
+    ) -> Vec<Instruction> {
+        sender.add_tip_ix(Tips {
+            tip_addr_idx: sender.random_tip_idx(),
+            ..self.tips(payer, ixs)
+        })
+    }
+
+    /// Lamports `sender` is tipped per transaction.
+    pub fn tip_lamports(&self, sender: &dyn TxSender) -> u64 {
+        sender.tip_lamports(self.third_party_fee)
+    }
+
+    /// Initializes (once) and returns the client of the profile's confirm service.
+    pub async fn tx_sender(&self) -> ClientResult<Arc<dyn TxSender>> {
+        let service = self.confirm_service.ok_or_else(|| {
+            ClientError::InvalidData(format!("profile '{}' has no confirm_service", self.name))
+        })?;
+
+        tx_sender(service).await
+    }
+
+    /// Every setting with its value as written in the config, for logging what a reload changed.
+    pub fn settings(&self) -> Vec<(&'static str, String)> {
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+
+        vec![
+            (
+                "buy_sol_amount",
+                optional(
+                    self.buy_sol_amount
+                        .map(|lamports| (lamports as f64 / LAMPORTS_PER_SOL as f64).to_string()),
+                ),
+            ),
+            ("slippage", self.slippage_pct().to_string()),
+            ("cu", self.cu.to_string()),
+            (
+                "priority_fee_micro_lamport",
+                self.priority_fee_micro_lamport.to_string(),
+            ),
+            (
+                "confirm_service",
+                optional(
+                    self.confirm_service
+                        .map(|service| service.as_str().to_string()),
+                ),
+            ),
+            ("third_party_fee", self.third_party_fee.to_string()),
+            (
+                "sell_strategy_file",
+                optional(self.sell_strategy_file.clone()),
+            ),
+        ]
+    }
+}
+
+impl fmt::Display for TradingProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.name)?;
+        if let Some(lamports) = self.buy_sol_amount {
+            write!(f, "buy {} SOL, ", lamports as f64 / LAMPORTS_PER_SOL as f64)?;
+        }
+        write!(
+            f,
+            "slippage {}%, cu {}, priority fee {}, tip {} SOL via {}",
+            self.slippage_pct(),
+            self.cu,
+            self.priority_fee_micro_lamport,
+            self.third_party_fee,
+            self.confirm_service.map_or("-", |service| service.as_str())
+        )?;
+        if let Some(path) = &self.sell_strategy_file {
+            write!(f, ", strategy {}", path)?;
+        }
+
+        Ok(())
+    }
+}
+
+struct ProfileState {
+    profiles: BTreeMap<String, TradingProfile>,
+    active: String,
+}
+
+// One lock for both so a reload never pairs new profiles with a removed active name
+static PROFILE_STATE: OnceCell<RwLock<ProfileState>> = OnceCell::new();
+
+// Profiles of the loaded config until a reload swaps them
+fn profile_state() -> ClientResult<&'static RwLock<ProfileState>> {
+    PROFILE_STATE.get_or_try_init(|| {
+        let config = bot_config()?;
+
+        Ok(RwLock::new(ProfileState {
+            profiles: config.profiles.clone(),
+            active: config.profile.clone(),
+        }))
+    })
+}
+
+pub fn trading_profile(name: &str) -> ClientResult<TradingProfile> {
+    profile_state()?
+        .read()
+        .unwrap()
+        .profiles
+        .get(name)
+        .cloned()
+        .ok_or_else(|| ClientError::InvalidData(format!("no profile named '{}'", name)))
+}
+
+/// All profiles, ordered by name.
+pub fn trading_profiles() -> ClientResult<Vec<TradingProfile>> {
+    Ok(profile_state()?
+        .read()
+        .unwrap()
+        .profiles
+        .values()
+        .cloned()
+        .collect())
+}
+
+/// Profile used by trades not pinned to one, `PROFILE` until switched.
+pub fn active_profile() -> ClientResult<TradingProfile> {
+    let state = profile_state()?.read().unwrap();
+
+    Ok(state.profiles[&state.active].clone())
+}
+
+/// Switches the active profile, later trades pick it up without a restart.
+pub fn set_active_profile(name: &str) -> ClientResult<TradingProfile> {
+    let profile = {
+        let mut state = profile_state()?.write().unwrap();
+        let profile = state
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ClientError::InvalidData(format!("no profile named '{}'", name)))?;
+        state.active = profile.name.clone();
+        profile
+    };
+    notify_settings_changed();
+
+    Ok(profile)
+}
+
+/// Replaces every profile at once, switching to `switch_to` or keeping the active profile.
+/// Returns the previous profiles and active name, or an error if the active one would be gone.
+pub(crate) fn swap_profiles(
+    profiles: BTreeMap<String, TradingProfile>,
+    switch_to: Option<String>,
+) -> ClientResult<(BTreeMap<String, TradingProfile>, String)> {
+    let mut state = profile_state()?.write().unwrap();
+    let active = switch_to.unwrap_or_else(|| state.active.clone());
+    if !profiles.contains_key(&active) {
+        return Err(ClientError::InvalidData(format!(
+            "the active profile '{}' is gone, switch to another one first",
+            active
+        )));
+    }
+
+    let old_profiles = std::mem::replace(&mut state.profiles, profiles);
+    let old_active = std::mem::replace(&mut state.active, active);
+
+    Ok((old_profiles, old_active))
+}
+
+/// Reads `profile NAME` and `profiles` commands from stdin to switch the active profile.
+pub fn spawn_profile_switcher() {
+    tokio::spawn(async {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some("profile"), Some(name)) => match set_active_profile(name) {
+                    Ok(profile) => println!("🔀 Switched to profile {}", profile),
+                    Err(err) => eprintln!("{}", err),
+                },
+                (Some("profile"), None) => match active_profile() {
+                    Ok(profile) => println!("Active profile {}", profile),
+                    Err(err) => eprintln!("{}", err),
+                },
+                (Some("profiles"), _) => match active_profile()
+                    .and_then(|active| Ok((active.name, trading_profiles()?)))
+                {
+                    Ok((active, profiles)) => {
+                        for profile in profiles {
+                            let marker = if profile.name == active { "*" } else { " " };
+                            println!("{} {}", marker, profile);
+                        }
+                    }
+                    Err(err) => eprintln!("{}", err),
+                },
+                (None, _) => {}
+                _ => eprintln!("Unknown command, expected `profile NAME` or `profiles`"),
+            }
+        }
+    });
+}
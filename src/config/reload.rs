@@ -0,0 +1,287 @@
+use once_cell::sync::{Lazy, OnceCell};
+use std::{collections::BTreeMap, fs, sync::Mutex, time::SystemTime};
+use tokio::{
+    sync::watch,
+    time::{Duration, interval},
+};
+
+use crate::{
+    config::{
+        PROFILE_KEYS, TradingProfile, active_profile, bot_config, load_bot_config, swap_profiles,
+    },
+    error::{ClientError, ClientResult},
+    strategy::StrategyConfig,
+};
+
+// How often the config file is checked for changes
+const WATCH_INTERVAL_MS: u64 = 1000;
+
+static SETTINGS_VERSION: Lazy<watch::Sender<u64>> = Lazy::new(|| watch::channel(0).0);
+
+struct LoadedSettings {
+    settings: BTreeMap<&'static str, String>,
+    shadowed: BTreeMap<&'static str, String>,
+}
+
+// Settings of the last accepted load, to tell what the next one changes
+static LOADED_SETTINGS: OnceCell<Mutex<LoadedSettings>> = OnceCell::new();
+
+fn loaded_settings() -> ClientResult<&'static Mutex<LoadedSettings>> {
+    LOADED_SETTINGS.get_or_try_init(|| {
+        let config = bot_config()?;
+
+        Ok(Mutex::new(LoadedSettings {
+            settings: config.settings.clone(),
+            shadowed: config.shadowed.clone(),
+        }))
+    })
+}
+
+/// Fires whenever a reload or a profile switch changes the trade settings.
+pub fn settings_changed() -> watch::Receiver<u64> {
+    SETTINGS_VERSION.subscribe()
+}
+
+pub(crate) fn notify_settings_changed() {
+    SETTINGS_VERSION.send_modify(|version| *version += 1);
+}
+
+/// Loads and validates the config again and swaps in its profiles, returning what changed.
+///
+/// Only the trading profiles are swapped, the other settings are used once at startup and
+/// changes to them are reported as needing a restart. Edits of keys the environment overrides
+/// are reported as having no effect. An invalid config is rejected as a whole.
+pub fn reload_config() -> ClientResult<Vec<String>> {
+    let config = load_bot_config()?;
+    for profile in config.profiles.values() {
+        StrategyConfig::from_profile(profile).map_err(|err| {
+            ClientError::InvalidData(format!("profile '{}': {}", profile.name, err))
+        })?;
+    }
+
+    let mut loaded = loaded_settings()?.lock().unwrap();
+    // Editing `profile` switches to it, otherwise a profile switched to at runtime stays active
+    let switch_to = (loaded.settings.get("profile") != config.settings.get("profile"))
+        .then(|| config.profile.clone());
+    let (old_profiles, old_active) = swap_profiles(config.profiles.clone(), switch_to)?;
+
+    let mut changes = profile_changes(&old_profiles, &config.profiles);
+    let active = active_profile()?.name;
+    if active != old_active {
+        changes.push(format!("active profile: {} -> {}", old_active, active));
+    }
+    for key in restart_changes(&loaded.settings, &config.settings) {
+        changes.push(format!("{} changed, restart to apply", key.to_uppercase()));
+    }
+    if !changes.is_empty() {
+        notify_settings_changed();
+    }
+
+    // Without the values since they may hold secrets
+    for (key, value) in &config.shadowed {
+        if loaded.shadowed.get(key) != Some(value) {
+            changes.push(format!(
+                "{} edited in the file but overridden by the environment, no effect",
+                key.to_uppercase()
+            ));
+        }
+    }
+    *loaded = LoadedSettings {
+        settings: config.settings,
+        shadowed: config.shadowed,
+    };
+
+    Ok(changes)
+}
+
+/// Reloads the config on SIGHUP (on unix) and whenever the config file is modified. A rejected
+/// reload is logged and the current settings stay in effect.
+pub fn spawn_config_reloader() -> ClientResult<()> {
+    let path = bot_config()?.source.clone();
+    let modified_at = move || {
+        path.as_ref()
+            .and_then(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+    };
+
+    tokio::spawn(async move {
+        let mut hangup = Hangup::new();
+        let mut modified: Option<SystemTime> = modified_at();
+        let mut ticker = interval(Duration::from_millis(WATCH_INTERVAL_MS));
+
+        loop {
+            let trigger = tokio::select! {
+                _ = hangup.recv() => "SIGHUP",
+                _ = ticker.tick() => {
+                    let now = modified_at();
+                    if now == modified {
+                        continue;
+                    }
+                    modified = now;
+                    "file change"
+                }
+            };
+
+            match reload_config() {
+                Ok(changes) if changes.is_empty() => {
+                    println!("🔄 Config reloaded on {}, nothing changed", trigger)
+                }
+                Ok(changes) => {
+                    println!("🔄 Config reloaded on {}:", trigger);
+                    for change in changes {
+                        println!("  {}", change);
+                    }
+                }
+                Err(err) => eprintln!(
+                    "⚠️ Config reload on {} rejected, keeping the current config: {}",
+                    trigger, err
+                ),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+// SIGHUP on unix, never fires elsewhere or when it could not be listened for
+struct Hangup {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Hangup {
+    #[cfg(unix)]
+    fn new() -> Self {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let signal = signal(SignalKind::hangup())
+            .inspect_err(|err| {
+                eprintln!(
+                    "Could not listen for SIGHUP, reloading on file changes only: {}",
+                    err
+                )
+            })
+            .ok();
+
+        Self { signal }
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> Self {
+        Self {}
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.signal
+            && signal.recv().await.is_some()
+        {
+            return;
+        }
+
+        std::future::pending().await
+    }
+}
+
+fn profile_changes(
+    old: &BTreeMap<String, TradingProfile>,
+    new: &BTreeMap<String, TradingProfile>,
+) -> Vec<String> {
+    let mut changes = Vec::new();
+    for name in old.keys().filter(|name| !new.contains_key(*name)) {
+        changes.push(format!("removed profile {}", name));
+    }
+
+    for (name, profile) in new {
+        let Some(old_profile) = old.get(name) else {
+            changes.push(format!("added profile {}", profile));
+            continue;
+        };
+
+        for ((key, old_value), (_, value)) in
+            old_profile.settings().into_iter().zip(profile.settings())
+        {
+            if old_value != value {
+                changes.push(format!("{}.{}: {} -> {}", name, key, old_value, value));
+            }
+        }
+    }
+
+    changes
+}
+
+// Keys outside the profiles whose value changed, without the values since they hold secrets
+fn restart_changes(
+    old: &BTreeMap<&'static str, String>,
+    new: &BTreeMap<&'static str, String>,
+) -> Vec<&'static str> {
+    let mut keys: Vec<&'static str> = old.keys().chain(new.keys()).copied().collect();
+    keys.sort_unstable();
+    keys.dedup();
+
+    keys.into_iter()
+        .filter(|key| *key != "profile" && !PROFILE_KEYS.contains(key))
+        .filter(|key| old.get(key) != new.get(key))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Keypair;
+    use std::path::PathBuf;
+
+    use crate::config::{init_bot_config, trading_profile};
+
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("pumpfun-reload-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    // The only test that loads the process wide config, the others parse their own
+    #[test]
+    fn rejected_reload_keeps_the_previous_config() {
+        let base = format!(
+            "private_key = \"{}\"\nrpc_endpoint = \"https://api.mainnet-beta.solana.com\"\nprofile = \"fast\"\n",
+            Keypair::new().to_base58_string()
+        );
+        let bot_toml = |fast: &str| format!("{}\n[profiles.fast]\n{}\n", base, fast);
+        let path = temp_file("bot.toml", &bot_toml("slippage = 10"));
+        // SAFETY: no other test reads or writes the environment
+        unsafe { std::env::set_var("BOT_CONFIG_FILE", &path) };
+        init_bot_config(&[]).unwrap();
+        assert_eq!(active_profile().unwrap().slippage, 0.1);
+
+        // A profile setting out of range fails the config itself
+        fs::write(&path, bot_toml("slippage = 150")).unwrap();
+        assert!(reload_config().is_err());
+
+        // A profile whose strategy file does not parse fails after the config loaded
+        let strategy = temp_file("strategy.json", "{\"rule\": {\"stop_loss\": 2.0}}");
+        fs::write(
+            &path,
+            bot_toml(&format!(
+                "slippage = 20\nsell_strategy_file = \"{}\"",
+                strategy.display()
+            )),
+        )
+        .unwrap();
+        assert!(reload_config().is_err());
+
+        let active = active_profile().unwrap();
+        assert_eq!(active.name, "fast");
+        assert_eq!(active.slippage, 0.1);
+        assert_eq!(active.sell_strategy_file, None);
+
+        // The next good reload is compared against the last accepted one
+        fs::write(&path, bot_toml("slippage = 20")).unwrap();
+        let changes = reload_config().unwrap();
+        assert_eq!(changes.len(), 1, "{:?}", changes);
+        assert!(changes[0].starts_with("fast.slippage"), "{:?}", changes);
+        assert_eq!(trading_profile("fast").unwrap().slippage, 0.2);
+
+        fs::remove_file(path).unwrap();
+        fs::remove_file(strategy).unwrap();
+    }
+}
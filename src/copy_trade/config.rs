@@ -0,0 +1,264 @@
+use serde::Deserialize;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use std::{fs, path::Path};
+
+use crate::{
+    config::{TradingProfile, active_profile, bot_config, trading_profile},
+    error::{ClientError, ClientResult},
+};
+
+/// How much we buy when the target buys, e.g. `{"percent": 25.0}` or
+/// `{"capped": {"percent": 50.0, "max_sol": 0.5}}`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopySizing {
+    /// The same amount of SOL on every buy.
+    FixedSol(f64),
+    /// This percentage of the target's buy.
+    Percent(f64),
+    /// A percentage of the target's buy, at most `max_sol`.
+    Capped { percent: f64, max_sol: f64 },
+}
+
+impl CopySizing {
+    /// Lamports we spend mirroring a buy of `target_lamports`.
+    pub fn buy_lamports(&self, target_lamports: u64) -> u64 {
+        let share = |percent: f64| (target_lamports as f64 * percent / 100.0) as u64;
+
+        match *self {
+            Self::FixedSol(sol) => to_lamports(sol),
+            Self::Percent(percent) => share(percent),
+            Self::Capped { percent, max_sol } => share(percent).min(to_lamports(max_sol)),
+        }
+    }
+
+    fn validate(&self, errors: &mut Vec<String>) {
+        let positive = |errors: &mut Vec<String>, name: &str, value: f64| {
+            if !value.is_finite() || value <= 0.0 {
+                errors.push(format!("{} must be positive, got {}", name, value));
+            }
+        };
+
+        match *self {
+            Self::FixedSol(sol) => positive(errors, "fixed_sol", sol),
+            Self::Percent(percent) => positive(errors, "percent", percent),
+            Self::Capped { percent, max_sol } => {
+                positive(errors, "capped.percent", percent);
+                positive(errors, "capped.max_sol", max_sol);
+            }
+        }
+    }
+}
+
+/// Copy-trade settings as loaded from `COPY_TRADE_FILE`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CopyTradeConfig {
+    pub sizing: CopySizing,
+    /// Defaults to the slippage of the profile.
+    #[serde(default)]
+    pub slippage_pct: Option<f64>,
+    /// Trading profile of the copy trades, unset follows the active profile.
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Target buys below this many SOL are not mirrored.
+    #[serde(default)]
+    pub min_target_buy_sol: Option<f64>,
+    /// Sell the same share of our position whenever the target sells.
+    #[serde(default = "default_mirror_sells")]
+    pub mirror_sells: bool,
+}
+
+fn default_mirror_sells() -> bool {
+    true
+}
+
+impl CopyTradeConfig {
+    /// Buys `lamports` on every target buy and mirrors the sells.
+    fn fixed(lamports: u64) -> Self {
+        Self {
+            sizing: CopySizing::FixedSol(lamports as f64 / LAMPORTS_PER_SOL as f64),
+            slippage_pct: None,
+            profile: None,
+            min_target_buy_sol: None,
+            mirror_sells: true,
+        }
+    }
+
+    /// Loads `COPY_TRADE_FILE` if set, otherwise buys `BUY_SOL_AMOUNT` on every target buy.
+    pub fn from_env() -> ClientResult<Self> {
+        let config = bot_config()?;
+        match config.copy_trade_file.as_deref() {
+            Some(path) => Self::load(path),
+            None => config
+                .default_profile()
+                .buy_sol_amount
+                .map(Self::fixed)
+                .ok_or(ClientError::InvalidInput(
+                    "set COPY_TRADE_FILE or BUY_SOL_AMOUNT to size the copy buys",
+                )),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> ClientResult<Self> {
+        let path = path.as_ref();
+        let input = fs::read_to_string(path)
+            .map_err(|err| ClientError::Other(format!("{}: {}", path.display(), err)))?;
+
+        Self::from_json(&input)
+    }
+
+    pub fn from_json(input: &str) -> ClientResult<Self> {
+        let config: Self = serde_json::from_str(input)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Checks every setting, reporting all problems at once.
+    pub fn validate(&self) -> ClientResult<()> {
+        let mut errors = Vec::new();
+        self.sizing.validate(&mut errors);
+        if let Some(slippage) = self.slippage_pct
+            && !(0.0..100.0).contains(&slippage)
+        {
+            errors.push(format!(
+                "slippage_pct must be in [0, 100), got {}",
+                slippage
+            ));
+        }
+        if let Some(sol) = self.min_target_buy_sol
+            && (!sol.is_finite() || sol < 0.0)
+        {
+            errors.push(format!(
+                "min_target_buy_sol must be a non-negative number, got {}",
+                sol
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ClientError::InvalidData(format!(
+                "invalid copy-trade config: {}",
+                errors.join("; ")
+            )))
+        }
+    }
+
+    /// The pinned profile, or the active one at the time of the call.
+    pub fn profile(&self) -> ClientResult<TradingProfile> {
+        match &self.profile {
+            Some(name) => trading_profile(name),
+            None => active_profile(),
+        }
+    }
+
+    pub fn slippage_pct(&self) -> ClientResult<f64> {
+        match self.slippage_pct {
+            Some(slippage) => Ok(slippage),
+            None => Ok(self.profile()?.slippage_pct()),
+        }
+    }
+
+    pub fn min_target_buy(&self) -> u64 {
+        self.min_target_buy_sol.map_or(0, to_lamports)
+    }
+}
+
+fn to_lamports(sol: f64) -> u64 {
+    (sol * LAMPORTS_PER_SOL as f64) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_SOL: u64 = LAMPORTS_PER_SOL;
+
+    #[test]
+    fn fixed_sizing_ignores_the_target_buy() {
+        let sizing = CopySizing::FixedSol(0.2);
+
+        assert_eq!(sizing.buy_lamports(ONE_SOL / 100), ONE_SOL / 5);
+        assert_eq!(sizing.buy_lamports(10 * ONE_SOL), ONE_SOL / 5);
+    }
+
+    #[test]
+    fn percent_sizing_scales_with_the_target_buy() {
+        let sizing = CopySizing::Percent(25.0);
+
+        assert_eq!(sizing.buy_lamports(2 * ONE_SOL), ONE_SOL / 2);
+        assert_eq!(sizing.buy_lamports(0), 0);
+    }
+
+    #[test]
+    fn capped_sizing_stops_at_max_sol() {
+        let sizing = CopySizing::Capped {
+            percent: 50.0,
+            max_sol: 0.5,
+        };
+
+        assert_eq!(sizing.buy_lamports(ONE_SOL / 2), ONE_SOL / 4);
+        assert_eq!(sizing.buy_lamports(4 * ONE_SOL), ONE_SOL / 2);
+    }
+
+    #[test]
+    fn min_target_buy_defaults_to_every_buy() {
+        let mut config = CopyTradeConfig::fixed(ONE_SOL);
+        assert_eq!(config.min_target_buy(), 0);
+
+        config.min_target_buy_sol = Some(1.5);
+        assert_eq!(config.min_target_buy(), 3 * ONE_SOL / 2);
+    }
+
+    #[test]
+    fn parses_defaults_and_overrides() {
+        let config = CopyTradeConfig::from_json(r#"{"sizing": {"percent": 10.0}}"#).unwrap();
+        assert_eq!(config.sizing, CopySizing::Percent(10.0));
+        assert!(config.mirror_sells);
+        assert_eq!(config.slippage_pct, None);
+
+        let config = CopyTradeConfig::from_json(
+            r#"{
+                "sizing": {"capped": {"percent": 50.0, "max_sol": 0.5}},
+                "slippage_pct": 15.0,
+                "min_target_buy_sol": 0.1,
+                "mirror_sells": false
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.slippage_pct().unwrap(), 15.0);
+        assert_eq!(config.min_target_buy(), ONE_SOL / 10);
+        assert!(!config.mirror_sells);
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        assert!(
+            CopyTradeConfig::from_json(r#"{"sizing": {"percent": 10.0}, "max_buys": 3}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn reports_every_invalid_setting() {
+        let err = CopyTradeConfig::from_json(
+            r#"{
+                "sizing": {"capped": {"percent": 0.0, "max_sol": -1.0}},
+                "slippage_pct": 100.0,
+                "min_target_buy_sol": -0.5
+            }"#,
+        )
+        .unwrap_err()
+        .to_string();
+
+        for setting in [
+            "capped.percent",
+            "capped.max_sol",
+            "slippage_pct",
+            "min_target_buy_sol",
+        ] {
+            assert!(err.contains(setting), "{} missing from {}", setting, err);
+        }
+    }
+}
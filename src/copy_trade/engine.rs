@@ -0,0 +1,193 @@
+use anyhow::Result;
+use carbon_pumpfun_decoder::instructions::buy::Buy;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use std::collections::HashMap;
+
+use crate::{
+    copy_trade::{CopyTradeConfig, TargetTrade},
+    error::ClientResult,
+    instructions::{
+        buy_ix::BuyExactInInstructionAccountsExt, pumpfun_accounts::buy_instruction_accounts,
+        pumpfun_sell::PumpFunSell,
+    },
+    ledger::LedgerPosition,
+    utils::BondingCurveQuote,
+};
+
+/// What the target and we hold of one mint since the copy trader started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopyPosition {
+    pub mint: Pubkey,
+    /// Tokens the target bought minus what it sold, as far as we have seen.
+    pub target_tokens: u64,
+    pub our_tokens: u64,
+    /// Lamports we paid for `our_tokens`, at most.
+    pub cost_basis: u64,
+}
+
+/// Our buy mirroring a target buy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopyBuy {
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub token_amount: u64,
+    pub max_sol_cost: u64,
+}
+
+/// Our sell mirroring a target sell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopySell {
+    pub mint: Pubkey,
+    pub token_amount: u64,
+    /// Set when the whole position is sold.
+    pub close_ata: bool,
+}
+
+/// Mirrors the trades of the target wallet, tracking a position per mint.
+#[derive(Debug, Clone)]
+pub struct CopyTrader {
+    pub config: CopyTradeConfig,
+    pub positions: HashMap<Pubkey, CopyPosition>,
+}
+
+impl CopyTrader {
+    pub fn new(config: CopyTradeConfig) -> ClientResult<Self> {
+        config.validate()?;
+        // An unknown profile name fails at startup instead of on the first copy
+        config.profile()?;
+
+        Ok(Self {
+            config,
+            positions: HashMap::new(),
+        })
+    }
+
+    /// Picks up open ledger positions of an earlier run. The target's holdings are unknown, so
+    /// its next sell of such a mint is mirrored as a full exit.
+    pub fn resume(&mut self, positions: &[LedgerPosition]) -> ClientResult<()> {
+        for open in positions.iter().filter(|position| position.is_open()) {
+            let position = self.position(open.mint.parse()?);
+            position.our_tokens = open.token_amount;
+            position.cost_basis = open.cost_basis;
+        }
+
+        Ok(())
+    }
+
+    fn position(&mut self, mint: Pubkey) -> &mut CopyPosition {
+        self.positions.entry(mint).or_insert(CopyPosition {
+            mint,
+            target_tokens: 0,
+            our_tokens: 0,
+            cost_basis: 0,
+        })
+    }
+
+    /// Tracks a target buy and sizes ours, `None` when it is not mirrored.
+    pub fn on_target_buy(&mut self, trade: &TargetTrade) -> ClientResult<Option<CopyBuy>> {
+        let event = &trade.event;
+        let position = self.position(event.mint);
+        position.target_tokens = position.target_tokens.saturating_add(event.token_amount);
+
+        if event.sol_amount < self.config.min_target_buy() {
+            return Ok(None);
+        }
+        let max_sol_cost = self.config.sizing.buy_lamports(event.sol_amount);
+
+        // The target's buy already moved the curve, what is left is the slippage
+        let quoted = BondingCurveQuote::from_trade_event(event).buy_exact_sol_in(max_sol_cost)?;
+        let slippage_bps = (self.config.slippage_pct()? * 100.0) as u128;
+        let token_amount = (quoted as u128 * (10_000 - slippage_bps) / 10_000) as u64;
+        if token_amount == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(CopyBuy {
+            mint: event.mint,
+            creator: event.creator,
+            token_amount,
+            max_sol_cost,
+        }))
+    }
+
+    /// Tracks a target sell and sizes ours as the same share of our position.
+    pub fn on_target_sell(&mut self, trade: &TargetTrade) -> Option<CopySell> {
+        let event = &trade.event;
+        let mirror_sells = self.config.mirror_sells;
+        let position = self.positions.get_mut(&event.mint)?;
+
+        // Selling more than we saw it buy means it bought before we started, treat it as all
+        let sold_all = event.token_amount >= position.target_tokens;
+        let token_amount = if sold_all {
+            position.our_tokens
+        } else {
+            (position.our_tokens as u128 * event.token_amount as u128
+                / position.target_tokens as u128) as u64
+        };
+        position.target_tokens = position.target_tokens.saturating_sub(event.token_amount);
+
+        if !mirror_sells || token_amount == 0 {
+            return None;
+        }
+
+        Some(CopySell {
+            mint: event.mint,
+            token_amount,
+            close_ata: token_amount == position.our_tokens,
+        })
+    }
+
+    /// Books our buy once it landed.
+    pub fn record_buy(&mut self, buy: &CopyBuy) {
+        let position = self.position(buy.mint);
+        position.our_tokens = position.our_tokens.saturating_add(buy.token_amount);
+        position.cost_basis = position.cost_basis.saturating_add(buy.max_sol_cost);
+    }
+
+    /// Books our sell once it landed, dropping positions that are fully closed.
+    pub fn record_sell(&mut self, sell: &CopySell) {
+        let Some(position) = self.positions.get_mut(&sell.mint) else {
+            return;
+        };
+
+        let remaining = position.our_tokens.saturating_sub(sell.token_amount);
+        if position.our_tokens > 0 {
+            position.cost_basis = (position.cost_basis as u128 * remaining as u128
+                / position.our_tokens as u128) as u64;
+        }
+        position.our_tokens = remaining;
+
+        if remaining == 0 && position.target_tokens == 0 {
+            self.positions.remove(&sell.mint);
+        }
+    }
+
+    /// Idempotent ATA + buy instructions of `buyer`.
+    pub fn buy_instructions(&self, buy: &CopyBuy, buyer: Pubkey) -> Vec<Instruction> {
+        let accounts = buy_instruction_accounts(&buy.mint, &buy.creator, &buyer);
+
+        vec![
+            accounts.get_create_idempotent_ata_ix(),
+            accounts.get_buy_ix(Buy {
+                amount: buy.token_amount,
+                max_sol_cost: buy.max_sol_cost,
+            }),
+        ]
+    }
+
+    /// Sell instructions of `seller`, quoted on the curve the target's sell left.
+    pub fn sell_instructions(
+        &self,
+        sell: &CopySell,
+        trade: &TargetTrade,
+        seller: Pubkey,
+    ) -> Result<Vec<Instruction>> {
+        PumpFunSell::get_sell_instructions_from_event(
+            &trade.event,
+            seller,
+            sell.token_amount,
+            self.config.slippage_pct()?,
+            sell.close_ata,
+        )
+    }
+}
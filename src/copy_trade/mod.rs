@@ -0,0 +1,7 @@
+pub mod config;
+pub mod engine;
+pub mod target_monitor;
+
+pub use config::*;
+pub use engine::*;
+pub use target_monitor::*;
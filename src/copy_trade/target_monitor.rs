@@ -0,0 +1,163 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use carbon_core::{
+    error::{CarbonResult, Error as CarbonError},
+    instruction::InstructionProcessorInputType,
+    metrics::MetricsCollection,
+    pipeline::{Pipeline, ShutdownStrategy},
+    processor::Processor,
+};
+use carbon_log_metrics::LogMetrics;
+use carbon_yellowstone_grpc_datasource::{BlockFilters, YellowstoneGrpcGeyserClient};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use tokio::{
+    sync::{RwLock, mpsc},
+    task::JoinHandle,
+};
+use yellowstone_grpc_proto::geyser::{CommitmentLevel, SubscribeRequestFilterTransactions};
+
+use crate::{
+    config::{grpc_endpoint, grpc_token},
+    error::{ClientError, ClientResult},
+    instructions::{pumpfun_buy::PUMPFUN_PROGRAM, types::TradeEventTemp},
+    monitor::{TradeEventDecoder, TradeSide},
+};
+
+pub const TARGET_FILTER_NAME: &str = "copy_target";
+
+/// A PumpFun trade the target wallet signed.
+#[derive(Debug, Clone)]
+pub struct TargetTrade {
+    pub side: TradeSide,
+    pub signature: Signature,
+    pub slot: u64,
+    pub event: TradeEventTemp,
+}
+
+impl TargetTrade {
+    pub fn is_buy(&self) -> bool {
+        self.side == TradeSide::Buy
+    }
+}
+
+/// Forwards the trade events of `target` in transactions it signed.
+pub struct TargetTradeProcessor {
+    pub target: Pubkey,
+    pub sender: mpsc::UnboundedSender<TargetTrade>,
+}
+
+#[async_trait]
+impl Processor for TargetTradeProcessor {
+    type InputType = InstructionProcessorInputType<TradeEventTemp>;
+
+    async fn process(
+        &mut self,
+        (metadata, instruction, _nested, _raw): Self::InputType,
+        _metrics: Arc<MetricsCollection>,
+    ) -> CarbonResult<()> {
+        let event = instruction.data;
+        if event.user != self.target {
+            return Ok(());
+        }
+
+        // Transfers into the target also include its account, only mirror what it signed
+        let transaction = &metadata.transaction_metadata;
+        let message = &transaction.message;
+        let signers = message.header().num_required_signatures as usize;
+        if !message
+            .static_account_keys()
+            .iter()
+            .take(signers)
+            .any(|key| *key == self.target)
+        {
+            return Ok(());
+        }
+
+        let side = if event.is_buy {
+            TradeSide::Buy
+        } else {
+            TradeSide::Sell
+        };
+
+        let trade = TargetTrade {
+            side,
+            signature: transaction.signature,
+            slot: transaction.slot,
+            event,
+        };
+
+        self.sender
+            .send(trade)
+            .map_err(|_| CarbonError::Custom(ClientError::ChannelClosed.to_string()))
+    }
+}
+
+/// Subscribes to PumpFun transactions of `target`.
+pub fn build_target_pipeline(
+    target: Pubkey,
+    sender: mpsc::UnboundedSender<TargetTrade>,
+) -> ClientResult<Pipeline> {
+    let mut transaction_filters = HashMap::new();
+    transaction_filters.insert(
+        TARGET_FILTER_NAME.to_string(),
+        SubscribeRequestFilterTransactions {
+            vote: Some(false),
+            failed: Some(false),
+            account_required: vec![target.to_string(), PUMPFUN_PROGRAM.to_string()],
+            ..Default::default()
+        },
+    );
+
+    let datasource = YellowstoneGrpcGeyserClient::new(
+        grpc_endpoint()?,
+        Some(grpc_token()?),
+        Some(CommitmentLevel::Processed),
+        HashMap::new(),
+        transaction_filters,
+        BlockFilters::default(),
+        Arc::new(RwLock::new(HashSet::new())),
+    );
+
+    let pipeline = Pipeline::builder()
+        .datasource(datasource)
+        .metrics(Arc::new(LogMetrics::new()))
+        .instruction(TradeEventDecoder, TargetTradeProcessor { target, sender })
+        .shutdown_strategy(ShutdownStrategy::Immediate)
+        .build()?;
+
+    Ok(pipeline)
+}
+
+/// Running target pipeline plus the channel of the trades `target` signs.
+pub struct TargetMonitor {
+    pub target: Pubkey,
+    pub receiver: mpsc::UnboundedReceiver<TargetTrade>,
+    pub handle: JoinHandle<()>,
+}
+
+impl TargetMonitor {
+    pub fn spawn(target: Pubkey) -> ClientResult<Self> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        let mut pipeline = build_target_pipeline(target, sender)?;
+        let handle = tokio::spawn(async move {
+            if let Err(err) = pipeline.run().await {
+                eprintln!("Copy target pipeline stopped: {}", err);
+            }
+        });
+
+        Ok(Self {
+            target,
+            receiver,
+            handle,
+        })
+    }
+
+    pub async fn recv(&mut self) -> Option<TargetTrade> {
+        self.receiver.recv().await
+    }
+}
@@ -45,9 +45,9 @@ pub enum ClientError {
     /// Error deserializing data using Borsh
     BorshError(std::io::Error),
     /// Error from Solana RPC client
-    SolanaClientError(solana_client::client_error::ClientError),
+    SolanaClientError(Box<solana_client::client_error::ClientError>),
     /// Error uploading metadata
-    UploadMetadataError(Box<dyn std::error::Error>),
+    UploadMetadataError(Box<dyn std::error::Error + Send + Sync>),
     /// Invalid input parameters
     InvalidInput(&'static str),
     /// Insufficient funds for transaction
@@ -92,6 +92,12 @@ pub enum ClientError {
     InvalidEventType,
 
     ChannelClosed,
+
+    CompileError(String),
+
+    SignError(String),
+    /// Serialized size of a transaction above the packet limit
+    TransactionTooLarge(usize),
 }
 
 impl std::fmt::Display for ClientError {
@@ -128,6 +134,14 @@ impl std::fmt::Display for ClientError {
             Self::Duplicate(msg) => write!(f, "Duplicate event: {}", msg),
             Self::InvalidEventType => write!(f, "Invalid event type"),
             Self::ChannelClosed => write!(f, "Channel closed"),
+            Self::CompileError(msg) => write!(f, "Failed to compile message: {}", msg),
+            Self::SignError(msg) => write!(f, "Failed to sign transaction: {}", msg),
+            Self::TransactionTooLarge(size) => write!(
+                f,
+                "Transaction too large: {} bytes, max {}",
+                size,
+                solana_sdk::packet::PACKET_DATA_SIZE
+            ),
         }
     }
 }
@@ -135,7 +149,7 @@ impl std::error::Error for ClientError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::BorshError(err) => Some(err),
-            Self::SolanaClientError(err) => Some(err),
+            Self::SolanaClientError(err) => Some(err.as_ref()),
             Self::UploadMetadataError(err) => Some(err.as_ref()),
             Self::ExternalService(_) => None,
             Self::Redis(_, _) => None,
@@ -176,6 +190,12 @@ impl From<ParsePubkeyError> for ClientError {
     }
 }
 
+impl From<carbon_core::error::Error> for ClientError {
+    fn from(error: carbon_core::error::Error) -> Self {
+        ClientError::Subscribe("Carbon pipeline error".to_string(), error.to_string())
+    }
+}
+
 impl From<Error> for ClientError {
     fn from(err: Error) -> Self {
         ClientError::Parse("JSON serialization error".to_string(), err.to_string())
@@ -0,0 +1,658 @@
+use rand::Rng;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signature,
+    signer::Signer, system_instruction,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::time::sleep;
+
+use crate::{
+    error::{ClientError, ClientResult},
+    farm::{FarmWallet, get_accounts, token_account_amount},
+    instructions::pumpfun_sell::PumpFunSell,
+    utils::{TxLifetime, try_build_and_sign},
+};
+
+/// Base fee of one signature.
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+/// Transfers that still fit into one legacy-sized packet with a single signer.
+pub const MAX_TRANSFERS_PER_TX: usize = 20;
+/// ATA closes per sweep transaction, next to the treasury and wallet signatures.
+pub const MAX_CLOSES_PER_TX: usize = 8;
+
+/// How SOL is spread from the treasury. Amounts and delays are drawn uniformly from the
+/// inclusive ranges.
+#[derive(Debug, Clone)]
+pub struct DistributionConfig {
+    pub min_lamports: u64,
+    pub max_lamports: u64,
+    /// Wait before each transaction after the first.
+    pub min_delay: Duration,
+    pub max_delay: Duration,
+    /// Recipients per transaction, 1 sends every transfer on its own.
+    pub transfers_per_tx: usize,
+}
+
+impl DistributionConfig {
+    fn validate(&self) -> ClientResult<()> {
+        if self.min_lamports == 0 || self.min_lamports > self.max_lamports {
+            return Err(ClientError::InvalidInput(
+                "distribution amount range must be non-empty and above zero",
+            ));
+        }
+        if self.min_delay > self.max_delay {
+            return Err(ClientError::InvalidInput(
+                "distribution delay range is empty",
+            ));
+        }
+        if !(1..=MAX_TRANSFERS_PER_TX).contains(&self.transfers_per_tx) {
+            return Err(ClientError::InvalidInput(
+                "transfers per transaction must be between 1 and MAX_TRANSFERS_PER_TX",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transfer {
+    pub to: Pubkey,
+    pub lamports: u64,
+}
+
+/// Transfers sent in one transaction after waiting `delay`.
+#[derive(Debug, Clone)]
+pub struct TransferBatch {
+    pub delay: Duration,
+    pub transfers: Vec<Transfer>,
+}
+
+/// Randomized funding of farm wallets from the treasury, built up front so it can be
+/// previewed before anything is sent.
+#[derive(Debug, Clone)]
+pub struct DistributionPlan {
+    pub treasury: Pubkey,
+    pub batches: Vec<TransferBatch>,
+}
+
+impl DistributionPlan {
+    pub fn new(
+        treasury: Pubkey,
+        recipients: &[Pubkey],
+        config: &DistributionConfig,
+    ) -> ClientResult<Self> {
+        Self::with_rng(treasury, recipients, config, &mut rand::thread_rng())
+    }
+
+    pub fn with_rng(
+        treasury: Pubkey,
+        recipients: &[Pubkey],
+        config: &DistributionConfig,
+        rng: &mut impl Rng,
+    ) -> ClientResult<Self> {
+        config.validate()?;
+
+        let recipients: Vec<Pubkey> = recipients
+            .iter()
+            .copied()
+            .filter(|recipient| *recipient != treasury)
+            .collect();
+        if recipients.is_empty() {
+            return Err(ClientError::InvalidInput("no wallets to distribute to"));
+        }
+
+        let batches = recipients
+            .chunks(config.transfers_per_tx)
+            .enumerate()
+            .map(|(idx, chunk)| TransferBatch {
+                delay: if idx == 0 {
+                    Duration::ZERO
+                } else {
+                    rng.gen_range(config.min_delay..=config.max_delay)
+                },
+                transfers: chunk
+                    .iter()
+                    .map(|to| Transfer {
+                        to: *to,
+                        lamports: rng.gen_range(config.min_lamports..=config.max_lamports),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Self { treasury, batches })
+    }
+
+    pub fn total_lamports(&self) -> u64 {
+        self.batches
+            .iter()
+            .flat_map(|batch| &batch.transfers)
+            .map(|transfer| transfer.lamports)
+            .sum()
+    }
+
+    pub fn estimated_fees(&self) -> u64 {
+        self.batches.len() as u64 * LAMPORTS_PER_SIGNATURE
+    }
+
+    pub fn total_delay(&self) -> Duration {
+        self.batches.iter().map(|batch| batch.delay).sum()
+    }
+
+    pub fn instructions(&self, batch: &TransferBatch) -> Vec<Instruction> {
+        batch
+            .transfers
+            .iter()
+            .map(|transfer| {
+                system_instruction::transfer(&self.treasury, &transfer.to, transfer.lamports)
+            })
+            .collect()
+    }
+
+    /// Prints every transfer and the totals against `treasury_balance`.
+    pub fn print_preview(&self, treasury_balance: u64) {
+        println!("Distribution from {}", self.treasury);
+        for (idx, batch) in self.batches.iter().enumerate() {
+            println!("  tx {} after {:?}", idx + 1, batch.delay);
+            for transfer in &batch.transfers {
+                println!("    {} {:.9} SOL", transfer.to, to_sol(transfer.lamports));
+            }
+        }
+
+        let needed = self.total_lamports() + self.estimated_fees();
+        println!(
+            "Total: {:.9} SOL to {} wallets in {} transactions over {:?}, fees {:.9} SOL",
+            to_sol(self.total_lamports()),
+            self.batches
+                .iter()
+                .map(|batch| batch.transfers.len())
+                .sum::<usize>(),
+            self.batches.len(),
+            self.total_delay(),
+            to_sol(self.estimated_fees()),
+        );
+        println!(
+            "Treasury balance: {:.9} SOL{}",
+            to_sol(treasury_balance),
+            if treasury_balance < needed {
+                " (insufficient)"
+            } else {
+                ""
+            }
+        );
+    }
+
+    /// Sends the batches one by one, waiting for each to confirm.
+    pub async fn execute(
+        &self,
+        rpc_client: &RpcClient,
+        treasury: &dyn Signer,
+    ) -> ClientResult<Vec<Signature>> {
+        if treasury.pubkey() != self.treasury {
+            return Err(ClientError::InvalidInput(
+                "signer is not the plan's treasury",
+            ));
+        }
+
+        let mut signatures = Vec::with_capacity(self.batches.len());
+        for batch in &self.batches {
+            sleep(batch.delay).await;
+
+            let blockhash = rpc_client.get_latest_blockhash().await?;
+            let txn = try_build_and_sign(
+                treasury,
+                &[],
+                &self.instructions(batch),
+                &[],
+                &TxLifetime::Blockhash(blockhash),
+            )?;
+            let signature = rpc_client.send_and_confirm_transaction(&txn).await?;
+            println!("Funded {} wallets ({})", batch.transfers.len(), signature);
+            signatures.push(signature);
+        }
+
+        Ok(signatures)
+    }
+}
+
+/// Empty ATA closed during a sweep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtaClose {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub rent_lamports: u64,
+}
+
+/// What is collected from one wallet.
+#[derive(Debug, Clone)]
+pub struct WalletSweep {
+    pub wallet: Arc<FarmWallet>,
+    pub lamports: u64,
+    pub closes: Vec<AtaClose>,
+    /// ATAs left open because they still hold tokens, with their raw amount.
+    pub skipped: Vec<(Pubkey, u64)>,
+}
+
+impl WalletSweep {
+    /// SOL balance plus recovered rent, minus what is kept in the wallet.
+    pub fn sweep_lamports(&self, keep_lamports: u64) -> u64 {
+        let rent: u64 = self.closes.iter().map(|close| close.rent_lamports).sum();
+
+        (self.lamports + rent).saturating_sub(keep_lamports)
+    }
+
+    pub fn transaction_count(&self) -> usize {
+        self.closes.len().div_ceil(MAX_CLOSES_PER_TX).max(1)
+    }
+}
+
+/// Collection of SOL and ATA rent from farm wallets back to the treasury, which pays the fees
+/// so wallets can be drained completely.
+#[derive(Debug, Clone)]
+pub struct SweepPlan {
+    pub treasury: Pubkey,
+    /// SOL left in every wallet, e.g. to keep it rent exempt.
+    pub keep_lamports: u64,
+    pub sweeps: Vec<WalletSweep>,
+}
+
+impl SweepPlan {
+    /// Reads the balances of `wallets` and their ATAs of `mints`. `keep_lamports` must be 0
+    /// or keep the wallets rent exempt, otherwise the sweep transfer fails.
+    pub async fn build(
+        rpc_client: &RpcClient,
+        treasury: Pubkey,
+        wallets: &[Arc<FarmWallet>],
+        mints: &[Pubkey],
+        keep_lamports: u64,
+    ) -> ClientResult<Self> {
+        let rent_exempt_minimum = rpc_client.get_minimum_balance_for_rent_exemption(0).await?;
+        check_keep_lamports(keep_lamports, rent_exempt_minimum)?;
+
+        let wallets: Vec<&Arc<FarmWallet>> = wallets
+            .iter()
+            .filter(|wallet| wallet.pubkey() != treasury)
+            .collect();
+
+        let addresses: Vec<Pubkey> = wallets
+            .iter()
+            .flat_map(|wallet| {
+                std::iter::once(wallet.pubkey())
+                    .chain(mints.iter().map(|mint| wallet.token_account(mint)))
+            })
+            .collect();
+        let accounts = get_accounts(rpc_client, &addresses).await?;
+
+        let mut sweeps = Vec::new();
+        for (wallet, accounts) in wallets.into_iter().zip(accounts.chunks(1 + mints.len())) {
+            let mut sweep = WalletSweep {
+                wallet: wallet.clone(),
+                lamports: accounts[0].as_ref().map_or(0, |account| account.lamports),
+                closes: Vec::new(),
+                skipped: Vec::new(),
+            };
+
+            for (mint, account) in mints.iter().zip(&accounts[1..]) {
+                let Some(account) = account else {
+                    continue;
+                };
+
+                match token_account_amount(&account.data)? {
+                    0 => sweep.closes.push(AtaClose {
+                        mint: *mint,
+                        token_account: wallet.token_account(mint),
+                        rent_lamports: account.lamports,
+                    }),
+                    amount => sweep.skipped.push((*mint, amount)),
+                }
+            }
+
+            if sweep.sweep_lamports(keep_lamports) > 0 || !sweep.closes.is_empty() {
+                sweeps.push(sweep);
+            }
+        }
+
+        Ok(Self {
+            treasury,
+            keep_lamports,
+            sweeps,
+        })
+    }
+
+    pub fn total_lamports(&self) -> u64 {
+        self.sweeps
+            .iter()
+            .map(|sweep| sweep.sweep_lamports(self.keep_lamports))
+            .sum()
+    }
+
+    pub fn total_rent(&self) -> u64 {
+        self.sweeps
+            .iter()
+            .flat_map(|sweep| &sweep.closes)
+            .map(|close| close.rent_lamports)
+            .sum()
+    }
+
+    /// Treasury and wallet sign every transaction.
+    pub fn estimated_fees(&self) -> u64 {
+        self.sweeps
+            .iter()
+            .map(|sweep| sweep.transaction_count() as u64 * 2 * LAMPORTS_PER_SIGNATURE)
+            .sum()
+    }
+
+    /// Close instructions in chunks of [`MAX_CLOSES_PER_TX`], the transfer to the treasury
+    /// goes into the last one.
+    pub fn instructions(&self, sweep: &WalletSweep) -> ClientResult<Vec<Vec<Instruction>>> {
+        let owner = sweep.wallet.pubkey();
+
+        let mut transactions = sweep
+            .closes
+            .chunks(MAX_CLOSES_PER_TX)
+            .map(|closes| {
+                closes
+                    .iter()
+                    .map(|close| {
+                        PumpFunSell::create_ata_close_instruction(
+                            close.token_account,
+                            owner,
+                            close.mint,
+                        )
+                        .map_err(|err| ClientError::PumpFunSell(err.to_string()))
+                    })
+                    .collect::<ClientResult<Vec<_>>>()
+            })
+            .collect::<ClientResult<Vec<_>>>()?;
+        if transactions.is_empty() {
+            transactions.push(Vec::new());
+        }
+
+        let lamports = sweep.sweep_lamports(self.keep_lamports);
+        if lamports > 0 {
+            transactions
+                .last_mut()
+                .expect("at least one transaction")
+                .push(system_instruction::transfer(
+                    &owner,
+                    &self.treasury,
+                    lamports,
+                ));
+        }
+
+        Ok(transactions)
+    }
+
+    pub fn print_preview(&self) {
+        println!("Sweep to {}", self.treasury);
+        for sweep in &self.sweeps {
+            println!(
+                "  {:<16} {} {:.9} SOL, {} ATAs closed",
+                sweep.wallet.label,
+                sweep.wallet.pubkey(),
+                to_sol(sweep.sweep_lamports(self.keep_lamports)),
+                sweep.closes.len()
+            );
+            for (mint, amount) in &sweep.skipped {
+                println!("    keeping ATA of {} holding {} tokens", mint, amount);
+            }
+        }
+
+        println!(
+            "Total: {:.9} SOL from {} wallets ({:.9} SOL rent), fees {:.9} SOL paid by the treasury",
+            to_sol(self.total_lamports()),
+            self.sweeps.len(),
+            to_sol(self.total_rent()),
+            to_sol(self.estimated_fees()),
+        );
+    }
+
+    /// Sends each wallet's transactions in order, waiting for each to confirm.
+    pub async fn execute(
+        &self,
+        rpc_client: &RpcClient,
+        treasury: &dyn Signer,
+    ) -> ClientResult<Vec<Signature>> {
+        if treasury.pubkey() != self.treasury {
+            return Err(ClientError::InvalidInput(
+                "signer is not the plan's treasury",
+            ));
+        }
+
+        let mut signatures = Vec::new();
+        for sweep in &self.sweeps {
+            for ixs in self.instructions(sweep)? {
+                let blockhash = rpc_client.get_latest_blockhash().await?;
+                let txn = try_build_and_sign(
+                    treasury,
+                    &[&*sweep.wallet.signer],
+                    &ixs,
+                    &[],
+                    &TxLifetime::Blockhash(blockhash),
+                )?;
+                let signature = rpc_client.send_and_confirm_transaction(&txn).await?;
+                signatures.push(signature);
+            }
+            println!("Swept {} ({})", sweep.wallet.label, sweep.wallet.pubkey());
+        }
+
+        Ok(signatures)
+    }
+}
+
+// A wallet left with less than its rent-exempt minimum but more than 0 fails the transfer
+fn check_keep_lamports(keep_lamports: u64, rent_exempt_minimum: u64) -> ClientResult<()> {
+    if (1..rent_exempt_minimum).contains(&keep_lamports) {
+        return Err(ClientError::InvalidInput(
+            "SOL kept in swept wallets must be 0 or at least their rent-exempt minimum",
+        ));
+    }
+
+    Ok(())
+}
+
+fn to_sol(lamports: u64) -> f64 {
+    lamports as f64 / LAMPORTS_PER_SOL as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+    use solana_sdk::signature::Keypair;
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    // Rent-exempt minimum of an account without data
+    const RENT_EXEMPT_MINIMUM: u64 = 890_880;
+
+    fn config(min_lamports: u64, max_lamports: u64, transfers_per_tx: usize) -> DistributionConfig {
+        DistributionConfig {
+            min_lamports,
+            max_lamports,
+            min_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(5),
+            transfers_per_tx,
+        }
+    }
+
+    fn sweep(lamports: u64, ata_rents: &[u64]) -> WalletSweep {
+        let wallet = FarmWallet::new("buyer-1", Arc::new(Keypair::new()), BTreeSet::new());
+        let closes = ata_rents
+            .iter()
+            .map(|rent| AtaClose {
+                mint: Pubkey::new_unique(),
+                token_account: Pubkey::new_unique(),
+                rent_lamports: *rent,
+            })
+            .collect();
+
+        WalletSweep {
+            wallet: Arc::new(wallet),
+            lamports,
+            closes,
+            skipped: Vec::new(),
+        }
+    }
+
+    fn plan(keep_lamports: u64, sweeps: Vec<WalletSweep>) -> SweepPlan {
+        SweepPlan {
+            treasury: Pubkey::new_unique(),
+            keep_lamports,
+            sweeps,
+        }
+    }
+
+    #[test]
+    fn uneven_split_fills_batches_in_order() {
+        let treasury = Pubkey::new_unique();
+        let mut recipients: Vec<Pubkey> = (0..7).map(|_| Pubkey::new_unique()).collect();
+        // The treasury is never funded from itself
+        recipients.push(treasury);
+
+        let plan = DistributionPlan::with_rng(
+            treasury,
+            &recipients,
+            &config(1_000_000, 1_000_000, 3),
+            &mut StdRng::seed_from_u64(7),
+        )
+        .unwrap();
+
+        let sizes: Vec<usize> = plan.batches.iter().map(|b| b.transfers.len()).collect();
+        assert_eq!(sizes, [3, 3, 1]);
+        let funded: Vec<Pubkey> = plan
+            .batches
+            .iter()
+            .flat_map(|batch| batch.transfers.iter().map(|transfer| transfer.to))
+            .collect();
+        assert_eq!(funded, recipients[..7]);
+        assert_eq!(plan.total_lamports(), 7_000_000);
+        assert_eq!(plan.estimated_fees(), 3 * LAMPORTS_PER_SIGNATURE);
+    }
+
+    #[test]
+    fn amounts_and_delays_stay_in_range() {
+        let recipients: Vec<Pubkey> = (0..50).map(|_| Pubkey::new_unique()).collect();
+        let plan = DistributionPlan::with_rng(
+            Pubkey::new_unique(),
+            &recipients,
+            &config(1_000, 1_999, 4),
+            &mut StdRng::seed_from_u64(7),
+        )
+        .unwrap();
+
+        assert_eq!(plan.batches[0].delay, Duration::ZERO);
+        for batch in &plan.batches[1..] {
+            assert!((Duration::from_secs(1)..=Duration::from_secs(5)).contains(&batch.delay));
+        }
+        let amounts: Vec<u64> = plan
+            .batches
+            .iter()
+            .flat_map(|batch| batch.transfers.iter().map(|transfer| transfer.lamports))
+            .collect();
+        assert!(
+            amounts
+                .iter()
+                .all(|amount| (1_000..=1_999).contains(amount))
+        );
+        assert_eq!(plan.total_lamports(), amounts.iter().sum::<u64>());
+    }
+
+    #[test]
+    fn invalid_distribution_config_is_rejected() {
+        let recipients = [Pubkey::new_unique()];
+        let treasury = Pubkey::new_unique();
+
+        assert!(DistributionPlan::new(treasury, &recipients, &config(0, 10, 1)).is_err());
+        assert!(DistributionPlan::new(treasury, &recipients, &config(10, 9, 1)).is_err());
+        assert!(DistributionPlan::new(treasury, &recipients, &config(1, 10, 0)).is_err());
+        assert!(
+            DistributionPlan::new(
+                treasury,
+                &recipients,
+                &config(1, 10, MAX_TRANSFERS_PER_TX + 1)
+            )
+            .is_err()
+        );
+        assert!(DistributionPlan::new(treasury, &[treasury], &config(1, 10, 1)).is_err());
+    }
+
+    #[test]
+    fn keep_lamports_is_zero_or_rent_exempt() {
+        assert!(check_keep_lamports(0, RENT_EXEMPT_MINIMUM).is_ok());
+        assert!(check_keep_lamports(1, RENT_EXEMPT_MINIMUM).is_err());
+        assert!(check_keep_lamports(RENT_EXEMPT_MINIMUM - 1, RENT_EXEMPT_MINIMUM).is_err());
+        assert!(check_keep_lamports(RENT_EXEMPT_MINIMUM, RENT_EXEMPT_MINIMUM).is_ok());
+    }
+
+    #[test]
+    fn sweep_takes_balance_and_ata_rent_minus_keep() {
+        let sweep = sweep(10_000_000, &[2_039_280, 2_039_280]);
+
+        assert_eq!(sweep.sweep_lamports(0), 14_078_560);
+        assert_eq!(
+            sweep.sweep_lamports(RENT_EXEMPT_MINIMUM),
+            14_078_560 - RENT_EXEMPT_MINIMUM
+        );
+    }
+
+    #[test]
+    fn wallet_below_rent_plus_fee_is_drained_by_the_treasury_paying() {
+        // Less than rent plus one signature fee, the treasury pays so all of it is swept
+        let plan = plan(0, vec![sweep(RENT_EXEMPT_MINIMUM + 4_999, &[])]);
+        let sweep = &plan.sweeps[0];
+
+        let transactions = plan.instructions(sweep).unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(
+            transactions[0],
+            [system_instruction::transfer(
+                &sweep.wallet.pubkey(),
+                &plan.treasury,
+                RENT_EXEMPT_MINIMUM + 4_999
+            )]
+        );
+        assert_eq!(plan.total_lamports(), RENT_EXEMPT_MINIMUM + 4_999);
+        assert_eq!(plan.estimated_fees(), 2 * LAMPORTS_PER_SIGNATURE);
+    }
+
+    #[test]
+    fn wallet_below_keep_only_gets_its_atas_closed() {
+        let plan = plan(RENT_EXEMPT_MINIMUM, vec![sweep(500_000, &[300_000])]);
+        let sweep = &plan.sweeps[0];
+
+        // Balance plus rent is still below what the wallet keeps
+        assert_eq!(sweep.sweep_lamports(plan.keep_lamports), 0);
+        let transactions = plan.instructions(sweep).unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].len(), 1);
+        assert_eq!(plan.total_lamports(), 0);
+        assert_eq!(plan.total_rent(), 300_000);
+    }
+
+    #[test]
+    fn closes_are_chunked_with_the_transfer_last() {
+        let rents = vec![2_039_280; MAX_CLOSES_PER_TX + 1];
+        let plan = plan(
+            RENT_EXEMPT_MINIMUM,
+            vec![sweep(RENT_EXEMPT_MINIMUM, &rents)],
+        );
+        let sweep = &plan.sweeps[0];
+
+        let transactions = plan.instructions(sweep).unwrap();
+        let sizes: Vec<usize> = transactions.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [MAX_CLOSES_PER_TX, 2]);
+        assert_eq!(
+            transactions[1][1],
+            system_instruction::transfer(
+                &sweep.wallet.pubkey(),
+                &plan.treasury,
+                2_039_280 * (MAX_CLOSES_PER_TX as u64 + 1)
+            )
+        );
+        // Treasury and wallet sign each of the two transactions
+        assert_eq!(plan.estimated_fees(), 4 * LAMPORTS_PER_SIGNATURE);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod distribution;
+pub mod wallet;
+pub mod wallet_farm;
+
+pub use distribution::*;
+pub use wallet::*;
+pub use wallet_farm::*;
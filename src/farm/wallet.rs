@@ -0,0 +1,173 @@
+use solana_sdk::{
+    address_lookup_table::AddressLookupTableAccount, instruction::Instruction, pubkey::Pubkey,
+    signer::Signer, transaction::VersionedTransaction,
+};
+use spl_associated_token_account::get_associated_token_address;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::Instant,
+};
+
+use crate::{
+    error::{ClientError, ClientResult},
+    signer::TxSigner,
+    utils::{TxLifetime, try_build_and_sign},
+};
+
+/// What a farm wallet is used for. A wallet can have several roles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WalletRole {
+    /// Creates tokens and makes the dev buy.
+    Deployer,
+    Buyer,
+    Seller,
+    /// Pays transaction fees and tips for the other signers.
+    FeePayer,
+}
+
+impl WalletRole {
+    pub const ALL: [WalletRole; 4] = [
+        WalletRole::Deployer,
+        WalletRole::Buyer,
+        WalletRole::Seller,
+        WalletRole::FeePayer,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Deployer => "deployer",
+            Self::Buyer => "buyer",
+            Self::Seller => "seller",
+            Self::FeePayer => "fee_payer",
+        }
+    }
+}
+
+impl fmt::Display for WalletRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WalletRole {
+    type Err = ClientError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "deployer" | "dev" => Ok(Self::Deployer),
+            "buyer" => Ok(Self::Buyer),
+            "seller" => Ok(Self::Seller),
+            "fee_payer" | "feepayer" | "payer" => Ok(Self::FeePayer),
+            other => Err(ClientError::Parse(
+                "wallet role".to_string(),
+                format!("unknown role '{}'", other),
+            )),
+        }
+    }
+}
+
+/// Last known balances of a wallet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WalletBalances {
+    pub lamports: u64,
+    /// Raw token amounts by mint, zero when the ATA does not exist.
+    pub tokens: HashMap<Pubkey, u64>,
+    /// `None` until the first refresh.
+    pub updated_at: Option<Instant>,
+}
+
+/// One key of the farm with its roles and balances.
+pub struct FarmWallet {
+    pub label: String,
+    pub signer: Arc<dyn TxSigner>,
+    pub roles: BTreeSet<WalletRole>,
+    balances: RwLock<WalletBalances>,
+}
+
+impl fmt::Debug for FarmWallet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FarmWallet")
+            .field("label", &self.label)
+            .field("pubkey", &self.pubkey())
+            .field("roles", &self.roles)
+            .finish()
+    }
+}
+
+impl FarmWallet {
+    pub fn new(
+        label: impl Into<String>,
+        signer: Arc<dyn TxSigner>,
+        roles: BTreeSet<WalletRole>,
+    ) -> Self {
+        Self {
+            label: label.into(),
+            signer,
+            roles,
+            balances: RwLock::new(WalletBalances::default()),
+        }
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+
+    pub fn has_role(&self, role: WalletRole) -> bool {
+        self.roles.contains(&role)
+    }
+
+    /// ATA of this wallet for `mint`.
+    pub fn token_account(&self, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address(&self.pubkey(), mint)
+    }
+
+    pub fn balances(&self) -> WalletBalances {
+        self.balances
+            .read()
+            .expect("balances lock poisoned")
+            .clone()
+    }
+
+    pub fn lamports(&self) -> u64 {
+        self.balances
+            .read()
+            .expect("balances lock poisoned")
+            .lamports
+    }
+
+    pub fn token_balance(&self, mint: &Pubkey) -> u64 {
+        self.balances
+            .read()
+            .expect("balances lock poisoned")
+            .tokens
+            .get(mint)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn set_lamports(&self, lamports: u64) {
+        let mut balances = self.balances.write().expect("balances lock poisoned");
+        balances.lamports = lamports;
+        balances.updated_at = Some(Instant::now());
+    }
+
+    pub fn set_token_balance(&self, mint: Pubkey, amount: u64) {
+        let mut balances = self.balances.write().expect("balances lock poisoned");
+        balances.tokens.insert(mint, amount);
+        balances.updated_at = Some(Instant::now());
+    }
+
+    /// Builds and signs `ixs` with this wallet as the fee payer.
+    pub fn sign(
+        &self,
+        extra_signers: &[&dyn Signer],
+        ixs: &[Instruction],
+        lookup_tables: &[AddressLookupTableAccount],
+        lifetime: &TxLifetime,
+    ) -> ClientResult<VersionedTransaction> {
+        try_build_and_sign(&*self.signer, extra_signers, ixs, lookup_tables, lifetime)
+    }
+}
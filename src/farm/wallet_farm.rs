@@ -0,0 +1,398 @@
+use serde_json::Value;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Keypair};
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    config::{bot_config, try_parse_keypair, tx_signer, unlock_keystore},
+    error::{ClientError, ClientResult},
+    farm::{FarmWallet, WalletRole},
+    keystore::Keystore,
+    signer::TxSigner,
+};
+
+/// `getMultipleAccounts` limit.
+const MAX_ACCOUNTS_PER_REQUEST: usize = 100;
+/// `amount` of an SPL token account, after mint and owner.
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+/// Label, roles and keypair of a wallet as written in its file.
+pub type WalletKeypair = (String, BTreeSet<WalletRole>, Keypair);
+
+/// The set of wallets the bot trades with.
+///
+/// Every farm has exactly one deployer and at least one fee payer, see [`WalletFarm::new`].
+#[derive(Debug, Clone)]
+pub struct WalletFarm {
+    pub wallets: Vec<Arc<FarmWallet>>,
+}
+
+impl WalletFarm {
+    /// Fills in missing roles: the first wallet becomes the deployer if none is marked, and
+    /// the deployer pays fees if no fee payer is marked.
+    pub fn new(mut wallets: Vec<FarmWallet>) -> ClientResult<Self> {
+        if wallets.is_empty() {
+            return Err(ClientError::InvalidInput("wallet farm is empty"));
+        }
+
+        let deployers = wallets
+            .iter()
+            .filter(|wallet| wallet.has_role(WalletRole::Deployer))
+            .count();
+        match deployers {
+            0 => {
+                wallets[0].roles.insert(WalletRole::Deployer);
+            }
+            1 => {}
+            _ => return Err(ClientError::InvalidInput("more than one deployer wallet")),
+        }
+
+        if !wallets
+            .iter()
+            .any(|wallet| wallet.has_role(WalletRole::FeePayer))
+            && let Some(deployer) = wallets
+                .iter_mut()
+                .find(|wallet| wallet.has_role(WalletRole::Deployer))
+        {
+            deployer.roles.insert(WalletRole::FeePayer);
+        }
+
+        let mut seen = BTreeSet::new();
+        for wallet in &wallets {
+            if !seen.insert(wallet.pubkey()) {
+                return Err(ClientError::Duplicate(format!(
+                    "wallet {} ({}) is loaded twice",
+                    wallet.pubkey(),
+                    wallet.label
+                )));
+            }
+        }
+
+        Ok(Self {
+            wallets: wallets.into_iter().map(Arc::new).collect(),
+        })
+    }
+
+    /// A farm of just `signer`, holding every role.
+    pub fn single(signer: Arc<dyn TxSigner>) -> Self {
+        let wallet = FarmWallet::new("main", signer, WalletRole::ALL.into_iter().collect());
+
+        Self {
+            wallets: vec![Arc::new(wallet)],
+        }
+    }
+
+    /// Loads `FARM_WALLETS` if set, otherwise a single wallet farm of `tx_signer`.
+    pub fn from_env() -> ClientResult<Self> {
+        // Unlocked first so every passphrase prompt comes before trading starts
+        let signer = tx_signer()?;
+        match bot_config()?.farm_wallets.as_deref() {
+            Some(path) => Self::load(path),
+            None => Ok(Self::single(signer)),
+        }
+    }
+
+    /// Loads a directory of keypair files or a single wallets file, see [`Self::load_dir`] and
+    /// [`Self::load_file`].
+    pub fn load(path: impl AsRef<Path>) -> ClientResult<Self> {
+        let path = path.as_ref();
+        if path.is_dir() {
+            Self::load_dir(path)
+        } else {
+            Self::load_file(path)
+        }
+    }
+
+    /// Loads every `*.json` keypair file (`solana-keygen` format) in `dir`, in file name order.
+    ///
+    /// The roles come from the file name prefix: `deployer*`, `buyer*`, `seller*` and
+    /// `fee_payer*`. Any other file is a buyer and seller.
+    pub fn load_dir(dir: impl AsRef<Path>) -> ClientResult<Self> {
+        Self::new(local_wallets(dir_wallets(dir.as_ref())?))
+    }
+
+    /// Loads a JSON array of wallets from one file. Each entry is either a bare keypair (byte
+    /// array, base58 or base64 string) or `{"label": .., "roles": [..], "keypair": ..}`.
+    ///
+    /// An encrypted keystore (see `Keystore`) is unlocked instead, its entries become the wallets.
+    /// When it is also `KEYSTORE_FILE`, the `KEYSTORE_KEY` entry stays out of the farm.
+    pub fn load_file(path: impl AsRef<Path>) -> ClientResult<Self> {
+        let path = path.as_ref();
+        let input = fs::read_to_string(path).map_err(|err| io_error(path, err))?;
+        if !Keystore::is_keystore(&input) {
+            return Self::from_json(&input);
+        }
+
+        let keystore = Arc::new(unlock_keystore(path)?);
+        let config = bot_config()?;
+        let main_key = config
+            .keystore_file
+            .as_deref()
+            .filter(|file| Path::new(file) == path)
+            .and_then(|_| keystore.keystore.find(&config.keystore_key))
+            .map(|entry| entry.pubkey.clone());
+        let mut wallets = keystore.wallets()?;
+        wallets.retain(|wallet| Some(wallet.pubkey().to_string()) != main_key);
+
+        Self::new(wallets)
+    }
+
+    /// Parses the format of [`Self::load_file`].
+    pub fn from_json(input: &str) -> ClientResult<Self> {
+        Self::new(local_wallets(json_wallets(input)?))
+    }
+
+    /// Label, roles and keypair of every wallet in a directory or plaintext wallets file, as
+    /// written without the roles [`Self::new`] fills in.
+    pub fn read_wallets(path: impl AsRef<Path>) -> ClientResult<Vec<WalletKeypair>> {
+        let path = path.as_ref();
+        if path.is_dir() {
+            return dir_wallets(path);
+        }
+
+        let input = fs::read_to_string(path).map_err(|err| io_error(path, err))?;
+        if Keystore::is_keystore(&input) {
+            return Err(ClientError::InvalidData(format!(
+                "{} is a keystore, not a wallets file",
+                path.display()
+            )));
+        }
+
+        json_wallets(&input)
+    }
+
+    pub fn len(&self) -> usize {
+        self.wallets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.wallets.is_empty()
+    }
+
+    pub fn pubkeys(&self) -> Vec<Pubkey> {
+        self.wallets.iter().map(|wallet| wallet.pubkey()).collect()
+    }
+
+    pub fn contains(&self, pubkey: &Pubkey) -> bool {
+        self.get(pubkey).is_some()
+    }
+
+    pub fn get(&self, pubkey: &Pubkey) -> Option<&Arc<FarmWallet>> {
+        self.wallets
+            .iter()
+            .find(|wallet| wallet.pubkey() == *pubkey)
+    }
+
+    pub fn with_role(&self, role: WalletRole) -> impl Iterator<Item = &Arc<FarmWallet>> {
+        self.wallets
+            .iter()
+            .filter(move |wallet| wallet.has_role(role))
+    }
+
+    pub fn deployer(&self) -> &Arc<FarmWallet> {
+        self.with_role(WalletRole::Deployer)
+            .next()
+            .expect("farm has a deployer")
+    }
+
+    /// First fee payer, the deployer itself unless another wallet is marked.
+    pub fn fee_payer(&self) -> &Arc<FarmWallet> {
+        self.with_role(WalletRole::FeePayer)
+            .next()
+            .expect("farm has a fee payer")
+    }
+
+    pub fn buyers(&self) -> impl Iterator<Item = &Arc<FarmWallet>> {
+        self.with_role(WalletRole::Buyer)
+    }
+
+    pub fn sellers(&self) -> impl Iterator<Item = &Arc<FarmWallet>> {
+        self.with_role(WalletRole::Seller)
+    }
+
+    pub fn total_lamports(&self) -> u64 {
+        self.wallets.iter().map(|wallet| wallet.lamports()).sum()
+    }
+
+    pub fn total_token_balance(&self, mint: &Pubkey) -> u64 {
+        self.wallets
+            .iter()
+            .map(|wallet| wallet.token_balance(mint))
+            .sum()
+    }
+
+    /// Reads the SOL balance of every wallet and its token balance for each of `mints`.
+    pub async fn refresh_balances(
+        &self,
+        rpc_client: &RpcClient,
+        mints: &[Pubkey],
+    ) -> ClientResult<()> {
+        // Per wallet: the wallet itself followed by its ATA of each mint
+        let addresses: Vec<Pubkey> = self
+            .wallets
+            .iter()
+            .flat_map(|wallet| {
+                std::iter::once(wallet.pubkey())
+                    .chain(mints.iter().map(|mint| wallet.token_account(mint)))
+            })
+            .collect();
+
+        let accounts = get_accounts(rpc_client, &addresses).await?;
+
+        for (wallet, accounts) in self.wallets.iter().zip(accounts.chunks(1 + mints.len())) {
+            wallet.set_lamports(accounts[0].as_ref().map_or(0, |account| account.lamports));
+
+            for (mint, account) in mints.iter().zip(&accounts[1..]) {
+                let amount = match account {
+                    Some(account) => token_account_amount(&account.data)?,
+                    None => 0,
+                };
+                wallet.set_token_balance(*mint, amount);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn print_summary(&self) {
+        println!("Wallet farm: {} wallets", self.len());
+        for wallet in &self.wallets {
+            let roles: Vec<&str> = wallet.roles.iter().map(|role| role.as_str()).collect();
+            println!(
+                "  {:<16} {} [{}] {} lamports",
+                wallet.label,
+                wallet.pubkey(),
+                roles.join(", "),
+                wallet.lamports()
+            );
+        }
+    }
+}
+
+/// `getMultipleAccounts` for any number of addresses.
+pub(crate) async fn get_accounts(
+    rpc_client: &RpcClient,
+    addresses: &[Pubkey],
+) -> ClientResult<Vec<Option<Account>>> {
+    let mut accounts = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+        accounts.extend(rpc_client.get_multiple_accounts(chunk).await?);
+    }
+
+    Ok(accounts)
+}
+
+fn local_wallets(keys: Vec<WalletKeypair>) -> Vec<FarmWallet> {
+    keys.into_iter()
+        .map(|(label, roles, keypair)| FarmWallet::new(label, Arc::new(keypair), roles))
+        .collect()
+}
+
+fn dir_wallets(dir: &Path) -> ClientResult<Vec<WalletKeypair>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|err| io_error(dir, err))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let label = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let input = fs::read_to_string(path).map_err(|err| io_error(path, err))?;
+            let keypair = try_parse_keypair(input.trim()).map_err(|why| {
+                ClientError::Parse(format!("keypair file {}", path.display()), why)
+            })?;
+
+            let roles = roles_from_label(&label);
+            Ok((label, roles, keypair))
+        })
+        .collect()
+}
+
+fn json_wallets(input: &str) -> ClientResult<Vec<WalletKeypair>> {
+    let entries: Vec<Value> = serde_json::from_str(input)?;
+
+    entries
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| parse_entry(idx, entry))
+        .collect()
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> ClientError {
+    ClientError::Other(format!("{}: {}", path.display(), err))
+}
+
+pub(crate) fn roles_from_label(label: &str) -> BTreeSet<WalletRole> {
+    let label = label.to_lowercase().replace('-', "_");
+
+    let role = WalletRole::ALL
+        .into_iter()
+        .find(|role| label.starts_with(role.as_str()));
+
+    match role {
+        Some(role) => BTreeSet::from([role]),
+        None => BTreeSet::from([WalletRole::Buyer, WalletRole::Seller]),
+    }
+}
+
+fn parse_entry(idx: usize, entry: &Value) -> ClientResult<WalletKeypair> {
+    let parse_err = |why: String| ClientError::Parse(format!("wallet entry {}", idx), why);
+
+    let (label, roles, keypair) = match entry {
+        Value::Object(fields) => {
+            let keypair = fields
+                .get("keypair")
+                .ok_or_else(|| parse_err("missing keypair".to_string()))?;
+            let label = fields
+                .get("label")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("wallet-{}", idx));
+            let roles = match fields.get("roles") {
+                Some(Value::Array(roles)) => roles
+                    .iter()
+                    .map(|role| {
+                        role.as_str()
+                            .ok_or_else(|| parse_err("roles must be strings".to_string()))?
+                            .parse()
+                    })
+                    .collect::<ClientResult<BTreeSet<WalletRole>>>()?,
+                Some(_) => return Err(parse_err("roles must be an array".to_string())),
+                None => roles_from_label(&label),
+            };
+
+            (label, roles, keypair)
+        }
+        keypair => {
+            let label = format!("wallet-{}", idx);
+            let roles = roles_from_label(&label);
+            (label, roles, keypair)
+        }
+    };
+
+    let keypair = match keypair {
+        Value::String(encoded) => try_parse_keypair(encoded),
+        bytes => try_parse_keypair(&bytes.to_string()),
+    }
+    .map_err(parse_err)?;
+
+    Ok((label, roles, keypair))
+}
+
+pub(crate) fn token_account_amount(data: &[u8]) -> ClientResult<u64> {
+    data.get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or_else(|| ClientError::InvalidData("token account data too short".to_string()))
+}
@@ -1,5 +1,7 @@
 pub mod buy_ix;
 pub mod sell_ix;
 pub mod types;
+pub mod pumpfun_accounts;
 pub mod pumpfun_buy;
 pub mod pumpfun_sell;
+pub mod pumpfun_launch;
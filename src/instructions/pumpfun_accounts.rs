@@ -0,0 +1,111 @@
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_pumpfun_decoder::{accounts::global::Global, instructions::buy::BuyInstructionAccounts};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, system_program};
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::{
+    error::{ClientError, ClientResult},
+    instructions::{
+        pumpfun_buy::{
+            PUMPFUN_EVENT_AUTHORITY, PUMPFUN_FEE_RECIPIENT, PUMPFUN_GLOBAL, PUMPFUN_PROGRAM,
+        },
+        types::BondingCurveAccount,
+    },
+};
+
+/// Bonding curve PDA of `mint`.
+pub fn bonding_curve_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], &PUMPFUN_PROGRAM).0
+}
+
+/// Vault collecting the creator fees of every token `creator` launched.
+pub fn creator_vault_pda(creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"creator-vault", creator.as_ref()], &PUMPFUN_PROGRAM).0
+}
+
+/// Accounts of a buy of `mint` by `buyer`, for `BuyExactInInstructionAccountsExt::get_buy_ix`.
+pub fn buy_instruction_accounts(
+    mint: &Pubkey,
+    creator: &Pubkey,
+    buyer: &Pubkey,
+) -> BuyInstructionAccounts {
+    let bonding_curve = bonding_curve_pda(mint);
+
+    BuyInstructionAccounts {
+        global: PUMPFUN_GLOBAL,
+        fee_recipient: PUMPFUN_FEE_RECIPIENT,
+        mint: *mint,
+        bonding_curve,
+        associated_bonding_curve: get_associated_token_address(&bonding_curve, mint),
+        associated_user: get_associated_token_address(buyer, mint),
+        user: *buyer,
+        system_program: system_program::id(),
+        token_program: spl_token::id(),
+        creator_vault: creator_vault_pda(creator),
+        event_authority: PUMPFUN_EVENT_AUTHORITY,
+        program: PUMPFUN_PROGRAM,
+    }
+}
+
+fn decode_bonding_curve(data: &[u8]) -> ClientResult<BondingCurveAccount> {
+    BondingCurveAccount::deserialize(data).ok_or(ClientError::BondingCurveError(
+        "invalid bonding curve account data",
+    ))
+}
+
+fn decode_global(data: &[u8]) -> ClientResult<Global> {
+    Global::deserialize(data).ok_or(ClientError::InvalidData(
+        "invalid pump.fun global account data".to_string(),
+    ))
+}
+
+/// Fetches the bonding curve of `mint`.
+pub async fn fetch_bonding_curve(
+    rpc_client: &RpcClient,
+    mint: &Pubkey,
+) -> ClientResult<BondingCurveAccount> {
+    let account = rpc_client
+        .get_multiple_accounts(&[bonding_curve_pda(mint)])
+        .await?
+        .pop()
+        .flatten()
+        .ok_or(ClientError::BondingCurveNotFound)?;
+
+    decode_bonding_curve(&account.data)
+}
+
+/// Fetches the pump.fun global config holding the fee settings.
+pub async fn fetch_global(rpc_client: &RpcClient) -> ClientResult<Global> {
+    let account = rpc_client.get_account(&PUMPFUN_GLOBAL).await?;
+
+    decode_global(&account.data)
+}
+
+/// Fetches the bonding curve of `mint` and the global config in one request.
+pub async fn fetch_curve_and_global(
+    rpc_client: &RpcClient,
+    mint: &Pubkey,
+) -> ClientResult<(BondingCurveAccount, Global)> {
+    let accounts = rpc_client
+        .get_multiple_accounts(&[bonding_curve_pda(mint), PUMPFUN_GLOBAL])
+        .await?;
+
+    let curve = accounts
+        .first()
+        .cloned()
+        .flatten()
+        .ok_or(ClientError::BondingCurveNotFound)?;
+    let global = accounts
+        .get(1)
+        .cloned()
+        .flatten()
+        .ok_or(ClientError::InvalidData(
+            "pump.fun global account not found".to_string(),
+        ))?;
+
+    Ok((
+        decode_bonding_curve(&curve.data)?,
+        decode_global(&global.data)?,
+    ))
+}
@@ -1,4 +1,5 @@
 use anyhow::{anyhow, Result};
+use carbon_pumpfun_decoder::accounts::global::Global;
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
@@ -9,6 +10,9 @@ use spl_associated_token_account::{
     instruction::create_associated_token_account_idempotent,
 };
 use solana_sdk::system_program;
+use std::sync::Arc;
+
+use crate::{signer::TxSigner, utils::BondingCurveQuote};
 
 
 // PumpFun specific constants
@@ -22,12 +26,18 @@ pub const PUMPFUN_EVENT_AUTHORITY: Pubkey = solana_sdk::pubkey!("Ce6TQqeHC9p8Ket
 
 
 // Initial reserves and supply
-pub const INITIAL_VIRTUAL_SOL_RESERVES: u64 = 29_998_065_120;
-pub const INITIAL_VIRTUAL_TOKEN_RESERVES: u64 = 73_000_000_000_000;
+pub const INITIAL_VIRTUAL_SOL_RESERVES: u64 = 30_000_000_000;
+pub const INITIAL_VIRTUAL_TOKEN_RESERVES: u64 = 1_073_000_000_000_000;
+pub const INITIAL_REAL_TOKEN_RESERVES: u64 = 793_100_000_000_000;
+pub const TOKEN_TOTAL_SUPPLY: u64 = 1_000_000_000_000_000;
+
+// Default fees of the global config, used where the curve does not exist yet
+pub const PUMPFUN_FEE_BASIS_POINTS: u64 = 95;
+pub const PUMPFUN_CREATOR_FEE_BASIS_POINTS: u64 = 5;
 
 #[derive(Debug)]
 pub struct TokenCreationParams {
-    pub deployer_keypair: Keypair,
+    pub deployer: Arc<dyn TxSigner>,
     pub token_mint_keypair: Keypair,
     pub metadata_uri: String,
     pub dev_buy_amount: f64,
@@ -39,14 +49,15 @@ pub struct TokenCreationParams {
 pub struct PumpFun;
 
 impl PumpFun {
-    // Creates token creation + buy instructions
+    // Creates token creation + buy instructions, the dev buy quoted on the fresh curve of `global`
     pub fn get_create_buy_instruction(
         params: &TokenCreationParams,
+        global: &Global,
     ) -> Result<Vec<Instruction>> {
         println!("Building token creation + dev buy instructions...");
         
         let TokenCreationParams {
-            deployer_keypair,
+            deployer,
             token_mint_keypair,
             metadata_uri,
             dev_buy_amount,
@@ -56,7 +67,7 @@ impl PumpFun {
         } = params;
 
         let mint_pubkey = token_mint_keypair.pubkey();
-        let deployer_pubkey = deployer_keypair.pubkey();
+        let deployer_pubkey = deployer.pubkey();
 
         println!("Mint Address: {}", mint_pubkey);
         println!("Deployer Address: {}", deployer_pubkey);
@@ -128,6 +139,7 @@ impl PumpFun {
             associated_bonding_curve,
             deployer_token_account,
             deployer_pubkey,
+            global,
         )?;
 
         println!("All instructions built successfully");
@@ -173,32 +185,55 @@ impl PumpFun {
         associated_bonding_curve: Pubkey,
         deployer_token_account: Pubkey,
         deployer_pubkey: Pubkey,
+        global: &Global,
     ) -> Result<Instruction> {
         println!("Calculating dev buy amounts...");
-        let buy_amount_lamports = (dev_buy_amount * 1_000_000_000.0) as u64;
-        println!("Buy amount in lamports: {}", buy_amount_lamports);
-        
-        let (tokens_to_receive, max_sol_cost, _) = Self::get_amount_out(
-            buy_amount_lamports,
-            INITIAL_VIRTUAL_SOL_RESERVES,
-            INITIAL_VIRTUAL_TOKEN_RESERVES,
-        );
-        println!("Tokens to receive: {}", tokens_to_receive);
+        let (tokens_with_slippage, max_sol_cost) = Self::dev_buy_amounts(*dev_buy_amount, global)?;
+        println!("Tokens with slippage: {} (15% slippage)", tokens_with_slippage);
         println!("Max SOL cost: {}", max_sol_cost);
-        
+
+        println!("Creating dev buy instruction...");
+        let instruction = Self::create_buy_instruction(
+            mint_pubkey,
+            bonding_curve,
+            associated_bonding_curve,
+            deployer_token_account,
+            deployer_pubkey,
+            deployer_pubkey,
+            tokens_with_slippage,
+            max_sol_cost,
+        )?;
+
+        println!("Dev buy instruction created successfully");
+        Ok(instruction)
+    }
+
+    // Tokens the dev buy of `dev_buy_amount` SOL asks for and its max SOL cost
+    pub fn dev_buy_amounts(dev_buy_amount: f64, global: &Global) -> Result<(u64, u64)> {
+        let buy_amount_lamports = (dev_buy_amount * 1_000_000_000.0) as u64;
+
+        let (tokens_to_receive, max_sol_cost) = Self::get_amount_out(buy_amount_lamports, global)?;
         let tokens_with_slippage = (tokens_to_receive * 85) / 100; // 15% slippage
-        println!("Tokens with slippage: {} (15% slippage)", tokens_with_slippage);
 
-        println!("Building dev buy instruction data...");
-        let buy_instruction_data = Self::build_buy_instruction_data(tokens_with_slippage, max_sol_cost);
-        println!("Buy instruction data built ({} bytes)", buy_instruction_data.len());
-        
-        let creator_vault = get_creator_vault_pda(&deployer_pubkey)?;
-        println!("Creator vault: {}", creator_vault);
+        Ok((tokens_with_slippage, max_sol_cost))
+    }
 
-        println!("Creating dev buy instruction...");
-       // let event_authority = get_event_authority_pda()?;
-        let instruction = Instruction {
+    // Creates a buy of exactly `token_amount` for `buyer_pubkey` on a curve created by `creator`
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_buy_instruction(
+        mint_pubkey: Pubkey,
+        bonding_curve: Pubkey,
+        associated_bonding_curve: Pubkey,
+        buyer_token_account: Pubkey,
+        buyer_pubkey: Pubkey,
+        creator: Pubkey,
+        token_amount: u64,
+        max_sol_cost: u64,
+    ) -> Result<Instruction> {
+        let buy_instruction_data = Self::build_buy_instruction_data(token_amount, max_sol_cost);
+        let creator_vault = get_creator_vault_pda(&creator)?;
+
+        Ok(Instruction {
             program_id: PUMPFUN_PROGRAM,
             accounts: vec![
                 AccountMeta::new_readonly(PUMPFUN_GLOBAL, false),
@@ -206,21 +241,18 @@ impl PumpFun {
                 AccountMeta::new(mint_pubkey, false),
                 AccountMeta::new(bonding_curve, false),
                 AccountMeta::new(associated_bonding_curve, false),
-                AccountMeta::new(deployer_token_account, false),
-                AccountMeta::new(deployer_pubkey, true),
+                AccountMeta::new(buyer_token_account, false),
+                AccountMeta::new(buyer_pubkey, true),
                 AccountMeta::new_readonly(system_program::id(), false),
                 AccountMeta::new_readonly(spl_token::id(), false),
                 AccountMeta::new(creator_vault, false),
                 AccountMeta::new_readonly(PUMPFUN_EVENT_AUTHORITY, false),
                 AccountMeta::new_readonly(PUMPFUN_PROGRAM, false),
                 AccountMeta::new(global_volume_accumulator_pda(), false),
-                AccountMeta::new(user_volume_accumulator_pda(&deployer_pubkey), false),
+                AccountMeta::new(user_volume_accumulator_pda(&buyer_pubkey), false),
             ],
             data: buy_instruction_data,
-        };
-
-        println!("Dev buy instruction created successfully");
-        Ok(instruction)
+        })
     }
 
     // Builds token data for creation instruction  
@@ -277,22 +309,11 @@ impl PumpFun {
         buy_instruction_data
     }
 
-    // Calculates amount out for bonding curve  
-    fn get_amount_out(
-        amount_in: u64,
-        virtual_sol_reserves: u64,
-        virtual_token_reserves: u64,
-    ) -> (u64, u64, u64) {
-        if virtual_sol_reserves == 0 {
-            return (0, amount_in, 0);
-        }
+    // Calculates tokens out of the fresh bonding curve and the max SOL cost
+    fn get_amount_out(amount_in: u64, global: &Global) -> Result<(u64, u64)> {
+        let tokens_out = BondingCurveQuote::initial_with(global).buy_exact_sol_in(amount_in)?;
 
-        let tokens_out = (amount_in as u128)
-            .checked_mul(virtual_token_reserves as u128)
-            .and_then(|result| result.checked_div(virtual_sol_reserves as u128))
-            .unwrap_or(0) as u64;
-        
-        (tokens_out, amount_in, 0)
+        Ok((tokens_out, amount_in))
     }
 }
 
@@ -0,0 +1,238 @@
+use anyhow::{Result, anyhow};
+use carbon_pumpfun_decoder::accounts::global::Global;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, hash::Hash, instruction::Instruction, pubkey::Pubkey,
+    signature::Signer, transaction::VersionedTransaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use std::sync::Arc;
+
+use crate::{
+    farm::FarmWallet,
+    instructions::{
+        pumpfun_accounts::bonding_curve_pda,
+        pumpfun_buy::{PumpFun, TokenCreationParams},
+    },
+    service::{JITO_MAX_BUNDLE_TXS, Tips, TxSender},
+    utils::{BondingCurveQuote, TxLifetime, try_build_and_sign},
+};
+
+/// Farm buys that fit into the bundle next to the create transaction.
+pub const MAX_LAUNCH_BUYS: usize = JITO_MAX_BUNDLE_TXS - 1;
+
+/// Follow-up buy of `sol_amount` lamports (fees included) from a farm wallet.
+#[derive(Debug, Clone)]
+pub struct LaunchBuy {
+    pub wallet: Arc<FarmWallet>,
+    pub sol_amount: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotedBuy {
+    pub buyer: Pubkey,
+    pub token_amount: u64,
+    pub max_sol_cost: u64,
+}
+
+/// Buys of a launch in bundle order, each quoted against the curve the previous ones leave.
+#[derive(Debug, Clone)]
+pub struct LaunchQuote {
+    pub dev_buy: QuotedBuy,
+    pub buys: Vec<QuotedBuy>,
+    /// Curve once every buy of the bundle has landed.
+    pub curve: BondingCurveQuote,
+    /// Global config the buys were quoted with, the create transaction quotes its dev buy with it.
+    pub global: Global,
+}
+
+impl LaunchQuote {
+    pub fn total_tokens(&self) -> u64 {
+        self.dev_buy.token_amount + self.buys.iter().map(|buy| buy.token_amount).sum::<u64>()
+    }
+}
+
+pub struct PumpFunLaunch;
+
+impl PumpFunLaunch {
+    // Quotes the dev buy and then every farm buy on the curve left by the ones before it, with
+    // the fees of `global`. Token amounts are cut by `slippage_pct` like the sniper's, so a buy
+    // still fits into its `sol_amount` if the fees differ from the quote.
+    pub fn quote(
+        params: &TokenCreationParams,
+        buys: &[LaunchBuy],
+        global: &Global,
+        slippage_pct: f64,
+    ) -> Result<LaunchQuote> {
+        if buys.len() > MAX_LAUNCH_BUYS {
+            return Err(anyhow!(
+                "A launch bundle fits at most {} farm buys, got {}",
+                MAX_LAUNCH_BUYS,
+                buys.len()
+            ));
+        }
+        if !(0.0..100.0).contains(&slippage_pct) {
+            return Err(anyhow!(
+                "Slippage must be in [0, 100) percent, got {}",
+                slippage_pct
+            ));
+        }
+        let slippage_bps = (slippage_pct * 100.0) as u128;
+
+        let (dev_tokens, dev_max_sol_cost) =
+            PumpFun::dev_buy_amounts(params.dev_buy_amount, global)?;
+        let dev_buy = QuotedBuy {
+            buyer: params.deployer.pubkey(),
+            token_amount: dev_tokens,
+            max_sol_cost: dev_max_sol_cost,
+        };
+        let mut curve = BondingCurveQuote::initial_with(global).after_buy(dev_tokens)?;
+
+        let mut quoted = Vec::with_capacity(buys.len());
+        for buy in buys {
+            // Nothing can land between the bundled buys, only the fees can be off
+            let quoted_tokens = curve.buy_exact_sol_in(buy.sol_amount)?;
+            let token_amount = (quoted_tokens as u128 * (10_000 - slippage_bps) / 10_000) as u64;
+            if token_amount == 0 {
+                return Err(anyhow!(
+                    "Buy of {} lamports from {} gets no tokens",
+                    buy.sol_amount,
+                    buy.wallet.pubkey()
+                ));
+            }
+
+            quoted.push(QuotedBuy {
+                buyer: buy.wallet.pubkey(),
+                token_amount,
+                max_sol_cost: buy.sol_amount,
+            });
+            curve = curve.after_buy(token_amount)?;
+        }
+
+        Ok(LaunchQuote {
+            dev_buy,
+            buys: quoted,
+            curve,
+            global: global.clone(),
+        })
+    }
+
+    // Creates ATA + buy instructions for one quoted farm buy
+    pub fn get_buy_instructions(
+        mint_pubkey: Pubkey,
+        creator: Pubkey,
+        buy: &QuotedBuy,
+    ) -> Result<Vec<Instruction>> {
+        let bonding_curve = bonding_curve_pda(&mint_pubkey);
+        let associated_bonding_curve = get_associated_token_address(&bonding_curve, &mint_pubkey);
+        let buyer_token_account = get_associated_token_address(&buy.buyer, &mint_pubkey);
+
+        let ata_instruction = create_associated_token_account_idempotent(
+            &buy.buyer,
+            &buy.buyer,
+            &mint_pubkey,
+            &spl_token::id(),
+        );
+        let buy_instruction = PumpFun::create_buy_instruction(
+            mint_pubkey,
+            bonding_curve,
+            associated_bonding_curve,
+            buyer_token_account,
+            buy.buyer,
+            creator,
+            buy.token_amount,
+            buy.max_sol_cost,
+        )?;
+
+        Ok(vec![ata_instruction, buy_instruction])
+    }
+
+    // Signs the create + dev buy transaction followed by one transaction per farm buy of
+    // `quote`. Only the create transaction tips, so the fee payer pays it; the bundle lands as a
+    // whole or not at all.
+    pub fn get_launch_bundle(
+        params: &TokenCreationParams,
+        fee_payer: &FarmWallet,
+        buys: &[LaunchBuy],
+        quote: &LaunchQuote,
+        sender: &dyn TxSender,
+        tips: &Tips,
+        blockhash: Hash,
+    ) -> Result<Vec<VersionedTransaction>> {
+        println!("Building launch bundle with {} farm buys...", buys.len());
+
+        if quote.buys.len() != buys.len() {
+            return Err(anyhow!(
+                "Launch quote has {} buys, expected {}",
+                quote.buys.len(),
+                buys.len()
+            ));
+        }
+        let mint_pubkey = params.token_mint_keypair.pubkey();
+        let creator = params.deployer.pubkey();
+        let lifetime = TxLifetime::Blockhash(blockhash);
+
+        let mut bundle = Vec::with_capacity(1 + buys.len());
+
+        // Signers per transaction, the first one pays
+        let mut transactions: Vec<(Vec<&dyn Signer>, Vec<Instruction>)> = vec![(
+            vec![
+                &*fee_payer.signer,
+                &*params.deployer,
+                &params.token_mint_keypair,
+            ],
+            PumpFun::get_create_buy_instruction(params, &quote.global)?,
+        )];
+        for (buy, quoted) in buys.iter().zip(&quote.buys) {
+            transactions.push((
+                vec![&*buy.wallet.signer],
+                Self::get_buy_instructions(mint_pubkey, creator, quoted)?,
+            ));
+        }
+
+        for (idx, (signers, ixs)) in transactions.into_iter().enumerate() {
+            let payer = signers[0];
+            let ixs = if idx == 0 {
+                sender.add_tip_ix(Tips {
+                    tip_addr_idx: sender.random_tip_idx(),
+                    payer: payer.pubkey(),
+                    pure_ix: ixs,
+                    ..tips.clone()
+                })
+            } else {
+                compute_budget_instructions(tips)
+                    .into_iter()
+                    .chain(ixs)
+                    .collect()
+            };
+
+            bundle.push(try_build_and_sign(
+                payer,
+                &signers[1..],
+                &ixs,
+                &[],
+                &lifetime,
+            )?);
+        }
+
+        println!(
+            "Launch bundle built: {} transactions, {} tokens bought in total",
+            bundle.len(),
+            quote.total_tokens()
+        );
+        Ok(bundle)
+    }
+}
+
+// Same compute budget as `TxSender::add_tip_ix`, without the tip
+fn compute_budget_instructions(tips: &Tips) -> Vec<Instruction> {
+    tips.cu
+        .map(|cu| ComputeBudgetInstruction::set_compute_unit_limit(cu as u32))
+        .into_iter()
+        .chain(
+            tips.priority_fee_micro_lamport
+                .map(ComputeBudgetInstruction::set_compute_unit_price),
+        )
+        .collect()
+}
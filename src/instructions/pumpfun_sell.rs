@@ -2,16 +2,18 @@ use anyhow::{anyhow, Result};
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    signature::Keypair,
-    signer::Signer,
     system_program,
 };
 use spl_associated_token_account::get_associated_token_address;
-use spl_token::state::{Account, AccountState};
 use std::sync::Arc;
 //use crate::config::PUBKEY;
 use solana_client::nonblocking::rpc_client::RpcClient;
 
+use crate::{
+    instructions::{pumpfun_accounts::fetch_curve_and_global, types::TradeEventTemp},
+    utils::BondingCurveQuote,
+};
+
 
 // PumpFun specific constants
 pub const PUMPFUN_PROGRAM: Pubkey = solana_sdk::pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
@@ -46,6 +48,7 @@ impl PumpFunSell {
     }
 
     // Creates sell instruction that sells all tokens in ATA
+    #[allow(clippy::too_many_arguments)]
     pub fn create_sell_all_instruction(
         mint_pubkey: Pubkey,
         bonding_curve: Pubkey,
@@ -90,7 +93,7 @@ impl PumpFunSell {
     pub fn create_ata_close_instruction(
         user_token_account: Pubkey,
         user_pubkey: Pubkey,
-        mint_pubkey: Pubkey,
+        _mint_pubkey: Pubkey,
     ) -> Result<Instruction> {
         println!("Creating ATA close instruction...");
         
@@ -113,20 +116,12 @@ impl PumpFunSell {
         mint_pubkey: Pubkey,
         user_token_account: Pubkey,
         user_pubkey: Pubkey,
-        creator_vault: Pubkey,
         slippage_percentage: f64,
     ) -> Result<Vec<Instruction>> {
         println!("Building enhanced sell all + close ATA instructions...");
         
         let bonding_curve = get_pda(&mint_pubkey, &PUMPFUN_PROGRAM)?;
-        let (metadata_account, _) = Pubkey::find_program_address(
-            &[b"metadata", METAPLEX_PROGRAM_ID.as_ref(), mint_pubkey.as_ref()],
-            &METAPLEX_PROGRAM_ID,
-        );
-        let deployer_pubkey = user_pubkey;
         let associated_bonding_curve = get_associated_token_address(&bonding_curve, &mint_pubkey);
-        let deployer_token_account = get_associated_token_address(&deployer_pubkey, &mint_pubkey);
-
         
         // Query the actual token balance in the ATA
         let token_balance = self.get_ata_token_balance(&user_token_account).await?;
@@ -136,23 +131,22 @@ impl PumpFunSell {
             return Err(anyhow!("No tokens to sell - ATA balance is 0"));
         }
         
-        // Calculate expected SOL output using bonding curve math
-        let expected_sol_output = Self::calculate_sell_sol_amount(
-            token_balance,
-            &mint_pubkey.to_string(),
-        )?;
-        
-        // Apply slippage protection - use a much more conservative approach
+        // Quote the sell against the current curve and fee settings
+        let (curve, global) = fetch_curve_and_global(&self.rpc_client, &mint_pubkey).await?;
+        let expected_sol_output =
+            BondingCurveQuote::new(&curve, &global).sell_exact_tokens_in(token_balance)?;
+
+        // Creator fees go to the vault of whoever created the token, not of the seller
+        let creator_vault = get_creator_vault_pda(&curve.creator)?;
+        println!("Creator Vault: {}", creator_vault);
+
+        // Apply slippage protection
         let slippage_bps = (slippage_percentage * 100.0) as u128;
-        let min_sol_output = min_amount_with_slippage(expected_sol_output as u64, slippage_bps);
-        
-        // Ensure minimum output is very low to avoid slippage errors
-        let final_min_output = std::cmp::min(min_sol_output, 100); // Max 100 lamports minimum (very conservative)
-        
+        let min_sol_output = min_amount_with_slippage(expected_sol_output, slippage_bps);
+
         println!("Expected SOL Output: {} lamports", expected_sol_output);
         println!("Minimum SOL Output (with {}% slippage): {} lamports", slippage_percentage, min_sol_output);
-        println!("Final Minimum Output (capped): {} lamports", final_min_output);
-        
+
         // Create sell instruction
         let sell_instruction = Self::create_sell_all_instruction(
             mint_pubkey,
@@ -162,7 +156,7 @@ impl PumpFunSell {
             user_pubkey,
             creator_vault,
             token_balance,
-            final_min_output,
+            min_sol_output,
         )?;
 
         // Create ATA close instruction
@@ -178,6 +172,133 @@ impl PumpFunSell {
         Ok(vec![sell_instruction, close_instruction])
     }
 
+    // Builds sell instructions for `token_amount` quoted against an already known curve, e.g. the
+    // one of the last trade event. The ATA is only closed with `close_ata`, for the final sell.
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_sell_instructions_with_quote(
+        mint_pubkey: Pubkey,
+        user_pubkey: Pubkey,
+        creator: Pubkey,
+        token_amount: u64,
+        quote: &BondingCurveQuote,
+        slippage_percentage: f64,
+        close_ata: bool,
+    ) -> Result<Vec<Instruction>> {
+        if token_amount == 0 {
+            return Err(anyhow!("No tokens to sell - amount is 0"));
+        }
+
+        let (bonding_curve, associated_bonding_curve, user_token_account, creator_vault) =
+            Self::get_sell_parameters(mint_pubkey, user_pubkey, creator)?;
+
+        // Apply slippage protection
+        let expected_sol_output = quote.sell_exact_tokens_in(token_amount)?;
+        let slippage_bps = (slippage_percentage * 100.0) as u128;
+        let min_sol_output = min_amount_with_slippage(expected_sol_output, slippage_bps);
+
+        println!("Selling {} tokens, expected SOL Output: {} lamports", token_amount, expected_sol_output);
+        println!("Minimum SOL Output (with {}% slippage): {} lamports", slippage_percentage, min_sol_output);
+
+        let mut instructions = vec![Self::create_sell_all_instruction(
+            mint_pubkey,
+            bonding_curve,
+            associated_bonding_curve,
+            user_token_account,
+            user_pubkey,
+            creator_vault,
+            token_amount,
+            min_sol_output,
+        )?];
+        if close_ata {
+            instructions.push(Self::create_ata_close_instruction(
+                user_token_account,
+                user_pubkey,
+                mint_pubkey,
+            )?);
+        }
+
+        Ok(instructions)
+    }
+
+    // Builds sell instructions for any PumpFun mint straight from one of its trade events, which
+    // carries both the curve state and the creator, so no RPC round trip is needed
+    pub fn get_sell_instructions_from_event(
+        event: &TradeEventTemp,
+        user_pubkey: Pubkey,
+        token_amount: u64,
+        slippage_percentage: f64,
+        close_ata: bool,
+    ) -> Result<Vec<Instruction>> {
+        Self::get_sell_instructions_with_quote(
+            event.mint,
+            user_pubkey,
+            event.creator,
+            token_amount,
+            &BondingCurveQuote::from_trade_event(event),
+            slippage_percentage,
+            close_ata,
+        )
+    }
+
+    // Sells exactly `token_amount` at the current curve price and keeps the ATA open
+    pub async fn sell_amount(
+        &self,
+        mint_pubkey: Pubkey,
+        user_pubkey: Pubkey,
+        token_amount: u64,
+        slippage_percentage: f64,
+    ) -> Result<Vec<Instruction>> {
+        println!("🚀 Selling {} tokens of mint: {}", token_amount, mint_pubkey);
+
+        let user_token_account = get_associated_token_address(&user_pubkey, &mint_pubkey);
+        let token_balance = self.get_ata_token_balance(&user_token_account).await?;
+        if token_amount > token_balance {
+            return Err(anyhow!(
+                "Cannot sell {} tokens - ATA balance is {}",
+                token_amount,
+                token_balance
+            ));
+        }
+
+        let (curve, global) = fetch_curve_and_global(&self.rpc_client, &mint_pubkey).await?;
+        Self::get_sell_instructions_with_quote(
+            mint_pubkey,
+            user_pubkey,
+            curve.creator,
+            token_amount,
+            &BondingCurveQuote::new(&curve, &global),
+            slippage_percentage,
+            false,
+        )
+    }
+
+    // Sells `percentage` (0-100] of the ATA balance. Selling 100% also closes the ATA.
+    pub async fn sell_percentage(
+        &self,
+        mint_pubkey: Pubkey,
+        user_pubkey: Pubkey,
+        percentage: f64,
+        slippage_percentage: f64,
+    ) -> Result<Vec<Instruction>> {
+        if !(percentage > 0.0 && percentage <= 100.0) {
+            return Err(anyhow!("Sell percentage must be in (0, 100], got {}", percentage));
+        }
+        if percentage == 100.0 {
+            return self
+                .sell_all_tokens_and_close_ata(mint_pubkey, user_pubkey, slippage_percentage)
+                .await;
+        }
+
+        let user_token_account = get_associated_token_address(&user_pubkey, &mint_pubkey);
+        let token_balance = self.get_ata_token_balance(&user_token_account).await?;
+        let percentage_bps = (percentage * 100.0) as u128;
+        let token_amount = (token_balance as u128 * percentage_bps / TEN_THOUSAND) as u64;
+        println!("{}% of {} tokens is {} tokens", percentage, token_balance, token_amount);
+
+        self.sell_amount(mint_pubkey, user_pubkey, token_amount, slippage_percentage)
+            .await
+    }
+
     // Builds sell instruction data
     fn build_sell_instruction_data(tokens_to_sell: u64, min_sol_output: u64) -> Vec<u8> {
         let mut sell_instruction_data = vec![
@@ -195,27 +316,12 @@ impl PumpFunSell {
         sell_instruction_data
     }
 
-    // Calculates sell SOL amount (simplified bonding curve math)
-    fn calculate_sell_sol_amount(
-        amount_specified: u64,
-        mint_str: &str,
-    ) -> Result<u128> {
-        // Use a much more conservative estimate for SOL output
-        // This should be much lower to avoid slippage errors
-        let estimated_sol_per_token = 0.000000001; // Very conservative estimate (1 nano SOL per token)
-        let sol_output = (amount_specified as f64 * estimated_sol_per_token * 1_000_000_000.0) as u128;
-        
-        // Ensure minimum output is at least 1 lamport
-        let min_output = if sol_output == 0 { 1 } else { sol_output };
-        
-        println!("Calculated SOL output: {} lamports for {} tokens", min_output, amount_specified);
-        Ok(min_output)
-    }
-
     // Utility function to get all required parameters for selling tokens
+    // `creator` is the token creator from the bonding curve account or a trade event
     pub fn get_sell_parameters(
         mint_pubkey: Pubkey,
         user_pubkey: Pubkey,
+        creator: Pubkey,
     ) -> Result<(Pubkey, Pubkey, Pubkey, Pubkey)> {
         println!("Getting sell parameters for mint: {}", mint_pubkey);
         
@@ -232,13 +338,13 @@ impl PumpFunSell {
         println!("User Token Account: {}", user_token_account);
         
         // Calculate creator vault PDA
-        let creator_vault = get_creator_vault_pda(&user_pubkey)?;
+        let creator_vault = get_creator_vault_pda(&creator)?;
         println!("Creator Vault: {}", creator_vault);
         
         Ok((bonding_curve, associated_bonding_curve, user_token_account, creator_vault))
     }
 
-    // Convenience function to sell all tokens and close ATA in one call
+    // Convenience function to sell all tokens and close ATA in one call, for any PumpFun mint
     pub async fn sell_all_tokens_and_close_ata(
         &self,
         mint_pubkey: Pubkey,
@@ -247,16 +353,14 @@ impl PumpFunSell {
     ) -> Result<Vec<Instruction>> {
         println!("🚀 Selling all tokens and closing ATA for mint: {}", mint_pubkey);
         
-        // Get all required parameters
-        let (bonding_curve, associated_bonding_curve, user_token_account, creator_vault) = 
-            Self::get_sell_parameters(mint_pubkey, user_pubkey)?;
+        let user_token_account = get_associated_token_address(&user_pubkey, &mint_pubkey);
+        println!("User Token Account: {}", user_token_account);
         
-        // Create instructions
+        // Create instructions, the creator vault comes from the bonding curve account
         let instructions = self.get_sell_all_and_close_instructions_with_balance(
             mint_pubkey,
             user_token_account,
             user_pubkey,
-            creator_vault,
             slippage_percentage,
         ).await?;
         
@@ -268,7 +372,7 @@ impl PumpFunSell {
 // Calculates minimum amount with slippage
 pub fn min_amount_with_slippage(input_amount: u64, slippage_bps: u128) -> u64 {
     let min_amount = u128::from(input_amount)
-        .checked_mul(TEN_THOUSAND.checked_sub(slippage_bps).unwrap_or(0))
+        .checked_mul(TEN_THOUSAND.saturating_sub(slippage_bps))
         .unwrap_or(0)
         .checked_div(TEN_THOUSAND)
         .unwrap_or(0);
@@ -29,3 +29,18 @@ pub struct TradeEventTemp {
     pub current_sol_volume: u64,
     pub last_update_timestamp: i64,
 }
+
+/// On-chain bonding curve account. The decoder's `BondingCurve` predates the `creator` field.
+#[derive(
+    CarbonDeserialize, Debug, PartialEq, Eq, Clone, Hash,
+)]
+#[carbon(discriminator = "0x17b7f83760d8ac60")]
+pub struct BondingCurveAccount {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    pub complete: bool,
+    pub creator: Pubkey,
+}
@@ -0,0 +1,219 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    KeyInit, XChaCha20Poly1305, XNonce,
+    aead::{Aead, Payload},
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::error::{ClientError, ClientResult};
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Argon2id memory in KiB, iterations and lanes of new keystores.
+pub const DEFAULT_KDF_COST: (u32, u32, u32) = (64 * 1024, 3, 1);
+
+/// How the keystore key is derived from the passphrase, stored so the cost can change later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub algorithm: String,
+    /// Base64.
+    pub salt: String,
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl KdfParams {
+    /// Argon2id with a fresh salt.
+    pub fn new() -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let (m_cost, t_cost, p_cost) = DEFAULT_KDF_COST;
+
+        Self {
+            algorithm: "argon2id".to_string(),
+            salt: base64::encode(salt),
+            m_cost,
+            t_cost,
+            p_cost,
+        }
+    }
+
+    pub fn derive_key(&self, passphrase: &str) -> ClientResult<Zeroizing<[u8; KEY_LEN]>> {
+        if self.algorithm != "argon2id" {
+            return Err(ClientError::InvalidData(format!(
+                "unsupported keystore kdf '{}'",
+                self.algorithm
+            )));
+        }
+        let salt = base64::decode(&self.salt)
+            .map_err(|err| ClientError::Parse("keystore salt".to_string(), err.to_string()))?;
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(KEY_LEN))
+            .map_err(|err| ClientError::InvalidData(format!("keystore kdf params: {}", err)))?;
+
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut())
+            .map_err(|err| ClientError::Other(format!("keystore kdf: {}", err)))?;
+
+        Ok(key)
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// XChaCha20-Poly1305 ciphertext with its nonce, both base64.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sealed {
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl Sealed {
+    /// Encrypts `plaintext` under `key`, authenticating `aad` along with it.
+    pub fn seal(key: &[u8; KEY_LEN], aad: &[u8], plaintext: &[u8]) -> ClientResult<Self> {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let ciphertext = XChaCha20Poly1305::new(key.into())
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| ClientError::Other("keystore encryption failed".to_string()))?;
+
+        Ok(Self {
+            nonce: base64::encode(nonce),
+            ciphertext: base64::encode(ciphertext),
+        })
+    }
+
+    /// Decrypts, failing if the key is wrong or the ciphertext or `aad` were tampered with.
+    pub fn open(&self, key: &[u8; KEY_LEN], aad: &[u8]) -> ClientResult<Zeroizing<Vec<u8>>> {
+        let decode = |field: &str, value: &str| {
+            base64::decode(value)
+                .map_err(|err| ClientError::Parse(format!("keystore {}", field), err.to_string()))
+        };
+        let nonce = decode("nonce", &self.nonce)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(ClientError::InvalidData(format!(
+                "keystore nonce has {} bytes, expected {}",
+                nonce.len(),
+                NONCE_LEN
+            )));
+        }
+        let ciphertext = decode("ciphertext", &self.ciphertext)?;
+
+        XChaCha20Poly1305::new(key.into())
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad,
+                },
+            )
+            .map(Zeroizing::new)
+            .map_err(|_| {
+                ClientError::InvalidData(
+                    "keystore decryption failed, wrong passphrase or corrupted file".to_string(),
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Minimal cost so the tests don't spend seconds in Argon2
+    fn kdf() -> KdfParams {
+        KdfParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..KdfParams::new()
+        }
+    }
+
+    #[test]
+    fn seal_open_round_trip() {
+        let kdf = kdf();
+        let sealed = Sealed::seal(&kdf.derive_key("hunter22").unwrap(), b"aad", b"secret").unwrap();
+
+        let opened = sealed
+            .open(&kdf.derive_key("hunter22").unwrap(), b"aad")
+            .unwrap();
+        assert_eq!(opened.as_slice(), b"secret");
+    }
+
+    #[test]
+    fn same_passphrase_other_salt_gives_other_key() {
+        assert_ne!(
+            *kdf().derive_key("hunter22").unwrap(),
+            *kdf().derive_key("hunter22").unwrap()
+        );
+    }
+
+    #[test]
+    fn wrong_passphrase_fails_to_open() {
+        let kdf = kdf();
+        let sealed = Sealed::seal(&kdf.derive_key("hunter22").unwrap(), b"aad", b"secret").unwrap();
+
+        assert!(
+            sealed
+                .open(&kdf.derive_key("hunter23").unwrap(), b"aad")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn tampered_ciphertext_fails_to_open() {
+        let key = kdf().derive_key("hunter22").unwrap();
+        let mut sealed = Sealed::seal(&key, b"aad", b"secret").unwrap();
+        let mut ciphertext = base64::decode(&sealed.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        sealed.ciphertext = base64::encode(ciphertext);
+
+        assert!(sealed.open(&key, b"aad").is_err());
+    }
+
+    #[test]
+    fn tampered_nonce_fails_to_open() {
+        let key = kdf().derive_key("hunter22").unwrap();
+        let mut sealed = Sealed::seal(&key, b"aad", b"secret").unwrap();
+        let mut nonce = base64::decode(&sealed.nonce).unwrap();
+        nonce[0] ^= 1;
+        sealed.nonce = base64::encode(nonce);
+
+        assert!(sealed.open(&key, b"aad").is_err());
+    }
+
+    #[test]
+    fn other_aad_fails_to_open() {
+        let key = kdf().derive_key("hunter22").unwrap();
+        let sealed = Sealed::seal(&key, b"aad", b"secret").unwrap();
+
+        assert!(sealed.open(&key, b"aae").is_err());
+    }
+
+    #[test]
+    fn unsupported_kdf_is_rejected() {
+        let kdf = KdfParams {
+            algorithm: "scrypt".to_string(),
+            ..kdf()
+        };
+
+        assert!(kdf.derive_key("hunter22").is_err());
+    }
+}
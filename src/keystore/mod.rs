@@ -0,0 +1,5 @@
+pub mod crypto;
+pub mod store;
+
+pub use crypto::*;
+pub use store::*;
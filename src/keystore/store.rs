@@ -0,0 +1,376 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use std::{collections::BTreeSet, fs, io::Write, path::Path, sync::Arc};
+use zeroize::Zeroizing;
+
+use crate::{
+    error::{ClientError, ClientResult},
+    farm::{FarmWallet, WalletRole, roles_from_label},
+    keystore::{KdfParams, Sealed},
+    signer::KeystoreSigner,
+};
+
+pub const KEYSTORE_VERSION: u32 = 1;
+// Sealed with no plaintext, opening it checks the passphrase even without entries
+const CHECK_AAD: &[u8] = b"keystore-check";
+
+/// Keypairs encrypted under a passphrase, stored as JSON.
+///
+/// Labels, roles and pubkeys are in the clear so the keystore can be listed without unlocking.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Keystore {
+    pub version: u32,
+    pub kdf: KdfParams,
+    pub check: Sealed,
+    #[serde(default)]
+    pub entries: Vec<KeystoreEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeystoreEntry {
+    pub label: String,
+    /// Farm roles, empty takes them from the label like a keypair file name.
+    #[serde(default)]
+    pub roles: Vec<String>,
+    pub pubkey: String,
+    /// The 64 byte keypair.
+    pub secret: Sealed,
+}
+
+impl KeystoreEntry {
+    pub fn pubkey(&self) -> ClientResult<Pubkey> {
+        Ok(self.pubkey.parse()?)
+    }
+
+    pub fn roles(&self) -> ClientResult<BTreeSet<WalletRole>> {
+        if self.roles.is_empty() {
+            return Ok(roles_from_label(&self.label));
+        }
+
+        self.roles.iter().map(|role| role.parse()).collect()
+    }
+
+    // Binds the secret to its label and pubkey so entries cannot be swapped around
+    fn aad(&self) -> Vec<u8> {
+        format!("{}:{}", self.label, self.pubkey).into_bytes()
+    }
+}
+
+impl Keystore {
+    /// Whether `input` looks like a keystore rather than a wallets file.
+    pub fn is_keystore(input: &str) -> bool {
+        serde_json::from_str::<serde_json::Value>(input)
+            .is_ok_and(|value| value.get("kdf").is_some() && value.get("check").is_some())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> ClientResult<Self> {
+        let path = path.as_ref();
+        let input = fs::read_to_string(path)
+            .map_err(|err| ClientError::Other(format!("{}: {}", path.display(), err)))?;
+
+        Self::from_json(&input)
+    }
+
+    pub fn from_json(input: &str) -> ClientResult<Self> {
+        let keystore: Self = serde_json::from_str(input)?;
+        if keystore.version != KEYSTORE_VERSION {
+            return Err(ClientError::InvalidData(format!(
+                "unsupported keystore version {}, expected {}",
+                keystore.version, KEYSTORE_VERSION
+            )));
+        }
+
+        Ok(keystore)
+    }
+
+    /// An empty keystore under `passphrase`.
+    pub fn create(passphrase: &str) -> ClientResult<UnlockedKeystore> {
+        let kdf = KdfParams::new();
+        let key = kdf.derive_key(passphrase)?;
+
+        Ok(UnlockedKeystore {
+            keystore: Self {
+                version: KEYSTORE_VERSION,
+                kdf,
+                check: Sealed::seal(&key, CHECK_AAD, &[])?,
+                entries: Vec::new(),
+            },
+            key,
+        })
+    }
+
+    /// Derives the key from `passphrase`, failing if it is the wrong one.
+    pub fn unlock(self, passphrase: &str) -> ClientResult<UnlockedKeystore> {
+        let key = self.kdf.derive_key(passphrase)?;
+        self.check.open(&key, CHECK_AAD)?;
+
+        Ok(UnlockedKeystore {
+            keystore: self,
+            key,
+        })
+    }
+
+    /// The entry with `label_or_pubkey` as its label or base58 pubkey.
+    pub fn find(&self, label_or_pubkey: &str) -> Option<&KeystoreEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.label == label_or_pubkey || entry.pubkey == label_or_pubkey)
+    }
+}
+
+/// A keystore with its derived key, able to decrypt and add entries. Not `Debug` so the key
+/// never ends up in a log.
+pub struct UnlockedKeystore {
+    pub keystore: Keystore,
+    key: Zeroizing<[u8; 32]>,
+}
+
+impl UnlockedKeystore {
+    /// Decrypts `entry`, checking the keypair matches its pubkey.
+    pub fn keypair(&self, entry: &KeystoreEntry) -> ClientResult<Keypair> {
+        let bytes = entry.secret.open(&self.key, &entry.aad())?;
+        let keypair = Keypair::from_bytes(&bytes).map_err(|err| {
+            ClientError::Parse(format!("keystore entry {}", entry.label), err.to_string())
+        })?;
+        if keypair.pubkey() != entry.pubkey()? {
+            return Err(ClientError::InvalidData(format!(
+                "keystore entry {} does not hold the keypair of {}",
+                entry.label, entry.pubkey
+            )));
+        }
+
+        Ok(keypair)
+    }
+
+    /// The keypair of the entry labeled `label_or_pubkey` or with it as its pubkey.
+    pub fn find_keypair(&self, label_or_pubkey: &str) -> ClientResult<Keypair> {
+        let entry = self.keystore.find(label_or_pubkey).ok_or_else(|| {
+            ClientError::InvalidData(format!("no keystore entry '{}'", label_or_pubkey))
+        })?;
+
+        self.keypair(entry)
+    }
+
+    /// Every entry as a farm wallet signing from the keystore, in keystore order.
+    pub fn wallets(self: &Arc<Self>) -> ClientResult<Vec<FarmWallet>> {
+        self.keystore
+            .entries
+            .iter()
+            .map(|entry| {
+                Ok(FarmWallet::new(
+                    entry.label.clone(),
+                    Arc::new(KeystoreSigner::new(self.clone(), &entry.pubkey)?),
+                    entry.roles()?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Encrypts and appends `keypair`, labels and pubkeys must be unique.
+    pub fn add(
+        &mut self,
+        label: &str,
+        roles: &BTreeSet<WalletRole>,
+        keypair: &Keypair,
+    ) -> ClientResult<()> {
+        let pubkey = keypair.pubkey().to_string();
+        if let Some(existing) = self.keystore.find(label).or(self.keystore.find(&pubkey)) {
+            return Err(ClientError::Duplicate(format!(
+                "keystore already holds {} ({})",
+                existing.label, existing.pubkey
+            )));
+        }
+
+        let roles = roles.iter().map(|role| role.as_str().to_string()).collect();
+        let entry = self.seal_entry(label, roles, keypair)?;
+        self.keystore.entries.push(entry);
+
+        Ok(())
+    }
+
+    /// Re-encrypts every entry under `passphrase` with a new salt.
+    pub fn rotate(&self, passphrase: &str) -> ClientResult<UnlockedKeystore> {
+        let mut rotated = Keystore::create(passphrase)?;
+        for entry in &self.keystore.entries {
+            let keypair = self.keypair(entry)?;
+            let entry = rotated.seal_entry(&entry.label, entry.roles.clone(), &keypair)?;
+            rotated.keystore.entries.push(entry);
+        }
+
+        Ok(rotated)
+    }
+
+    fn seal_entry(
+        &self,
+        label: &str,
+        roles: Vec<String>,
+        keypair: &Keypair,
+    ) -> ClientResult<KeystoreEntry> {
+        let mut entry = KeystoreEntry {
+            label: label.to_string(),
+            roles,
+            pubkey: keypair.pubkey().to_string(),
+            secret: Sealed {
+                nonce: String::new(),
+                ciphertext: String::new(),
+            },
+        };
+        let secret = Zeroizing::new(keypair.to_bytes());
+        entry.secret = Sealed::seal(&self.key, &entry.aad(), secret.as_ref())?;
+
+        Ok(entry)
+    }
+
+    /// Writes the keystore readable by the owner only, replacing `path` in one step.
+    pub fn save(&self, path: impl AsRef<Path>) -> ClientResult<()> {
+        let path = path.as_ref();
+        let io_err =
+            |err: std::io::Error| ClientError::Other(format!("{}: {}", path.display(), err));
+        let tmp = path.with_extension("tmp");
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options.open(&tmp).map_err(io_err)?;
+        file.write_all(serde_json::to_string_pretty(&self.keystore)?.as_bytes())
+            .and_then(|_| file.sync_all())
+            .map_err(io_err)?;
+        fs::rename(&tmp, path).map_err(io_err)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Like `Keystore::create` at minimal Argon2 cost
+    fn create(passphrase: &str) -> UnlockedKeystore {
+        let kdf = KdfParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..KdfParams::new()
+        };
+        let key = kdf.derive_key(passphrase).unwrap();
+
+        UnlockedKeystore {
+            keystore: Keystore {
+                version: KEYSTORE_VERSION,
+                kdf,
+                check: Sealed::seal(&key, CHECK_AAD, &[]).unwrap(),
+                entries: Vec::new(),
+            },
+            key,
+        }
+    }
+
+    fn with_entries(passphrase: &str, labels: &[&str]) -> (UnlockedKeystore, Vec<Keypair>) {
+        let mut unlocked = create(passphrase);
+        let keypairs: Vec<Keypair> = labels.iter().map(|_| Keypair::new()).collect();
+        for (label, keypair) in labels.iter().zip(&keypairs) {
+            unlocked.add(label, &BTreeSet::new(), keypair).unwrap();
+        }
+
+        (unlocked, keypairs)
+    }
+
+    fn reload(keystore: &Keystore) -> Keystore {
+        Keystore::from_json(&serde_json::to_string(keystore).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn json_round_trip_unlocks_every_entry() {
+        let (unlocked, keypairs) = with_entries("hunter22", &["buyer-1", "seller-2"]);
+
+        let unlocked = reload(&unlocked.keystore).unlock("hunter22").unwrap();
+        assert_eq!(
+            unlocked.find_keypair("buyer-1").unwrap().to_bytes(),
+            keypairs[0].to_bytes()
+        );
+        assert_eq!(
+            unlocked
+                .find_keypair(&keypairs[1].pubkey().to_string())
+                .unwrap()
+                .to_bytes(),
+            keypairs[1].to_bytes()
+        );
+    }
+
+    #[test]
+    fn wrong_passphrase_does_not_unlock() {
+        let (unlocked, _) = with_entries("hunter22", &["main"]);
+
+        assert!(reload(&unlocked.keystore).unlock("hunter23").is_err());
+    }
+
+    #[test]
+    fn wrong_passphrase_does_not_unlock_empty_keystore() {
+        assert!(reload(&create("hunter22").keystore).unlock("").is_err());
+    }
+
+    #[test]
+    fn tampered_secret_fails_to_decrypt() {
+        let (mut unlocked, _) = with_entries("hunter22", &["main"]);
+        let secret = &mut unlocked.keystore.entries[0].secret;
+        let mut ciphertext = base64::decode(&secret.ciphertext).unwrap();
+        ciphertext[10] ^= 1;
+        secret.ciphertext = base64::encode(ciphertext);
+
+        assert!(unlocked.find_keypair("main").is_err());
+    }
+
+    #[test]
+    fn relabeled_entry_fails_to_decrypt() {
+        let (mut unlocked, _) = with_entries("hunter22", &["main"]);
+        unlocked.keystore.entries[0].label = "buyer-1".to_string();
+
+        assert!(unlocked.find_keypair("buyer-1").is_err());
+    }
+
+    #[test]
+    fn swapped_secrets_fail_to_decrypt() {
+        let (mut unlocked, _) = with_entries("hunter22", &["buyer-1", "seller-2"]);
+        let entries = &mut unlocked.keystore.entries;
+        let secret = entries[0].secret.clone();
+        entries[0].secret = entries[1].secret.clone();
+        entries[1].secret = secret;
+
+        assert!(unlocked.find_keypair("buyer-1").is_err());
+        assert!(unlocked.find_keypair("seller-2").is_err());
+    }
+
+    #[test]
+    fn rotate_keeps_entries_under_new_passphrase() {
+        let (unlocked, keypairs) = with_entries("hunter22", &["main"]);
+        let rotated = unlocked.rotate("correct horse").unwrap();
+
+        assert!(reload(&rotated.keystore).unlock("hunter22").is_err());
+        let rotated = reload(&rotated.keystore).unlock("correct horse").unwrap();
+        assert_eq!(
+            rotated.find_keypair("main").unwrap().to_bytes(),
+            keypairs[0].to_bytes()
+        );
+    }
+
+    #[test]
+    fn duplicate_label_or_pubkey_is_rejected() {
+        let (mut unlocked, keypairs) = with_entries("hunter22", &["main"]);
+
+        assert!(
+            unlocked
+                .add("main", &BTreeSet::new(), &Keypair::new())
+                .is_err()
+        );
+        assert!(
+            unlocked
+                .add("other", &BTreeSet::new(), &keypairs[0])
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use carbon_core::deserialize::CarbonDeserialize;
+use serde::{Deserialize, Serialize};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status_client_types::{UiInstruction, UiTransactionEncoding};
+
+use crate::{
+    error::{ClientError, ClientResult},
+    instructions::types::TradeEventTemp,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FillSide {
+    Buy,
+    Sell,
+}
+
+/// One of our trades as written to the ledger, all amounts in lamports or raw token units.
+///
+/// Pubkeys and the signature are stored as base58 so the file stays readable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fill {
+    pub signature: String,
+    pub slot: u64,
+    /// Block time of the trade in unix seconds.
+    pub timestamp: i64,
+    pub wallet: String,
+    pub mint: String,
+    pub side: FillSide,
+    /// Lamports that went into or came out of the curve, before fees.
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub fee: u64,
+    pub creator_fee: u64,
+    /// Relayer tip of the transaction.
+    pub tip: u64,
+}
+
+impl Fill {
+    pub fn from_event(event: &TradeEventTemp, signature: &Signature, slot: u64, tip: u64) -> Self {
+        Self {
+            signature: signature.to_string(),
+            slot,
+            timestamp: event.timestamp,
+            wallet: event.user.to_string(),
+            mint: event.mint.to_string(),
+            side: if event.is_buy {
+                FillSide::Buy
+            } else {
+                FillSide::Sell
+            },
+            sol_amount: event.sol_amount,
+            token_amount: event.token_amount,
+            fee: event.fee,
+            creator_fee: event.creator_fee,
+            tip,
+        }
+    }
+
+    pub fn wallet(&self) -> ClientResult<Pubkey> {
+        Ok(self.wallet.parse()?)
+    }
+
+    pub fn mint(&self) -> ClientResult<Pubkey> {
+        Ok(self.mint.parse()?)
+    }
+
+    /// Fees, creator fee and tip of the fill.
+    pub fn costs(&self) -> u64 {
+        self.fee + self.creator_fee + self.tip
+    }
+
+    /// What the fill did to our SOL balance: negative for buys, positive for sells.
+    pub fn sol_delta(&self) -> i128 {
+        match self.side {
+            FillSide::Buy => -((self.sol_amount + self.costs()) as i128),
+            FillSide::Sell => self.sol_amount as i128 - self.costs() as i128,
+        }
+    }
+}
+
+/// Fills of `wallets` in a landed transaction, decoded from its PumpFun trade events. `tip` is
+/// booked on the first fill only.
+pub async fn fetch_fills(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+    wallets: &[Pubkey],
+    tip: u64,
+) -> ClientResult<Vec<Fill>> {
+    let transaction = rpc_client
+        .get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await?;
+
+    let meta = transaction
+        .transaction
+        .meta
+        .ok_or_else(|| ClientError::InvalidData(format!("{} has no status meta", signature)))?;
+    let inner_instructions: Option<Vec<_>> = meta.inner_instructions.into();
+
+    let mut fills: Vec<Fill> = inner_instructions
+        .into_iter()
+        .flatten()
+        .flat_map(|inner| inner.instructions)
+        .filter_map(|instruction| match instruction {
+            UiInstruction::Compiled(compiled) => bs58::decode(compiled.data).into_vec().ok(),
+            UiInstruction::Parsed(_) => None,
+        })
+        .filter_map(|data| TradeEventTemp::deserialize(&data))
+        .filter(|event| wallets.contains(&event.user))
+        .map(|event| Fill::from_event(&event, signature, transaction.slot, 0))
+        .collect();
+    if let Some(first) = fills.first_mut() {
+        first.tip = tip;
+    }
+
+    Ok(fills)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(side: FillSide, sol_amount: u64) -> Fill {
+        Fill {
+            signature: Signature::default().to_string(),
+            slot: 1,
+            timestamp: 0,
+            wallet: Pubkey::new_unique().to_string(),
+            mint: Pubkey::new_unique().to_string(),
+            side,
+            sol_amount,
+            token_amount: 1_000,
+            fee: 10,
+            creator_fee: 5,
+            tip: 100,
+        }
+    }
+
+    #[test]
+    fn costs_add_fees_and_tip() {
+        assert_eq!(fill(FillSide::Buy, 1_000_000).costs(), 115);
+    }
+
+    #[test]
+    fn buys_pay_their_costs_on_top() {
+        assert_eq!(fill(FillSide::Buy, 1_000_000).sol_delta(), -1_000_115);
+    }
+
+    #[test]
+    fn sells_pay_their_costs_out_of_the_proceeds() {
+        assert_eq!(fill(FillSide::Sell, 1_000_000).sol_delta(), 999_885);
+        assert_eq!(fill(FillSide::Sell, 0).sol_delta(), -115);
+    }
+
+    #[test]
+    fn round_trips_through_a_ledger_line() {
+        let fill = fill(FillSide::Sell, 1_000_000);
+        let line = serde_json::to_string(&fill).unwrap();
+
+        assert!(line.contains(r#""side":"sell""#));
+        assert_eq!(serde_json::from_str::<Fill>(&line).unwrap(), fill);
+        assert!(fill.wallet().is_ok() && fill.mint().is_ok());
+    }
+}
@@ -0,0 +1,7 @@
+pub mod fill;
+pub mod position;
+pub mod store;
+
+pub use fill::*;
+pub use position::*;
+pub use store::*;
@@ -0,0 +1,251 @@
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+    error::ClientResult,
+    ledger::{Fill, FillSide},
+    utils::BondingCurveQuote,
+};
+
+/// Average-cost position of one wallet in one mint, rebuilt from its fills.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LedgerPosition {
+    pub wallet: String,
+    pub mint: String,
+    pub token_amount: u64,
+    /// Lamports paid for `token_amount`, fees and tips included.
+    pub cost_basis: u64,
+    /// Sell proceeds after costs minus the cost basis of what was sold.
+    pub realized_pnl: i64,
+    /// Fees, creator fees and tips paid over the life of the position.
+    pub costs: u64,
+    pub buys: u32,
+    pub sells: u32,
+    pub last_slot: u64,
+}
+
+impl LedgerPosition {
+    pub fn new(wallet: &str, mint: &str) -> Self {
+        Self {
+            wallet: wallet.to_string(),
+            mint: mint.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.token_amount > 0
+    }
+
+    pub fn apply(&mut self, fill: &Fill) {
+        self.costs = self.costs.saturating_add(fill.costs());
+        self.last_slot = self.last_slot.max(fill.slot);
+
+        match fill.side {
+            FillSide::Buy => {
+                self.buys += 1;
+                self.token_amount = self.token_amount.saturating_add(fill.token_amount);
+                self.cost_basis = self
+                    .cost_basis
+                    .saturating_add(fill.sol_amount + fill.costs());
+            }
+            FillSide::Sell => {
+                self.sells += 1;
+                // Tokens bought before the ledger existed have no basis
+                let sold = fill.token_amount.min(self.token_amount);
+                let sold_basis = if self.token_amount == 0 {
+                    0
+                } else {
+                    (self.cost_basis as u128 * sold as u128 / self.token_amount as u128) as u64
+                };
+
+                self.realized_pnl += fill.sol_delta() as i64 - sold_basis as i64;
+                self.cost_basis -= sold_basis;
+                self.token_amount -= sold;
+            }
+        }
+    }
+
+    /// Lamports the open tokens would fetch on `quote` after fees, minus their cost basis.
+    pub fn unrealized_pnl(&self, quote: &BondingCurveQuote) -> ClientResult<i64> {
+        if !self.is_open() {
+            return Ok(0);
+        }
+        let value = quote.sell_exact_tokens_in(self.token_amount)?;
+
+        Ok(value as i64 - self.cost_basis as i64)
+    }
+}
+
+/// Realized PnL and open cost of a set of positions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PnlSummary {
+    pub positions: usize,
+    pub open: usize,
+    pub cost_basis: u64,
+    pub realized_pnl: i64,
+    pub costs: u64,
+}
+
+impl PnlSummary {
+    /// Totals of `positions` grouped by `key`, e.g. the mint or the wallet.
+    pub fn group_by<'a>(
+        positions: impl IntoIterator<Item = &'a LedgerPosition>,
+        key: impl Fn(&LedgerPosition) -> String,
+    ) -> BTreeMap<String, Self> {
+        let mut summaries: BTreeMap<String, Self> = BTreeMap::new();
+        for position in positions {
+            summaries.entry(key(position)).or_default().add(position);
+        }
+
+        summaries
+    }
+
+    pub fn add(&mut self, position: &LedgerPosition) {
+        self.positions += 1;
+        if position.is_open() {
+            self.open += 1;
+        }
+        self.cost_basis = self.cost_basis.saturating_add(position.cost_basis);
+        self.realized_pnl += position.realized_pnl;
+        self.costs = self.costs.saturating_add(position.costs);
+    }
+}
+
+impl fmt::Display for PnlSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let to_sol = |lamports: i64| lamports as f64 / LAMPORTS_PER_SOL as f64;
+
+        write!(
+            f,
+            "{} positions ({} open), cost basis {:.4} SOL, realized {:+.4} SOL, fees and tips {:.4} SOL",
+            self.positions,
+            self.open,
+            to_sol(self.cost_basis as i64),
+            to_sol(self.realized_pnl),
+            to_sol(self.costs as i64)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "wallet";
+    const MINT: &str = "mint";
+
+    fn fill(side: FillSide, sol_amount: u64, token_amount: u64, fee: u64, tip: u64) -> Fill {
+        Fill {
+            signature: String::new(),
+            slot: 1,
+            timestamp: 0,
+            wallet: WALLET.to_string(),
+            mint: MINT.to_string(),
+            side,
+            sol_amount,
+            token_amount,
+            fee,
+            creator_fee: fee / 2,
+            tip,
+        }
+    }
+
+    fn bought() -> LedgerPosition {
+        let mut position = LedgerPosition::new(WALLET, MINT);
+        position.apply(&fill(FillSide::Buy, 1_000_000, 1_000, 10, 100));
+        position
+    }
+
+    #[test]
+    fn buys_add_fees_and_tips_to_the_cost_basis() {
+        let position = bought();
+
+        assert_eq!(position.token_amount, 1_000);
+        assert_eq!(position.cost_basis, 1_000_115);
+        assert_eq!(position.costs, 115);
+        assert_eq!(position.realized_pnl, 0);
+        assert_eq!(position.buys, 1);
+    }
+
+    #[test]
+    fn partial_sell_realizes_its_share_of_the_basis() {
+        let mut position = bought();
+        position.apply(&fill(FillSide::Sell, 600_000, 400, 6, 0));
+
+        // 400 of 1_000 tokens carry 400_046 of the basis, the sell nets 600_000 - 9
+        assert_eq!(position.realized_pnl, 599_991 - 400_046);
+        assert_eq!(position.cost_basis, 1_000_115 - 400_046);
+        assert_eq!(position.token_amount, 600);
+        assert_eq!(position.costs, 124);
+        assert!(position.is_open());
+    }
+
+    #[test]
+    fn closing_sell_realizes_the_rest() {
+        let mut position = bought();
+        position.apply(&fill(FillSide::Sell, 600_000, 400, 6, 0));
+        position.apply(&fill(FillSide::Sell, 300_000, 600, 2, 50));
+
+        // Every lamport in and out is realized once the position is closed
+        assert_eq!(position.realized_pnl, -1_000_115 + 599_991 + 299_947);
+        assert_eq!(position.cost_basis, 0);
+        assert_eq!(position.token_amount, 0);
+        assert_eq!(position.sells, 2);
+        assert!(!position.is_open());
+        assert_eq!(
+            position
+                .unrealized_pnl(&BondingCurveQuote::initial())
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn sell_without_a_basis_is_all_profit() {
+        let mut position = LedgerPosition::new(WALLET, MINT);
+        position.apply(&fill(FillSide::Sell, 50_000, 1_000, 10, 0));
+
+        assert_eq!(position.realized_pnl, 50_000 - 15);
+        assert_eq!(position.token_amount, 0);
+    }
+
+    #[test]
+    fn unrealized_pnl_values_the_open_tokens_on_the_curve() {
+        let quote = BondingCurveQuote::initial();
+        let mut position = LedgerPosition::new(WALLET, MINT);
+        position.apply(&fill(
+            FillSide::Buy,
+            1_000_000_000,
+            30_000_000_000_000,
+            0,
+            0,
+        ));
+
+        let value = quote.sell_exact_tokens_in(position.token_amount).unwrap();
+        assert_eq!(
+            position.unrealized_pnl(&quote).unwrap(),
+            value as i64 - 1_000_000_000
+        );
+    }
+
+    #[test]
+    fn summaries_add_up_per_key() {
+        let mut closed = bought();
+        closed.apply(&fill(FillSide::Sell, 2_000_000, 1_000, 0, 0));
+        let mut other = bought();
+        other.wallet = "other".to_string();
+
+        let summaries = PnlSummary::group_by([&closed, &other, &bought()], |position| {
+            position.wallet.clone()
+        });
+
+        assert_eq!(summaries.len(), 2);
+        let summary = summaries[WALLET];
+        assert_eq!(summary.positions, 2);
+        assert_eq!(summary.open, 1);
+        assert_eq!(summary.cost_basis, 1_000_115);
+        assert_eq!(summary.realized_pnl, 2_000_000 - 1_000_115);
+        assert_eq!(summary.costs, 230);
+    }
+}
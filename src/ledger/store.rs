@@ -0,0 +1,197 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use tokio::time::{Duration, sleep};
+
+use crate::{
+    config::bot_config,
+    error::{ClientError, ClientResult},
+    ledger::{Fill, LedgerPosition, PnlSummary, fetch_fills},
+};
+
+// The RPC node can serve the transaction a little after the signature status says it landed
+const FETCH_ATTEMPTS: u32 = 5;
+const FETCH_RETRY_MS: u64 = 800;
+
+type PositionKey = (String, String);
+
+struct LedgerState {
+    file: File,
+    seen: HashSet<(String, String, String)>,
+    positions: BTreeMap<PositionKey, LedgerPosition>,
+}
+
+/// Append-only JSONL file of our fills with the positions they add up to.
+///
+/// Opening replays the file, so positions left open by an earlier run are resumed.
+pub struct Ledger {
+    path: PathBuf,
+    state: Mutex<LedgerState>,
+}
+
+impl Ledger {
+    /// Opens `LEDGER_FILE`.
+    pub fn from_env() -> ClientResult<Self> {
+        Self::open(&bot_config()?.ledger_file)
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> ClientResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let io_err =
+            |err: std::io::Error| ClientError::Other(format!("{}: {}", path.display(), err));
+
+        let mut state = LedgerState {
+            file: OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(io_err)?,
+            seen: HashSet::new(),
+            positions: BTreeMap::new(),
+        };
+
+        let contents = fs::read_to_string(&path).map_err(io_err)?;
+        for (number, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            // A crash mid-write leaves a truncated last line, skip it rather than refuse to start
+            match serde_json::from_str::<Fill>(line) {
+                Ok(fill) => state.apply(&fill),
+                Err(err) => eprintln!(
+                    "Skipping line {} of {}: {}",
+                    number + 1,
+                    path.display(),
+                    err
+                ),
+            }
+        }
+        // Terminate the truncated line so the next fill starts on its own
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            state.file.write_all(b"\n").map_err(io_err)?;
+        }
+
+        Ok(Self {
+            path,
+            state: Mutex::new(state),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends `fill` and updates its position, returns false if it was already recorded.
+    pub fn record(&self, fill: &Fill) -> ClientResult<bool> {
+        let mut state = self.state.lock().unwrap();
+        if state.seen.contains(&fill_id(fill)) {
+            return Ok(false);
+        }
+
+        let mut line = serde_json::to_string(fill)?;
+        line.push('\n');
+        state
+            .file
+            .write_all(line.as_bytes())
+            .and_then(|_| state.file.flush())
+            .map_err(|err| ClientError::Other(format!("{}: {}", self.path.display(), err)))?;
+        state.apply(fill);
+
+        Ok(true)
+    }
+
+    /// Fetches the fills of `wallets` in the landed `signature` and records them.
+    pub async fn record_transaction(
+        &self,
+        rpc_client: &RpcClient,
+        signature: &Signature,
+        wallets: &[Pubkey],
+        tip: u64,
+    ) -> ClientResult<Vec<Fill>> {
+        let mut attempt = 1;
+        let fills = loop {
+            match fetch_fills(rpc_client, signature, wallets, tip).await {
+                Ok(fills) => break fills,
+                Err(_) if attempt < FETCH_ATTEMPTS => {
+                    attempt += 1;
+                    sleep(Duration::from_millis(FETCH_RETRY_MS)).await;
+                }
+                Err(err) => return Err(err),
+            }
+        };
+
+        for fill in &fills {
+            self.record(fill)?;
+        }
+
+        Ok(fills)
+    }
+
+    pub fn position(&self, wallet: &str, mint: &str) -> Option<LedgerPosition> {
+        let state = self.state.lock().unwrap();
+        state
+            .positions
+            .get(&(wallet.to_string(), mint.to_string()))
+            .cloned()
+    }
+
+    /// All positions, ordered by wallet then mint.
+    pub fn positions(&self) -> Vec<LedgerPosition> {
+        let state = self.state.lock().unwrap();
+        state.positions.values().cloned().collect()
+    }
+
+    pub fn open_positions(&self) -> Vec<LedgerPosition> {
+        let state = self.state.lock().unwrap();
+        state
+            .positions
+            .values()
+            .filter(|position| position.is_open())
+            .cloned()
+            .collect()
+    }
+
+    /// Totals per mint across all wallets.
+    pub fn pnl_by_mint(&self) -> BTreeMap<String, PnlSummary> {
+        self.summarize(|position| position.mint.clone())
+    }
+
+    /// Totals per wallet across all mints.
+    pub fn pnl_by_wallet(&self) -> BTreeMap<String, PnlSummary> {
+        self.summarize(|position| position.wallet.clone())
+    }
+
+    fn summarize(&self, key: impl Fn(&LedgerPosition) -> String) -> BTreeMap<String, PnlSummary> {
+        let state = self.state.lock().unwrap();
+        PnlSummary::group_by(state.positions.values(), key)
+    }
+}
+
+impl LedgerState {
+    fn apply(&mut self, fill: &Fill) {
+        if !self.seen.insert(fill_id(fill)) {
+            return;
+        }
+
+        self.positions
+            .entry((fill.wallet.clone(), fill.mint.clone()))
+            .or_insert_with(|| LedgerPosition::new(&fill.wallet, &fill.mint))
+            .apply(fill);
+    }
+}
+
+// A transaction can trade several wallets and mints, but each only once
+fn fill_id(fill: &Fill) -> (String, String, String) {
+    (
+        fill.signature.clone(),
+        fill.wallet.clone(),
+        fill.mint.clone(),
+    )
+}
@@ -1,5 +1,13 @@
 pub mod config;
+pub mod copy_trade;
 pub mod error;
+pub mod farm;
 pub mod instructions;  // Re-enabled since pumpfun.rs is now working
+pub mod keystore;
+pub mod ledger;
+pub mod monitor;
 pub mod service;
+pub mod signer;
+pub mod sniper;
+pub mod strategy;
 pub mod utils;
@@ -0,0 +1,595 @@
+use anyhow::{Result, anyhow};
+use carbon_pumpfun_decoder::accounts::global::Global;
+use dotenvy::dotenv;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+};
+
+use pumpfun_monitor::{
+    config::{
+        TradingProfile, active_profile, bot_config, init_bot_config, init_jito, rpc_client,
+        settings_changed, spawn_config_reloader, spawn_profile_switcher,
+    },
+    farm::{FarmWallet, WalletFarm},
+    instructions::{
+        pumpfun_accounts::fetch_global,
+        pumpfun_buy::{PumpFun, TokenCreationParams},
+        pumpfun_launch::{LaunchBuy, PumpFunLaunch},
+        pumpfun_sell::PumpFunSell,
+    },
+    ledger::Ledger,
+    monitor::TradeMonitor,
+    service::{ConfirmationTracker, FanOut, FanOutGuard, TxOutcome, TxSender},
+    strategy::{LadderSell, Position, SellLadder, Strategy, StrategyConfig},
+    utils::{
+        BondingCurveQuote, NonceAccount, NonceManager, TxLifetime, spawn_blockhash_refresher,
+        wait_for_blockhash,
+    },
+};
+use std::time::Duration;
+
+const CREATED_MINT_FILE: &str = "created_token_mint.txt";
+// How often time based sell rules are checked between trades
+const STRATEGY_TICK_SEC: u64 = 1;
+
+// Books the fills of `wallets` in a landed transaction, a failure only costs the ledger entry
+async fn record_fills(ledger: &Ledger, signature: &Signature, wallets: &[Pubkey], tip: u64) {
+    let recorded = match rpc_client() {
+        Ok(rpc_client) => {
+            ledger
+                .record_transaction(&rpc_client, signature, wallets, tip)
+                .await
+        }
+        Err(err) => Err(err),
+    };
+    match recorded {
+        Ok(fills) => println!("📒 Recorded {} fills of {}", fills.len(), signature),
+        Err(err) => eprintln!("Could not record {} in the ledger: {}", signature, err),
+    }
+}
+
+// Without a durable nonce, sell-all + close ATA conflicting with itself ensures that only one
+// provider's version can land
+async fn fan_out_sell(
+    profile: &TradingProfile,
+    fee_payer: &FarmWallet,
+    seller: &FarmWallet,
+    tracker: &ConfirmationTracker,
+    sell_ixs: Vec<Instruction>,
+    nonce: Option<&NonceAccount>,
+) -> Result<Option<Signature>> {
+    let guard = match nonce {
+        Some(nonce) => nonce.fan_out_guard(),
+        None => {
+            let blockhash = wait_for_blockhash().await;
+            FanOutGuard::ConflictingWrites {
+                recent_blockhash: blockhash.blockhash,
+                last_valid_block_height: blockhash.last_valid_block_height,
+            }
+        }
+    };
+
+    let report = FanOut::all_services()
+        .await?
+        .send(
+            &*fee_payer.signer,
+            &[&*seller.signer],
+            tracker,
+            &profile.tips(fee_payer.pubkey(), sell_ixs),
+            &guard,
+        )
+        .await?;
+
+    match report.landed {
+        Some(landed) if landed.outcome.is_landed() => {
+            println!(
+                "✅ Sell via {} ({}): {:?}",
+                landed.provider, landed.signature, landed.outcome
+            );
+            Ok(Some(landed.signature))
+        }
+        Some(landed) => {
+            println!(
+                "⚠️ Sell via {} ({}) did not land: {:?}",
+                landed.provider, landed.signature, landed.outcome
+            );
+            Ok(None)
+        }
+        None => {
+            println!("⚠️ Every sell version expired");
+            Ok(None)
+        }
+    }
+}
+
+fn token_creation_params(
+    deployer: &FarmWallet,
+    mint_keypair: &Keypair,
+    dev_buy_lamports: u64,
+) -> Result<TokenCreationParams> {
+    let metadata = bot_config()?.token_metadata()?;
+
+    Ok(TokenCreationParams {
+        deployer: deployer.signer.clone(),
+        token_mint_keypair: mint_keypair.insecure_clone(),
+        metadata_uri: metadata.uri.clone(),
+        dev_buy_amount: dev_buy_lamports as f64 / 1_000_000_000.0,
+        token_name: metadata.name.clone(),
+        token_symbol: metadata.symbol.clone(),
+        token_description: metadata.description.clone(),
+    })
+}
+
+async fn create_token(
+    farm: &WalletFarm,
+    profile: &TradingProfile,
+    sender: &dyn TxSender,
+    tracker: &ConfirmationTracker,
+    mint_keypair: &Keypair,
+    global: &Global,
+) -> Result<Signature> {
+    println!("\n🔄 Step 1: Creating token...");
+
+    let deployer = farm.deployer();
+    let fee_payer = farm.fee_payer();
+
+    let params = token_creation_params(deployer, mint_keypair, profile.buy_sol_amount()?)?;
+    let create_ixs = PumpFun::get_create_buy_instruction(&params, global)?;
+    let blockhash = wait_for_blockhash().await;
+    // The create transaction needs the deployer and mint keypair besides the fee payer
+    let txn = fee_payer.sign(
+        &[&*deployer.signer, mint_keypair],
+        &profile.with_tip(sender, fee_payer.pubkey(), create_ixs),
+        &[],
+        &TxLifetime::Blockhash(blockhash.blockhash),
+    )?;
+    let encoded_tx = sender.encode(&txn)?;
+
+    std::fs::write(CREATED_MINT_FILE, mint_keypair.pubkey().to_string())?;
+
+    println!("\n🔄 Step 2: Waiting for create + dev buy to land...");
+    match sender
+        .send_and_confirm(
+            &encoded_tx,
+            tracker,
+            Some(blockhash.last_valid_block_height),
+        )
+        .await?
+    {
+        TxOutcome::Landed { slot, level } => {
+            println!("Create landed in slot {} ({:?})", slot, level);
+            Ok(txn.signatures[0])
+        }
+        outcome => Err(anyhow!("Create transaction did not land: {:?}", outcome)),
+    }
+}
+
+// Create, dev buy and the `LAUNCH_BUYS` farm buys in one Jito bundle, so nobody can buy in between.
+// Returns the signatures of the bundle with the tip lamports each one paid.
+async fn launch_token(
+    farm: &WalletFarm,
+    profile: &TradingProfile,
+    tracker: &ConfirmationTracker,
+    mint_keypair: &Keypair,
+    global: &Global,
+) -> Result<Vec<(Signature, u64)>> {
+    let launch_buys = &bot_config()?.launch_buys;
+    println!(
+        "\n🔄 Step 1: Launching token with {} bundled buys...",
+        launch_buys.len()
+    );
+
+    let deployer = farm.deployer();
+    let buyers: Vec<_> = farm
+        .buyers()
+        .filter(|wallet| wallet.pubkey() != deployer.pubkey())
+        .collect();
+    if buyers.len() < launch_buys.len() {
+        return Err(anyhow!(
+            "LAUNCH_BUYS has {} amounts but the farm only has {} buyer wallets",
+            launch_buys.len(),
+            buyers.len()
+        ));
+    }
+
+    let buys: Vec<LaunchBuy> = buyers
+        .into_iter()
+        .zip(launch_buys.iter())
+        .map(|(wallet, sol_amount)| LaunchBuy {
+            wallet: wallet.clone(),
+            sol_amount: *sol_amount,
+        })
+        .collect();
+
+    let jito = init_jito().await?;
+    let params = token_creation_params(deployer, mint_keypair, profile.buy_sol_amount()?)?;
+    let quote = PumpFunLaunch::quote(&params, &buys, global, profile.slippage_pct())?;
+    let blockhash = wait_for_blockhash().await;
+    let bundle = PumpFunLaunch::get_launch_bundle(
+        &params,
+        farm.fee_payer(),
+        &buys,
+        &quote,
+        jito.as_ref(),
+        &profile.tips(farm.fee_payer().pubkey(), Vec::new()),
+        blockhash.blockhash,
+    )?;
+    for buy in &quote.buys {
+        println!("  {} buys {} tokens", buy.buyer, buy.token_amount);
+    }
+
+    let encoded_txs = bundle
+        .iter()
+        .map(|txn| jito.encode(txn))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    std::fs::write(CREATED_MINT_FILE, mint_keypair.pubkey().to_string())?;
+
+    println!("\n🔄 Step 2: Waiting for the launch bundle to land...");
+    let bundle_id = jito.send_bundle(&encoded_txs).await?;
+    let status = jito.confirm_bundle(&bundle_id, tracker.timeout).await?;
+    match status.err {
+        None => {
+            println!("Launch bundle {} landed in slot {}", bundle_id, status.slot);
+            // Only the create transaction carries the tip
+            let tip = profile.tip_lamports(jito.as_ref());
+            Ok(bundle
+                .iter()
+                .enumerate()
+                .map(|(idx, txn)| (txn.signatures[0], if idx == 0 { tip } else { 0 }))
+                .collect())
+        }
+        Some(err) => Err(anyhow!("Launch bundle {} failed: {}", bundle_id, err)),
+    }
+}
+
+// A ladder sell that was sent, kept after it timed out since it may still land
+struct SentTranche {
+    sell: LadderSell,
+    signature: Signature,
+    last_valid_block_height: u64,
+    tip: u64,
+}
+
+// Sells the tranches of `sell` right away, quoted against the curve of the trade that hit them
+async fn sell_tranche(
+    profile: &TradingProfile,
+    tracker: &ConfirmationTracker,
+    fee_payer: &FarmWallet,
+    seller: &FarmWallet,
+    sell: &LadderSell,
+    creator: Pubkey,
+    curve: &BondingCurveQuote,
+) -> Result<(SentTranche, TxOutcome)> {
+    println!(
+        "\n🪜 Ladder: {} tranche(s) hit at market cap {} lamports, selling {} tokens",
+        sell.tranches.len(),
+        sell.market_cap,
+        sell.token_amount
+    );
+
+    let sender = profile.tx_sender().await?;
+    let sell_ixs = sell.instructions(seller.pubkey(), creator, curve, profile.slippage_pct())?;
+    let blockhash = wait_for_blockhash().await;
+    let txn = fee_payer.sign(
+        &[&*seller.signer],
+        &profile.with_tip(sender.as_ref(), fee_payer.pubkey(), sell_ixs),
+        &[],
+        &TxLifetime::Blockhash(blockhash.blockhash),
+    )?;
+    let encoded_tx = sender.encode(&txn)?;
+    let outcome = sender
+        .send_and_confirm(
+            &encoded_tx,
+            tracker,
+            Some(blockhash.last_valid_block_height),
+        )
+        .await?;
+
+    Ok((
+        SentTranche {
+            sell: sell.clone(),
+            signature: txn.signatures[0],
+            last_valid_block_height: blockhash.last_valid_block_height,
+            tip: profile.tip_lamports(sender.as_ref()),
+        },
+        outcome,
+    ))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    env_logger::init();
+    let config = init_bot_config(&[
+        "grpc_endpoint",
+        "grpc_token",
+        "confirm_service",
+        "buy_sol_amount",
+        "token_name",
+        "token_symbol",
+        "token_uri",
+    ])?;
+
+    println!("🚀 PumpFun Sniper Farm Bot");
+    let rpc_client = rpc_client()?;
+    let farm = WalletFarm::from_env()?;
+    farm.refresh_balances(&rpc_client, &[]).await?;
+    farm.print_summary();
+    // The token is launched with the profile active at startup, its sells use whichever profile
+    // is active when they are sent
+    let launch_profile = active_profile()?;
+    println!("Profile {}", launch_profile);
+    // Loaded before the launch so a broken strategy config fails fast
+    let strategy_config = StrategyConfig::from_profile(&launch_profile)?;
+    let mut strategy = Strategy::new(&strategy_config, &farm)?;
+    let ledger = Ledger::from_env()?;
+    let earlier_runs = ledger.pnl_by_wallet();
+    if !earlier_runs.is_empty() {
+        println!(
+            "📒 {} from earlier runs, see `farm pnl`",
+            ledger.path().display()
+        );
+        for (wallet, summary) in earlier_runs {
+            println!("  {}: {}", wallet, summary);
+        }
+    }
+
+    let deployer = farm.deployer();
+    let fee_payer = farm.fee_payer();
+
+    let sender = launch_profile.tx_sender().await?;
+    println!("Connected to {}", sender.region());
+
+    spawn_blockhash_refresher(rpc_client.clone())?;
+    spawn_profile_switcher();
+    spawn_config_reloader()?;
+
+    let nonce = if config.durable_nonce {
+        let nonce = NonceManager::new(rpc_client.clone())
+            .ensure_nonce_account(&*deployer.signer)
+            .await?;
+        println!("Durable nonce account: {}", nonce.address);
+        Some(nonce)
+    } else {
+        None
+    };
+
+    let mint_keypair = Keypair::new();
+    let mint = mint_keypair.pubkey();
+    println!("🎯 Generated new mint address: {}", mint);
+
+    let tracker = ConfirmationTracker::new(rpc_client.clone());
+    // Every buy of the launch is quoted on the fresh curve of the on-chain config
+    let global = fetch_global(&rpc_client).await?;
+    if config.launch_buys.is_empty() {
+        let signature = create_token(
+            &farm,
+            &launch_profile,
+            sender.as_ref(),
+            &tracker,
+            &mint_keypair,
+            &global,
+        )
+        .await?;
+        let tip = launch_profile.tip_lamports(sender.as_ref());
+        record_fills(&ledger, &signature, &[deployer.pubkey()], tip).await;
+    } else {
+        let wallets: Vec<_> = farm.wallets.iter().map(|wallet| wallet.pubkey()).collect();
+        for (signature, tip) in
+            launch_token(&farm, &launch_profile, &tracker, &mint_keypair, &global).await?
+        {
+            record_fills(&ledger, &signature, &wallets, tip).await;
+        }
+    }
+
+    println!("\n🔄 Step 3: Pre-building sell instructions...");
+    // The profile the sell was built with, it is rebuilt if another one is active when it fires
+    let sell_profile = launch_profile.clone();
+    let sell_ixs = PumpFunSell::new(rpc_client.clone())
+        .sell_all_tokens_and_close_ata(mint, deployer.pubkey(), sell_profile.slippage_pct())
+        .await?;
+
+    // Signed against the durable nonce, the sell stays valid however long the wait for a buyer
+    let mut presigned_sell = match &nonce {
+        Some(nonce) if !config.fan_out_sell => {
+            let txn = fee_payer.sign(
+                &[&*deployer.signer],
+                &sell_profile.with_tip(sender.as_ref(), fee_payer.pubkey(), sell_ixs.clone()),
+                &[],
+                &nonce.lifetime(),
+            )?;
+            Some((sender.encode(&txn)?, txn.signatures[0]))
+        }
+        _ => None,
+    };
+
+    let (dev_tokens, _) = PumpFun::dev_buy_amounts(
+        launch_profile.buy_sol_amount()? as f64 / 1_000_000_000.0,
+        &global,
+    )?;
+    let mut position = Position::new(
+        mint,
+        dev_tokens,
+        BondingCurveQuote::initial_with(&global).buy_exact_tokens_out(dev_tokens)?,
+    );
+    // Dropped once a tranche sold, the pre-built sell was sized for the whole position
+    let mut sell_ixs = Some(sell_ixs);
+    let mut ladder = SellLadder::new(mint, dev_tokens, &strategy_config.ladder)?;
+    let mut pending_tranche: Option<SentTranche> = None;
+    if !ladder.is_empty() {
+        println!("Sell ladder with {} tranches", ladder.tranches.len());
+    }
+
+    println!("\n🔄 Step 4: Monitoring trades of {}...", mint);
+    let mut monitor = TradeMonitor::spawn(vec![mint])?;
+    let mut ticker = tokio::time::interval(Duration::from_secs(STRATEGY_TICK_SEC));
+    let mut settings = settings_changed();
+
+    let signal = loop {
+        let signal = tokio::select! {
+            update = monitor.recv() => {
+                let update = update
+                    .ok_or_else(|| anyhow!("Monitoring pipeline closed before the sell triggered"))?;
+                strategy.on_trade(&mut position, &update)
+            }
+            _ = ticker.tick() => strategy.on_tick(&position),
+            Ok(()) = settings.changed() => {
+                // The ladder keeps its tranches, only the rule selling the rest follows the profile
+                let profile = active_profile()?;
+                match StrategyConfig::from_profile(&profile)
+                    .and_then(|config| Strategy::new(&config, &farm))
+                {
+                    Ok(reloaded) if reloaded.rule != strategy.rule => {
+                        println!("📐 Sell rule of profile {} in effect", profile.name);
+                        strategy = reloaded;
+                    }
+                    Ok(_) => {}
+                    Err(err) => eprintln!("Keeping the current sell rule: {}", err),
+                }
+                None
+            }
+        };
+
+        if let Some(signal) = signal {
+            break signal;
+        }
+
+        // Tranches that were hit are sold right away, the rest of the position keeps waiting
+        let (Some(curve), Some(creator)) = (position.curve, position.creator) else {
+            continue;
+        };
+        // A timed out sell may still land, its tranches are only offered again once it cannot
+        let (sent, outcome) = match pending_tranche.take() {
+            Some(sent) => match tracker
+                .check(&sent.signature, Some(sent.last_valid_block_height))
+                .await
+            {
+                Ok(Some(outcome)) => (sent, outcome),
+                Ok(None) => {
+                    pending_tranche = Some(sent);
+                    continue;
+                }
+                Err(err) => {
+                    eprintln!("Could not check ladder sell {}: {}", sent.signature, err);
+                    pending_tranche = Some(sent);
+                    continue;
+                }
+            },
+            None => {
+                let Some(sell) = ladder.on_curve(&curve) else {
+                    continue;
+                };
+                match sell_tranche(
+                    &active_profile()?,
+                    &tracker,
+                    fee_payer,
+                    deployer,
+                    &sell,
+                    creator,
+                    &curve,
+                )
+                .await
+                {
+                    Ok(sent) => sent,
+                    Err(err) => {
+                        eprintln!("Ladder sell failed, retrying on the next trade: {}", err);
+                        continue;
+                    }
+                }
+            }
+        };
+        println!("Ladder sell outcome: {:?}", outcome);
+        match outcome {
+            TxOutcome::Landed { .. } => {}
+            TxOutcome::TimedOut => {
+                pending_tranche = Some(sent);
+                continue;
+            }
+            // A missed tranche stays in the ladder and is tried again on the next trade
+            _ => continue,
+        }
+
+        let sell = &sent.sell;
+        ladder.mark_sold(sell);
+        record_fills(&ledger, &sent.signature, &[deployer.pubkey()], sent.tip).await;
+        position.reduce(sell.token_amount);
+        if sell.close_ata {
+            println!("✅ Sell ladder completed, position closed");
+            return Ok(());
+        }
+        sell_ixs = None;
+        presigned_sell = None;
+    };
+    println!("👀 Sell triggered: {}", signal.reason);
+    // A tranche sell that may still land changes the balance the sell was built for
+    if pending_tranche.is_some() {
+        sell_ixs = None;
+    }
+
+    println!("\n🔄 Step 5: Firing sell...");
+    let profile = active_profile()?;
+    let sell_ixs = match sell_ixs {
+        Some(sell_ixs) if profile == sell_profile => sell_ixs,
+        _ => {
+            println!("Rebuilding the sell with profile {}", profile.name);
+            presigned_sell = None;
+            PumpFunSell::new(rpc_client.clone())
+                .sell_all_tokens_and_close_ata(mint, deployer.pubkey(), profile.slippage_pct())
+                .await?
+        }
+    };
+    let sender = profile.tx_sender().await?;
+    let tip = profile.tip_lamports(sender.as_ref());
+    let landed = if config.fan_out_sell {
+        fan_out_sell(
+            &profile,
+            fee_payer,
+            deployer,
+            &tracker,
+            sell_ixs,
+            nonce.as_ref(),
+        )
+        .await?
+    } else if let Some((encoded_tx, signature)) = presigned_sell {
+        let outcome = sender.send_and_confirm(&encoded_tx, &tracker, None).await?;
+        println!("Sell outcome: {:?}", outcome);
+        outcome.is_landed().then_some(signature)
+    } else {
+        let blockhash = wait_for_blockhash().await;
+        let txn = fee_payer.sign(
+            &[&*deployer.signer],
+            &profile.with_tip(sender.as_ref(), fee_payer.pubkey(), sell_ixs),
+            &[],
+            &TxLifetime::Blockhash(blockhash.blockhash),
+        )?;
+        let encoded_tx = sender.encode(&txn)?;
+        let outcome = sender
+            .send_and_confirm(
+                &encoded_tx,
+                &tracker,
+                Some(blockhash.last_valid_block_height),
+            )
+            .await?;
+        println!("Sell outcome: {:?}", outcome);
+        outcome.is_landed().then_some(txn.signatures[0])
+    };
+
+    if let Some(signature) = landed {
+        record_fills(&ledger, &signature, &[deployer.pubkey()], tip).await;
+        if let Some(position) = ledger.position(&deployer.pubkey().to_string(), &mint.to_string()) {
+            println!(
+                "📒 Realized PnL of {}: {} lamports",
+                mint, position.realized_pnl
+            );
+        }
+        if let Some(summary) = ledger.pnl_by_mint().get(&mint.to_string()) {
+            println!("📒 {} across the farm: {}", mint, summary);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use carbon_core::{
+    deserialize::{ArrangeAccounts, CarbonDeserialize},
+    error::{CarbonResult, Error as CarbonError},
+    instruction::InstructionProcessorInputType,
+    metrics::MetricsCollection,
+    pipeline::{Pipeline, ShutdownStrategy},
+    processor::Processor,
+};
+use carbon_log_metrics::LogMetrics;
+use carbon_pumpfun_decoder::{
+    PumpfunDecoder,
+    accounts::global::Global,
+    instructions::{PumpfunInstruction, create::Create},
+};
+use carbon_yellowstone_grpc_datasource::{BlockFilters, YellowstoneGrpcGeyserClient};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use tokio::{
+    sync::{RwLock, mpsc},
+    task::JoinHandle,
+};
+use yellowstone_grpc_proto::geyser::{CommitmentLevel, SubscribeRequestFilterTransactions};
+
+use crate::{
+    config::{grpc_endpoint, grpc_token},
+    error::{ClientError, ClientResult},
+    instructions::{
+        pumpfun_buy::{PUMPFUN_MINT_AUTHORITY, PUMPFUN_PROGRAM},
+        types::TradeEventTemp,
+    },
+    utils::BondingCurveQuote,
+};
+
+pub const CREATE_FILTER_NAME: &str = "pumpfun_creates";
+
+/// A token created on PumpFun, decoded from its `create` instruction.
+#[derive(Debug, Clone)]
+pub struct NewToken {
+    pub signature: Signature,
+    pub slot: u64,
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub associated_bonding_curve: Pubkey,
+    /// Creator recorded on the curve, who receives the creator fees.
+    pub creator: Pubkey,
+    /// Wallet that signed the create.
+    pub user: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    /// Buy of `user` in the create transaction, if any.
+    pub dev_buy: Option<TradeEventTemp>,
+}
+
+impl NewToken {
+    /// Lamports the creator spent on the dev buy.
+    pub fn dev_buy_sol(&self) -> u64 {
+        self.dev_buy.as_ref().map_or(0, |event| event.sol_amount)
+    }
+
+    /// Curve once the create transaction landed, the fresh curve of `global` without a dev buy.
+    pub fn curve(&self, global: &Global) -> BondingCurveQuote {
+        self.dev_buy
+            .as_ref()
+            .map(BondingCurveQuote::from_trade_event)
+            .unwrap_or_else(|| BondingCurveQuote::initial_with(global))
+    }
+}
+
+/// Forwards every decoded `create` instruction as a [`NewToken`].
+pub struct CreateProcessor {
+    pub sender: mpsc::UnboundedSender<NewToken>,
+}
+
+#[async_trait]
+impl Processor for CreateProcessor {
+    type InputType = InstructionProcessorInputType<PumpfunInstruction>;
+
+    async fn process(
+        &mut self,
+        (metadata, instruction, _nested, _raw): Self::InputType,
+        _metrics: Arc<MetricsCollection>,
+    ) -> CarbonResult<()> {
+        let PumpfunInstruction::Create(create) = instruction.data else {
+            return Ok(());
+        };
+        let Some(accounts) = Create::arrange_accounts(&instruction.accounts) else {
+            return Ok(());
+        };
+
+        // The dev buy emits its trade event through a self-CPI of the same transaction
+        let transaction = &metadata.transaction_metadata;
+        let dev_buy = transaction
+            .meta
+            .inner_instructions
+            .iter()
+            .flatten()
+            .flat_map(|inner| &inner.instructions)
+            .filter_map(|inner| TradeEventTemp::deserialize(&inner.instruction.data))
+            .find(|event| {
+                event.is_buy && event.mint == accounts.mint && event.user == accounts.user
+            });
+
+        let token = NewToken {
+            signature: transaction.signature,
+            slot: transaction.slot,
+            mint: accounts.mint,
+            bonding_curve: accounts.bonding_curve,
+            associated_bonding_curve: accounts.associated_bonding_curve,
+            creator: create.creator,
+            user: accounts.user,
+            name: create.name,
+            symbol: create.symbol,
+            uri: create.uri,
+            dev_buy,
+        };
+
+        self.sender
+            .send(token)
+            .map_err(|_| CarbonError::Custom(ClientError::ChannelClosed.to_string()))
+    }
+}
+
+/// Subscribes to PumpFun transactions that create a token.
+pub fn build_create_pipeline(sender: mpsc::UnboundedSender<NewToken>) -> ClientResult<Pipeline> {
+    let mut transaction_filters = HashMap::new();
+    transaction_filters.insert(
+        CREATE_FILTER_NAME.to_string(),
+        SubscribeRequestFilterTransactions {
+            vote: Some(false),
+            failed: Some(false),
+            // Only `create` touches the mint authority
+            account_include: vec![PUMPFUN_MINT_AUTHORITY.to_string()],
+            account_required: vec![PUMPFUN_PROGRAM.to_string()],
+            ..Default::default()
+        },
+    );
+
+    let datasource = YellowstoneGrpcGeyserClient::new(
+        grpc_endpoint()?,
+        Some(grpc_token()?),
+        Some(CommitmentLevel::Processed),
+        HashMap::new(),
+        transaction_filters,
+        BlockFilters::default(),
+        Arc::new(RwLock::new(HashSet::new())),
+    );
+
+    let pipeline = Pipeline::builder()
+        .datasource(datasource)
+        .metrics(Arc::new(LogMetrics::new()))
+        .instruction(PumpfunDecoder, CreateProcessor { sender })
+        .shutdown_strategy(ShutdownStrategy::Immediate)
+        .build()?;
+
+    Ok(pipeline)
+}
+
+/// Running create pipeline plus the channel of new tokens.
+pub struct CreateMonitor {
+    pub receiver: mpsc::UnboundedReceiver<NewToken>,
+    pub handle: JoinHandle<()>,
+}
+
+impl CreateMonitor {
+    pub fn spawn() -> ClientResult<Self> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        let mut pipeline = build_create_pipeline(sender)?;
+        let handle = tokio::spawn(async move {
+            if let Err(err) = pipeline.run().await {
+                eprintln!("Create monitor pipeline stopped: {}", err);
+            }
+        });
+
+        Ok(Self { receiver, handle })
+    }
+
+    pub async fn recv(&mut self) -> Option<NewToken> {
+        self.receiver.recv().await
+    }
+}
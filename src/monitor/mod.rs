@@ -0,0 +1,6 @@
+pub mod create_monitor;
+pub mod trade_pipeline;
+pub mod trade_processor;
+pub use create_monitor::*;
+pub use trade_pipeline::*;
+pub use trade_processor::*;
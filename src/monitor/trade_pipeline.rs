@@ -0,0 +1,107 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use carbon_core::pipeline::{Pipeline, ShutdownStrategy};
+use carbon_log_metrics::LogMetrics;
+use carbon_yellowstone_grpc_datasource::{BlockFilters, YellowstoneGrpcGeyserClient};
+use solana_sdk::pubkey::Pubkey;
+use tokio::{
+    sync::{RwLock, mpsc},
+    task::JoinHandle,
+};
+use yellowstone_grpc_proto::geyser::{CommitmentLevel, SubscribeRequestFilterTransactions};
+
+use crate::{
+    config::{grpc_endpoint, grpc_token},
+    error::ClientResult,
+    instructions::pumpfun_buy::PUMPFUN_PROGRAM,
+    monitor::{TrackedMints, TradeEventDecoder, TradeEventProcessor, TradeUpdate},
+};
+
+pub const TRADE_FILTER_NAME: &str = "pumpfun_trades";
+
+/// Subscribes to PumpFun transactions touching `mints`.
+///
+/// The Yellowstone filter is fixed for the lifetime of the subscription, so mints added to
+/// `tracked_mints` afterwards are only seen if they share a transaction with a subscribed one;
+/// pass an empty `mints` slice to stream every PumpFun trade and filter purely on `tracked_mints`.
+pub fn build_trade_pipeline(
+    mints: &[Pubkey],
+    tracked_mints: TrackedMints,
+    sender: mpsc::UnboundedSender<TradeUpdate>,
+) -> ClientResult<Pipeline> {
+    let mut transaction_filters = HashMap::new();
+    transaction_filters.insert(
+        TRADE_FILTER_NAME.to_string(),
+        SubscribeRequestFilterTransactions {
+            vote: Some(false),
+            failed: Some(false),
+            account_include: mints.iter().map(Pubkey::to_string).collect(),
+            account_required: vec![PUMPFUN_PROGRAM.to_string()],
+            ..Default::default()
+        },
+    );
+
+    let datasource = YellowstoneGrpcGeyserClient::new(
+        grpc_endpoint()?,
+        Some(grpc_token()?),
+        Some(CommitmentLevel::Processed),
+        HashMap::new(),
+        transaction_filters,
+        BlockFilters::default(),
+        Arc::new(RwLock::new(HashSet::new())),
+    );
+
+    let pipeline = Pipeline::builder()
+        .datasource(datasource)
+        .metrics(Arc::new(LogMetrics::new()))
+        .instruction(
+            TradeEventDecoder,
+            TradeEventProcessor::new(tracked_mints, sender),
+        )
+        .shutdown_strategy(ShutdownStrategy::Immediate)
+        .build()?;
+
+    Ok(pipeline)
+}
+
+/// Running trade pipeline plus the channel the strategy layer consumes.
+pub struct TradeMonitor {
+    pub tracked_mints: TrackedMints,
+    pub receiver: mpsc::UnboundedReceiver<TradeUpdate>,
+    pub handle: JoinHandle<()>,
+}
+
+impl TradeMonitor {
+    pub fn spawn(mints: Vec<Pubkey>) -> ClientResult<Self> {
+        let tracked_mints: TrackedMints = Arc::new(RwLock::new(mints.iter().copied().collect()));
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        let mut pipeline = build_trade_pipeline(&mints, tracked_mints.clone(), sender)?;
+        let handle = tokio::spawn(async move {
+            if let Err(err) = pipeline.run().await {
+                eprintln!("Trade monitor pipeline stopped: {}", err);
+            }
+        });
+
+        Ok(Self {
+            tracked_mints,
+            receiver,
+            handle,
+        })
+    }
+
+    pub async fn track(&self, mint: Pubkey) {
+        self.tracked_mints.write().await.insert(mint);
+    }
+
+    pub async fn untrack(&self, mint: &Pubkey) {
+        self.tracked_mints.write().await.remove(mint);
+    }
+
+    pub async fn recv(&mut self) -> Option<TradeUpdate> {
+        self.receiver.recv().await
+    }
+}
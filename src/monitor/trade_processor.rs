@@ -0,0 +1,115 @@
+use std::{collections::HashSet, sync::Arc};
+
+use async_trait::async_trait;
+use carbon_core::{
+    deserialize::CarbonDeserialize,
+    error::{CarbonResult, Error as CarbonError},
+    instruction::{DecodedInstruction, InstructionDecoder, InstructionProcessorInputType},
+    metrics::MetricsCollection,
+    processor::Processor,
+};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signature};
+use tokio::sync::{RwLock, mpsc};
+
+use crate::{
+    error::ClientError,
+    instructions::{pumpfun_buy::PUMPFUN_PROGRAM, types::TradeEventTemp},
+};
+
+/// Mints whose trades are forwarded; shared so mints can be added while the pipeline runs.
+pub type TrackedMints = Arc<RwLock<HashSet<Pubkey>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+/// A decoded PumpFun trade on one of the tracked mints.
+#[derive(Debug, Clone)]
+pub struct TradeUpdate {
+    pub side: TradeSide,
+    pub signature: Signature,
+    pub slot: u64,
+    pub fee_payer: Pubkey,
+    pub event: TradeEventTemp,
+}
+
+impl TradeUpdate {
+    pub fn is_buy(&self) -> bool {
+        self.side == TradeSide::Buy
+    }
+}
+
+/// Decodes the `TradeEvent` PumpFun emits through its self-CPI inner instruction.
+pub struct TradeEventDecoder;
+
+impl InstructionDecoder<'_> for TradeEventDecoder {
+    type InstructionType = TradeEventTemp;
+
+    fn decode_instruction(
+        &self,
+        instruction: &Instruction,
+    ) -> Option<DecodedInstruction<Self::InstructionType>> {
+        if instruction.program_id != PUMPFUN_PROGRAM {
+            return None;
+        }
+
+        let event = TradeEventTemp::deserialize(&instruction.data)?;
+
+        Some(DecodedInstruction {
+            program_id: instruction.program_id,
+            data: event,
+            accounts: instruction.accounts.clone(),
+        })
+    }
+}
+
+pub struct TradeEventProcessor {
+    pub tracked_mints: TrackedMints,
+    pub sender: mpsc::UnboundedSender<TradeUpdate>,
+}
+
+impl TradeEventProcessor {
+    pub fn new(tracked_mints: TrackedMints, sender: mpsc::UnboundedSender<TradeUpdate>) -> Self {
+        Self {
+            tracked_mints,
+            sender,
+        }
+    }
+}
+
+#[async_trait]
+impl Processor for TradeEventProcessor {
+    type InputType = InstructionProcessorInputType<TradeEventTemp>;
+
+    async fn process(
+        &mut self,
+        (metadata, instruction, _nested, _raw): Self::InputType,
+        _metrics: Arc<MetricsCollection>,
+    ) -> CarbonResult<()> {
+        let event = instruction.data;
+
+        if !self.tracked_mints.read().await.contains(&event.mint) {
+            return Ok(());
+        }
+
+        let side = if event.is_buy {
+            TradeSide::Buy
+        } else {
+            TradeSide::Sell
+        };
+
+        let update = TradeUpdate {
+            side,
+            signature: metadata.transaction_metadata.signature,
+            slot: metadata.transaction_metadata.slot,
+            fee_payer: metadata.transaction_metadata.fee_payer,
+            event,
+        };
+
+        self.sender
+            .send(update)
+            .map_err(|_| CarbonError::Custom(ClientError::ChannelClosed.to_string()))
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use reqwest::RequestBuilder;
+use serde_json::{Value, json};
+use solana_sdk::signature::Signature;
+use tokio::time::sleep;
+
+use crate::{
+    error::{ClientError, ClientResult},
+    service::{CONFIRM_POLL_MS, ConfirmationLevel, Jito},
+};
+
+/// Jito rejects bundles with more transactions than this.
+pub const JITO_MAX_BUNDLE_TXS: usize = 5;
+/// Max bundle ids per `getBundleStatuses` / `getInflightBundleStatuses` call.
+pub const JITO_MAX_STATUS_IDS: usize = 5;
+
+/// Landed bundle as reported by `getBundleStatuses`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleStatus {
+    pub bundle_id: String,
+    pub transactions: Vec<Signature>,
+    pub slot: u64,
+    pub confirmation_status: ConfirmationLevel,
+    /// `None` when every transaction succeeded.
+    pub err: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InflightBundleState {
+    /// Unknown to the block engine, or older than 5 minutes.
+    Invalid,
+    Pending,
+    /// Every region that received the bundle marked it failed.
+    Failed,
+    Landed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InflightBundleStatus {
+    pub bundle_id: String,
+    pub status: InflightBundleState,
+    pub landed_slot: Option<u64>,
+}
+
+impl FromStr for InflightBundleState {
+    type Err = ClientError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Invalid" => Ok(Self::Invalid),
+            "Pending" => Ok(Self::Pending),
+            "Failed" => Ok(Self::Failed),
+            "Landed" => Ok(Self::Landed),
+            other => Err(ClientError::InvalidData(format!(
+                "unknown inflight bundle status '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+impl Jito {
+    /// Attaches `x-jito-auth` when an auth key is configured.
+    pub(crate) fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.auth_key {
+            Some(auth_key) => request.header("x-jito-auth", auth_key),
+            None => request,
+        }
+    }
+
+    async fn bundle_rpc(&self, method: &str, params: Value) -> ClientResult<Value> {
+        let payload = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params
+        });
+
+        let response = self
+            .authorized(self.client.post(self.endpoint.bundle_endpoint()))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|err| ClientError::Send(method.to_string(), err.to_string()))?;
+        let data: Value = response
+            .json()
+            .await
+            .map_err(|err| ClientError::Parse(method.to_string(), err.to_string()))?;
+
+        if let Some(error) = data.get("error") {
+            return Err(ClientError::Send(method.to_string(), error.to_string()));
+        }
+
+        data.get("result").cloned().ok_or_else(|| {
+            ClientError::Parse(format!("{} has no result", method), data.to_string())
+        })
+    }
+
+    /// Submits base64 encoded transactions as one atomic bundle and returns the bundle id.
+    ///
+    /// The tip has to be part of one of the transactions, usually the last one.
+    pub async fn send_bundle(&self, encoded_txs: &[String]) -> ClientResult<String> {
+        if encoded_txs.is_empty() || encoded_txs.len() > JITO_MAX_BUNDLE_TXS {
+            return Err(ClientError::InvalidInput(
+                "a bundle needs between 1 and 5 transactions",
+            ));
+        }
+
+        let start = Instant::now();
+        let result = self
+            .bundle_rpc("sendBundle", json!([encoded_txs, {"encoding": "base64"}]))
+            .await?;
+        println!("Bundle submission took: {:?}", start.elapsed());
+
+        result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| ClientError::Parse("sendBundle result".to_string(), result.to_string()))
+    }
+
+    /// Statuses of landed bundles, `None` for ids that have not landed (yet).
+    pub async fn get_bundle_statuses(
+        &self,
+        bundle_ids: &[String],
+    ) -> ClientResult<Vec<Option<BundleStatus>>> {
+        check_status_ids(bundle_ids)?;
+
+        let result = self
+            .bundle_rpc("getBundleStatuses", json!([bundle_ids]))
+            .await?;
+        let values = status_values(&result)?;
+
+        let mut statuses = vec![None; bundle_ids.len()];
+        for value in values.iter().filter(|value| !value.is_null()) {
+            let status = parse_bundle_status(value)?;
+            if let Some(idx) = bundle_ids.iter().position(|id| *id == status.bundle_id) {
+                statuses[idx] = Some(status);
+            }
+        }
+
+        Ok(statuses)
+    }
+
+    /// Statuses of bundles submitted within the last 5 minutes.
+    pub async fn get_inflight_bundle_statuses(
+        &self,
+        bundle_ids: &[String],
+    ) -> ClientResult<Vec<InflightBundleStatus>> {
+        check_status_ids(bundle_ids)?;
+
+        let result = self
+            .bundle_rpc("getInflightBundleStatuses", json!([bundle_ids]))
+            .await?;
+
+        status_values(&result)?
+            .iter()
+            .map(parse_inflight_status)
+            .collect()
+    }
+
+    /// Polls the inflight status until the bundle lands or is dropped, then returns the full
+    /// landed status.
+    pub async fn confirm_bundle(
+        &self,
+        bundle_id: &str,
+        timeout: Duration,
+    ) -> ClientResult<BundleStatus> {
+        let ids = [bundle_id.to_string()];
+        let deadline = Instant::now() + timeout;
+
+        while Instant::now() < deadline {
+            let inflight = self.get_inflight_bundle_statuses(&ids).await?;
+
+            match inflight.first().map(|status| status.status) {
+                Some(InflightBundleState::Landed) => {
+                    if let Some(Some(status)) = self.get_bundle_statuses(&ids).await?.pop() {
+                        return Ok(status);
+                    }
+                }
+                Some(InflightBundleState::Failed) => {
+                    return Err(ClientError::Send(
+                        "JITO".to_string(),
+                        format!("bundle {} failed", bundle_id),
+                    ));
+                }
+                // A fresh bundle can show up as invalid until the engine has seen it
+                Some(InflightBundleState::Invalid | InflightBundleState::Pending) | None => {}
+            }
+
+            sleep(Duration::from_millis(CONFIRM_POLL_MS)).await;
+        }
+
+        Err(ClientError::Timeout(
+            "bundle confirmation".to_string(),
+            format!("{} not landed after {:?}", bundle_id, timeout),
+        ))
+    }
+}
+
+fn check_status_ids(bundle_ids: &[String]) -> ClientResult<()> {
+    if bundle_ids.is_empty() || bundle_ids.len() > JITO_MAX_STATUS_IDS {
+        return Err(ClientError::InvalidInput(
+            "bundle status requests take between 1 and 5 ids",
+        ));
+    }
+
+    Ok(())
+}
+
+fn status_values(result: &Value) -> ClientResult<&Vec<Value>> {
+    result
+        .get("value")
+        .and_then(Value::as_array)
+        .ok_or_else(|| ClientError::Parse("bundle status value".to_string(), result.to_string()))
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> ClientResult<&'a Value> {
+    value.get(name).ok_or_else(|| {
+        ClientError::Parse(format!("missing bundle field {}", name), value.to_string())
+    })
+}
+
+fn parse_bundle_status(value: &Value) -> ClientResult<BundleStatus> {
+    let bundle_id = field(value, "bundle_id")?
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+
+    let transactions = field(value, "transactions")?
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .map(|sig| {
+            Signature::from_str(sig.as_str().unwrap_or_default())
+                .map_err(|err| ClientError::Parse("bundle signature".to_string(), err.to_string()))
+        })
+        .collect::<ClientResult<Vec<_>>>()?;
+
+    let slot = field(value, "slot")?.as_u64().unwrap_or_default();
+
+    let confirmation_status = match field(value, "confirmation_status")?.as_str() {
+        Some("processed") => ConfirmationLevel::Processed,
+        Some("confirmed") => ConfirmationLevel::Confirmed,
+        Some("finalized") => ConfirmationLevel::Finalized,
+        other => {
+            return Err(ClientError::InvalidData(format!(
+                "unknown bundle confirmation status {:?}",
+                other
+            )));
+        }
+    };
+
+    // Success is reported as `{"Ok": null}`
+    let err = match value.get("err") {
+        None | Some(Value::Null) => None,
+        Some(err) if err.get("Ok").is_some() => None,
+        Some(err) => Some(err.to_string()),
+    };
+
+    Ok(BundleStatus {
+        bundle_id,
+        transactions,
+        slot,
+        confirmation_status,
+        err,
+    })
+}
+
+fn parse_inflight_status(value: &Value) -> ClientResult<InflightBundleStatus> {
+    Ok(InflightBundleStatus {
+        bundle_id: field(value, "bundle_id")?
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        status: InflightBundleState::from_str(
+            field(value, "status")?.as_str().unwrap_or_default(),
+        )?,
+        landed_slot: value.get("landed_slot").and_then(Value::as_u64),
+    })
+}
@@ -1,15 +1,10 @@
+use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::json;
-use solana_program::example_mocks::solana_sdk::system_instruction;
-use solana_sdk::{
-    compute_budget::ComputeBudgetInstruction, instruction::Instruction,
-    native_token::sol_to_lamports, pubkey::Pubkey,
-};
 use std::time::{Duration, Instant};
-use tokio::time::sleep;
 
 use crate::service::{
-    ping_all, ping_one, JitoEndpoint, JitoRegionsType, Tips, HEALTH_CHECK_SEC, JITO_MIN_TIP, JITO_REGIONS, JITO_TIP, PING_DURATION_SEC
+    ping_all, ping_one, JitoEndpoint, JitoRegionsType, TxSender, HEALTH_CHECK_SEC, JITO_MIN_TIP, JITO_REGIONS, JITO_TIP, PING_DURATION_SEC
 };
 
 #[derive(Debug)]
@@ -88,7 +83,7 @@ impl Jito {
         }
     }
 
-    pub fn health_check(&self, interval_sec: u64) {
+    pub fn health_check(&self, _interval_sec: u64) {
         // let client = self.client.clone();
         // let endpoint = self.endpoint.clone();
         // let relayer_name = endpoint.relayer_name.clone();
@@ -123,38 +118,10 @@ impl Jito {
         // });
     }
 
-    pub fn add_tip_ix(&self, tip_config: Tips) -> Vec<Instruction> {
-        let mut ixs: Vec<Instruction> = Vec::new();
-
-        if let Some(cu) = tip_config.cu {
-            ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(cu as u32));
-        };
-
-        if let Some(priority_fee_micro_lamport) = tip_config.priority_fee_micro_lamport {
-            ixs.push(ComputeBudgetInstruction::set_compute_unit_price(
-                priority_fee_micro_lamport,
-            ));
-        };
-
-        ixs.extend(tip_config.pure_ix.clone());
-
-        let relayer_fee = tip_config.tip_sol_amount.max(JITO_MIN_TIP); // use `.max()` for clarity
-
-        let recipient = Pubkey::from_str_const(JITO_TIP[tip_config.tip_addr_idx as usize]);
-        let transfer_ix = system_instruction::transfer(
-            &tip_config.payer,
-            &recipient,
-            sol_to_lamports(relayer_fee),
-        );
-        ixs.push(transfer_ix);
-
-        ixs
-    }
-
     pub async fn send_transaction(&self, encoded_tx: &str) -> anyhow::Result<serde_json::Value> {
         let start = Instant::now();
 
-        let url = format!("{}", self.endpoint.submit_endpoint);
+        let url = self.endpoint.submit_endpoint;
 
         let payload = json!({
             "jsonrpc": "2.0",
@@ -163,7 +130,7 @@ impl Jito {
             "params": [encoded_tx, {"encoding": "base64"}]
         });
 
-        let response = self.client.post(url).json(&payload).send().await?;
+        let response = self.authorized(self.client.post(url)).json(&payload).send().await?;
 
         let data: serde_json::Value = response.json().await?;
 
@@ -199,3 +166,26 @@ impl Jito {
         Ok(data)
     }
 }
+
+#[async_trait]
+impl TxSender for Jito {
+    fn name(&self) -> &'static str {
+        "JITO"
+    }
+
+    fn region(&self) -> &'static str {
+        self.endpoint.relayer_name
+    }
+
+    fn min_tip(&self) -> f64 {
+        JITO_MIN_TIP
+    }
+
+    fn tip_accounts(&self) -> &'static [&'static str] {
+        &JITO_TIP
+    }
+
+    async fn send_transaction(&self, encoded_tx: &str) -> anyhow::Result<serde_json::Value> {
+        Jito::send_transaction(self, encoded_tx).await
+    }
+}
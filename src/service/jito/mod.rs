@@ -1,6 +1,8 @@
+pub mod jito_bundle;
 pub mod jito_confirm;
 pub mod jito_regions;
 pub mod jito_tip_ix;
+pub use jito_bundle::*;
 pub use jito_confirm::*;
 pub use jito_regions::*;
 pub use jito_tip_ix::*;
@@ -0,0 +1,245 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{signature::Signature, transaction::TransactionError};
+use solana_transaction_status_client_types::{TransactionConfirmationStatus, TransactionStatus};
+use std::str::FromStr;
+use tokio::time::sleep;
+
+use crate::error::{ClientError, ClientResult};
+
+pub const CONFIRM_TIMEOUT_SEC: u64 = 30;
+pub const CONFIRM_POLL_MS: u64 = 400;
+// Block height is only checked every few polls, it moves ~2.5 times per second
+const EXPIRY_CHECK_EVERY: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfirmationLevel {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl From<&TransactionStatus> for ConfirmationLevel {
+    fn from(status: &TransactionStatus) -> Self {
+        match &status.confirmation_status {
+            Some(TransactionConfirmationStatus::Processed) => Self::Processed,
+            Some(TransactionConfirmationStatus::Confirmed) => Self::Confirmed,
+            Some(TransactionConfirmationStatus::Finalized) => Self::Finalized,
+            // Nodes that don't report a status only leave `confirmations` empty once rooted
+            None if status.confirmations.is_none() => Self::Finalized,
+            None => Self::Processed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxOutcome {
+    /// Executed successfully and reached the requested level.
+    Landed { slot: u64, level: ConfirmationLevel },
+    /// Included in a block but the execution failed; fees were still paid.
+    Failed {
+        slot: u64,
+        level: ConfirmationLevel,
+        err: TransactionError,
+    },
+    /// Blockhash expired before the transaction was seen.
+    Expired,
+    /// Not at the requested level when the tracker gave up. It may still land while its
+    /// blockhash is valid, so check it with `ConfirmationTracker::check` before sending again.
+    TimedOut,
+}
+
+impl TxOutcome {
+    pub fn is_landed(&self) -> bool {
+        matches!(self, Self::Landed { .. })
+    }
+
+    pub fn slot(&self) -> Option<u64> {
+        match self {
+            Self::Landed { slot, .. } | Self::Failed { slot, .. } => Some(*slot),
+            Self::Expired | Self::TimedOut => None,
+        }
+    }
+}
+
+/// Watches signatures returned by the relayers until they land, fail or expire.
+#[derive(Clone)]
+pub struct ConfirmationTracker {
+    pub rpc_client: Arc<RpcClient>,
+    pub target: ConfirmationLevel,
+    pub timeout: Duration,
+    pub poll_interval: Duration,
+}
+
+impl ConfirmationTracker {
+    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
+        Self {
+            rpc_client,
+            target: ConfirmationLevel::Confirmed,
+            timeout: Duration::from_secs(CONFIRM_TIMEOUT_SEC),
+            poll_interval: Duration::from_millis(CONFIRM_POLL_MS),
+        }
+    }
+
+    pub fn with_target(mut self, target: ConfirmationLevel) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub async fn track(
+        &self,
+        signature: &Signature,
+        last_valid_block_height: Option<u64>,
+    ) -> ClientResult<TxOutcome> {
+        self.track_many(std::slice::from_ref(signature), last_valid_block_height)
+            .await
+            .map(|(_, outcome)| outcome)
+    }
+
+    /// Tracks several signatures of which at most one is expected to land, returning the first
+    /// one that does. The others may fail on-chain by design, so `Failed` is only returned once
+    /// all of them failed, or once the blockhash expired after one of them failed. `Expired` is
+    /// returned once none of them can land anymore, `TimedOut` if that is still unknown when
+    /// the timeout is up.
+    pub async fn track_many(
+        &self,
+        signatures: &[Signature],
+        last_valid_block_height: Option<u64>,
+    ) -> ClientResult<(Signature, TxOutcome)> {
+        let first = *signatures
+            .first()
+            .ok_or(ClientError::InvalidInput("no signatures to track"))?;
+        let deadline = Instant::now() + self.timeout;
+        let mut polls: u32 = 0;
+
+        while Instant::now() < deadline {
+            // A version that executed can still reach the target after the blockhash expired
+            polls += 1;
+            let expiry =
+                last_valid_block_height.filter(|_| polls.is_multiple_of(EXPIRY_CHECK_EVERY));
+            if let Some(settled) = self.poll(signatures, expiry).await? {
+                return Ok(settled);
+            }
+
+            sleep(self.poll_interval).await;
+        }
+
+        println!(
+            "⏳ {} not {:?} after {:?}, it may still land",
+            first, self.target, self.timeout
+        );
+        Ok((first, TxOutcome::TimedOut))
+    }
+
+    /// Checks `signature` once, for a transaction that `TimedOut`. `None` while it may still
+    /// land, so it must not be sent again.
+    pub async fn check(
+        &self,
+        signature: &Signature,
+        last_valid_block_height: Option<u64>,
+    ) -> ClientResult<Option<TxOutcome>> {
+        let settled = self
+            .poll(std::slice::from_ref(signature), last_valid_block_height)
+            .await?;
+
+        Ok(settled.map(|(_, outcome)| outcome))
+    }
+
+    // One status query of `track_many`, `None` while the outcome is open. Expiry is only checked
+    // with `last_valid_block_height`.
+    async fn poll(
+        &self,
+        signatures: &[Signature],
+        last_valid_block_height: Option<u64>,
+    ) -> ClientResult<Option<(Signature, TxOutcome)>> {
+        let statuses = self
+            .rpc_client
+            .get_signature_statuses(signatures)
+            .await?
+            .value;
+
+        let mut executed = false;
+        let mut failure = None;
+        let mut failed = 0;
+        for (signature, status) in signatures.iter().zip(statuses) {
+            let Some(status) = status else {
+                continue;
+            };
+
+            let level = ConfirmationLevel::from(&status);
+            if let Some(err) = status.err {
+                failed += 1;
+                failure = Some((
+                    *signature,
+                    TxOutcome::Failed {
+                        slot: status.slot,
+                        level,
+                        err,
+                    },
+                ));
+                continue;
+            }
+
+            executed = true;
+            if level >= self.target {
+                return Ok(Some((
+                    *signature,
+                    TxOutcome::Landed {
+                        slot: status.slot,
+                        level,
+                    },
+                )));
+            }
+        }
+
+        if failed == signatures.len() {
+            return Ok(failure);
+        }
+
+        if !executed
+            && let Some(last_valid) = last_valid_block_height
+            && self.rpc_client.get_block_height().await? > last_valid
+        {
+            return Ok(Some(failure.unwrap_or((signatures[0], TxOutcome::Expired))));
+        }
+
+        Ok(None)
+    }
+}
+
+/// Extracts the signature from a relayer's JSON-RPC `sendTransaction` response.
+pub fn parse_signature_response(
+    provider: &str,
+    response: &serde_json::Value,
+) -> ClientResult<Signature> {
+    if let Some(error) = response.get("error") {
+        return Err(ClientError::Send(provider.to_string(), error.to_string()));
+    }
+
+    let result = response
+        .get("result")
+        .and_then(|result| result.as_str())
+        .ok_or_else(|| {
+            ClientError::Parse(
+                format!("{} response has no signature", provider),
+                response.to_string(),
+            )
+        })?;
+
+    Signature::from_str(result)
+        .map_err(|err| ClientError::Parse("Invalid signature".to_string(), err.to_string()))
+}
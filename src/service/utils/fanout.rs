@@ -0,0 +1,207 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures::future::join_all;
+use solana_sdk::{
+    hash::Hash, pubkey::Pubkey, signature::Signature, signer::Signer,
+    transaction::VersionedTransaction,
+};
+
+use crate::{
+    config::{ConfirmService, tx_sender},
+    error::{ClientError, ClientResult},
+    service::{ConfirmationTracker, Tips, TxOutcome, TxSender},
+    utils::{TxLifetime, try_build_and_sign},
+};
+
+/// How the per-provider versions are made mutually exclusive.
+///
+/// Every provider gets its own tip instruction, so each version has a different signature and
+/// would otherwise be able to land on its own.
+#[derive(Debug, Clone)]
+pub enum FanOutGuard {
+    /// All versions advance the same durable nonce: once one lands the others are invalid.
+    Nonce {
+        nonce_account: Pubkey,
+        authority: Pubkey,
+        nonce_hash: Hash,
+    },
+    /// The instructions themselves conflict (e.g. sell all + close ATA), so a second version
+    /// fails on-chain. Only use this when that is actually true.
+    ConflictingWrites {
+        recent_blockhash: Hash,
+        last_valid_block_height: u64,
+    },
+}
+
+impl FanOutGuard {
+    pub fn lifetime(&self) -> TxLifetime {
+        match self {
+            Self::Nonce {
+                nonce_account,
+                authority,
+                nonce_hash,
+            } => TxLifetime::Nonce {
+                nonce_account: *nonce_account,
+                authority: *authority,
+                nonce: *nonce_hash,
+            },
+            Self::ConflictingWrites {
+                recent_blockhash, ..
+            } => TxLifetime::Blockhash(*recent_blockhash),
+        }
+    }
+
+    /// Block height after which no version can land, a nonce never expires.
+    pub fn last_valid_block_height(&self) -> Option<u64> {
+        match self {
+            Self::Nonce { .. } => None,
+            Self::ConflictingWrites {
+                last_valid_block_height,
+                ..
+            } => Some(*last_valid_block_height),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ProviderResult {
+    pub provider: &'static str,
+    pub region: &'static str,
+    pub signature: Signature,
+    pub response: Result<serde_json::Value, String>,
+    pub latency: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct LandedTx {
+    pub provider: &'static str,
+    pub signature: Signature,
+    pub outcome: TxOutcome,
+}
+
+#[derive(Debug)]
+pub struct FanOutReport {
+    pub results: Vec<ProviderResult>,
+    /// The version the tracker settled on, `None` once every version expired. Its outcome can
+    /// also be `Failed` or `TimedOut`.
+    pub landed: Option<LandedTx>,
+}
+
+/// Submits one signed version of a transaction to every provider concurrently.
+pub struct FanOut {
+    pub senders: Vec<Arc<dyn TxSender>>,
+}
+
+impl FanOut {
+    pub fn new(senders: Vec<Arc<dyn TxSender>>) -> Self {
+        Self { senders }
+    }
+
+    /// Initializes every known confirm service.
+    pub async fn all_services() -> ClientResult<Self> {
+        let mut senders = Vec::new();
+        for service in ConfirmService::ALL {
+            senders.push(tx_sender(service).await?);
+        }
+
+        Ok(Self::new(senders))
+    }
+
+    /// Builds one version per provider from `tips`, replacing its tip account with a random
+    /// one of that provider. `payer` must be `tips.payer`.
+    pub fn build_versions(
+        &self,
+        payer: &dyn Signer,
+        extra_signers: &[&dyn Signer],
+        tips: &Tips,
+        guard: &FanOutGuard,
+    ) -> ClientResult<Vec<VersionedTransaction>> {
+        let lifetime = guard.lifetime();
+
+        self.senders
+            .iter()
+            .map(|sender| {
+                let ixs = sender.add_tip_ix(Tips {
+                    tip_addr_idx: sender.random_tip_idx(),
+                    ..tips.clone()
+                });
+
+                try_build_and_sign(payer, extra_signers, &ixs, &[], &lifetime)
+            })
+            .collect()
+    }
+
+    pub async fn submit(&self, versions: &[VersionedTransaction]) -> Vec<ProviderResult> {
+        let futures = self
+            .senders
+            .iter()
+            .zip(versions)
+            .map(|(sender, txn)| async move {
+                let start = Instant::now();
+                let response = match sender.encode(txn) {
+                    Ok(encoded_tx) => sender
+                        .send_transaction(&encoded_tx)
+                        .await
+                        .map_err(|err| err.to_string()),
+                    Err(err) => Err(err.to_string()),
+                };
+
+                ProviderResult {
+                    provider: sender.name(),
+                    region: sender.region(),
+                    signature: txn.signatures[0],
+                    response,
+                    latency: start.elapsed(),
+                }
+            });
+
+        join_all(futures).await
+    }
+
+    pub async fn send(
+        &self,
+        payer: &dyn Signer,
+        extra_signers: &[&dyn Signer],
+        tracker: &ConfirmationTracker,
+        tips: &Tips,
+        guard: &FanOutGuard,
+    ) -> ClientResult<FanOutReport> {
+        if self.senders.is_empty() {
+            return Err(ClientError::InvalidInput("no providers to fan out to"));
+        }
+
+        let versions = self.build_versions(payer, extra_signers, tips, guard)?;
+        let results = self.submit(&versions).await;
+
+        for result in &results {
+            match &result.response {
+                Ok(response) => println!("{:<20} {:?} {}", result.region, result.latency, response),
+                Err(err) => eprintln!("{:<20} {:?} failed: {}", result.region, result.latency, err),
+            }
+        }
+
+        let signatures: Vec<Signature> = results.iter().map(|r| r.signature).collect();
+        let landed = match tracker
+            .track_many(&signatures, guard.last_valid_block_height())
+            .await
+        {
+            Ok((_, TxOutcome::Expired)) => None,
+            Ok((signature, outcome)) => {
+                results
+                    .iter()
+                    .find(|r| r.signature == signature)
+                    .map(|r| LandedTx {
+                        provider: r.provider,
+                        signature,
+                        outcome,
+                    })
+            }
+            Err(err) => return Err(err),
+        };
+
+        Ok(FanOutReport { results, landed })
+    }
+}
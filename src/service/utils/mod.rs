@@ -1,6 +1,12 @@
+pub mod confirm;
+pub mod fanout;
 pub mod ping;
+pub mod sender;
 pub mod services;
 pub mod tip;
+pub use confirm::*;
+pub use fanout::*;
 pub use ping::*;
+pub use sender::*;
 pub use services::*;
 pub use tip::*;
\ No newline at end of file
@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use rand::Rng;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, system_instruction,
+    transaction::VersionedTransaction,
+};
+
+use crate::{
+    error::{ClientError, ClientResult},
+    service::{ConfirmationTracker, Tips, TxOutcome, parse_signature_response},
+    utils::TxEncoding,
+};
+
+/// Provider-agnostic view of a transaction relayer (Jito, Nozomi, ZeroSlot).
+#[async_trait]
+pub trait TxSender: Send + Sync {
+    /// Service name as used in `CONFIRM_SERVICE`.
+    fn name(&self) -> &'static str;
+
+    /// Name of the region the client is connected to.
+    fn region(&self) -> &'static str;
+
+    /// Minimum tip in SOL the relayer accepts.
+    fn min_tip(&self) -> f64;
+
+    fn tip_accounts(&self) -> &'static [&'static str];
+
+    /// Encoding `send_transaction` declares for the transaction.
+    fn encoding(&self) -> TxEncoding {
+        TxEncoding::Base64
+    }
+
+    fn encode(&self, txn: &VersionedTransaction) -> ClientResult<String> {
+        self.encoding().encode(txn)
+    }
+
+    async fn send_transaction(&self, encoded_tx: &str) -> anyhow::Result<serde_json::Value>;
+
+    /// Sends `encoded_tx` and waits until the returned signature lands, fails, expires or the
+    /// tracker times out.
+    async fn send_and_confirm(
+        &self,
+        encoded_tx: &str,
+        tracker: &ConfirmationTracker,
+        last_valid_block_height: Option<u64>,
+    ) -> ClientResult<TxOutcome> {
+        let response = self
+            .send_transaction(encoded_tx)
+            .await
+            .map_err(|err| ClientError::Send(self.name().to_string(), err.to_string()))?;
+        let signature = parse_signature_response(self.name(), &response)?;
+        println!("{} accepted {}", self.region(), signature);
+
+        tracker.track(&signature, last_valid_block_height).await
+    }
+
+    /// Lamports `add_tip_ix` transfers for a tip of `tip_sol_amount`.
+    fn tip_lamports(&self, tip_sol_amount: f64) -> u64 {
+        (tip_sol_amount.max(self.min_tip()) * LAMPORTS_PER_SOL as f64) as u64
+    }
+
+    fn random_tip_idx(&self) -> u8 {
+        rand::thread_rng().gen_range(0..self.tip_accounts().len()) as u8
+    }
+
+    /// Prepends compute budget instructions to `tip_config.pure_ix` and appends the relayer tip.
+    fn add_tip_ix(&self, tip_config: Tips) -> Vec<Instruction> {
+        let mut ixs: Vec<Instruction> = Vec::new();
+
+        if let Some(cu) = tip_config.cu {
+            ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(cu as u32));
+        };
+
+        if let Some(priority_fee_micro_lamport) = tip_config.priority_fee_micro_lamport {
+            ixs.push(ComputeBudgetInstruction::set_compute_unit_price(
+                priority_fee_micro_lamport,
+            ));
+        };
+
+        ixs.extend(tip_config.pure_ix);
+
+        let tip_accounts = self.tip_accounts();
+        let recipient = Pubkey::from_str_const(
+            tip_accounts[tip_config.tip_addr_idx as usize % tip_accounts.len()],
+        );
+        let transfer_ix = system_instruction::transfer(
+            &tip_config.payer,
+            &recipient,
+            self.tip_lamports(tip_config.tip_sol_amount),
+        );
+        ixs.push(transfer_ix);
+
+        ixs
+    }
+}
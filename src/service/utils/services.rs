@@ -22,4 +22,14 @@ pub struct JitoEndpoint {
     pub submit_endpoint: &'static str,
     pub ping_endpoint: &'static str,
     pub relayer_name: &'static str,
-}
\ No newline at end of file
+}
+
+impl JitoEndpoint {
+    /// `sendBundle` and bundle status endpoint of the same block engine.
+    pub fn bundle_endpoint(&self) -> String {
+        match self.submit_endpoint.strip_suffix("/transactions") {
+            Some(base) => format!("{}/bundles", base),
+            None => format!("https://{}/api/v1/bundles", self.ping_endpoint),
+        }
+    }
+}
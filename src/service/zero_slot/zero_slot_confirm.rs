@@ -1,14 +1,10 @@
+use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::json;
-use solana_sdk::{
-    compute_budget::ComputeBudgetInstruction, instruction::Instruction,
-    native_token::sol_to_lamports, pubkey::Pubkey, system_instruction,
-};
 use std::time::{Duration, Instant};
-use tokio::time::sleep;
 
 use crate::service::{
-    HEALTH_CHECK_SEC, PING_DURATION_SEC, Tips, ZSLOT_MIN_TIP, ZSLOT_REGIONS, ZSLOT_TIP,
+    HEALTH_CHECK_SEC, PING_DURATION_SEC, TxSender, ZSLOT_MIN_TIP, ZSLOT_REGIONS, ZSLOT_TIP,
     ZSlotEndpoint, ZSlotRegionsType, ping_all, ping_one,
 };
 
@@ -88,7 +84,7 @@ impl ZeroSlot {
         }
     }
 
-    pub fn health_check(&self, interval_sec: u64) {
+    pub fn health_check(&self, _interval_sec: u64) {
         // let client = self.client.clone();
         // let endpoint = self.endpoint.clone();
         // let relayer_name = endpoint.relayer_name.clone();
@@ -123,34 +119,6 @@ impl ZeroSlot {
         // });
     }
 
-    pub fn add_tip_ix(&self, tip_config: Tips) -> Vec<Instruction> {
-        let mut ixs: Vec<Instruction> = Vec::new();
-
-        if let Some(cu) = tip_config.cu {
-            ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(cu as u32));
-        };
-
-        if let Some(priority_fee_micro_lamport) = tip_config.priority_fee_micro_lamport {
-            ixs.push(ComputeBudgetInstruction::set_compute_unit_price(
-                priority_fee_micro_lamport,
-            ));
-        };
-
-        ixs.extend(tip_config.pure_ix.clone());
-
-        let relayer_fee = tip_config.tip_sol_amount.max(ZSLOT_MIN_TIP); // use `.max()` for clarity
-
-        let recipient = Pubkey::from_str_const(ZSLOT_TIP[tip_config.tip_addr_idx as usize]);
-        let transfer_ix = system_instruction::transfer(
-            &tip_config.payer,
-            &recipient,
-            sol_to_lamports(relayer_fee),
-        );
-        ixs.push(transfer_ix);
-
-        ixs
-    }
-
     pub async fn send_transaction(&self, encoded_tx: &str) -> anyhow::Result<serde_json::Value> {
         let start = Instant::now();
 
@@ -208,3 +176,26 @@ impl ZeroSlot {
         Ok(json)
     }
 }
+
+#[async_trait]
+impl TxSender for ZeroSlot {
+    fn name(&self) -> &'static str {
+        "ZERO_SLOT"
+    }
+
+    fn region(&self) -> &'static str {
+        self.endpoint.relayer_name
+    }
+
+    fn min_tip(&self) -> f64 {
+        ZSLOT_MIN_TIP
+    }
+
+    fn tip_accounts(&self) -> &'static [&'static str] {
+        &ZSLOT_TIP
+    }
+
+    async fn send_transaction(&self, encoded_tx: &str) -> anyhow::Result<serde_json::Value> {
+        ZeroSlot::send_transaction(self, encoded_tx).await
+    }
+}
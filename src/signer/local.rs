@@ -0,0 +1,76 @@
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::{Signer, SignerError},
+};
+use std::{fmt, sync::Arc};
+
+use crate::{error::ClientResult, keystore::UnlockedKeystore, signer::TxSigner};
+
+impl TxSigner for Keypair {
+    fn location(&self) -> String {
+        "local keypair".to_string()
+    }
+}
+
+/// Signs with a keystore entry, decrypting it for each signature so its keypair is only in
+/// memory while signing. The key derived from the passphrase stays in memory as long as the
+/// keystore is unlocked, so anyone able to read the process memory can decrypt every entry.
+pub struct KeystoreSigner {
+    keystore: Arc<UnlockedKeystore>,
+    label: String,
+    pubkey: Pubkey,
+}
+
+impl KeystoreSigner {
+    /// The entry labeled `label_or_pubkey` or with it as its pubkey, decrypted once up front so
+    /// a broken entry fails here rather than at the first trade.
+    pub fn new(keystore: Arc<UnlockedKeystore>, label_or_pubkey: &str) -> ClientResult<Self> {
+        let pubkey = keystore.find_keypair(label_or_pubkey)?.pubkey();
+        let label = keystore
+            .keystore
+            .find(label_or_pubkey)
+            .map(|entry| entry.label.clone())
+            .unwrap_or_default();
+
+        Ok(Self {
+            keystore,
+            label,
+            pubkey,
+        })
+    }
+}
+
+impl fmt::Debug for KeystoreSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeystoreSigner")
+            .field("label", &self.label)
+            .field("pubkey", &self.pubkey)
+            .finish()
+    }
+}
+
+impl Signer for KeystoreSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        let keypair = self
+            .keystore
+            .find_keypair(&self.pubkey.to_string())
+            .map_err(|err| SignerError::Custom(err.to_string()))?;
+
+        keypair.try_sign_message(message)
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+impl TxSigner for KeystoreSigner {
+    fn location(&self) -> String {
+        format!("keystore entry {}", self.label)
+    }
+}
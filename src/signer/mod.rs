@@ -0,0 +1,21 @@
+pub mod local;
+pub mod protocol;
+pub mod remote;
+pub mod server;
+
+pub use local::*;
+pub use protocol::*;
+pub use remote::*;
+pub use server::*;
+
+use solana_sdk::signer::Signer;
+use std::fmt;
+
+/// A key transactions are signed with, held in this process or by a separate signer.
+///
+/// Signing goes through the `Signer` supertrait, so a `&dyn TxSigner` works wherever
+/// `try_build_and_sign` takes a signer.
+pub trait TxSigner: Signer + Send + Sync + fmt::Debug {
+    /// Where the key is held, for logs.
+    fn location(&self) -> String;
+}
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, path::PathBuf, str::FromStr};
+
+use crate::error::{ClientError, ClientResult};
+
+/// Largest request body the signer server reads, a transaction message is far below it.
+pub const MAX_SIGNER_BODY: usize = 64 * 1024;
+
+/// Where a signer server listens, `http://HOST:PORT` or `unix:PATH`.
+///
+/// Plain HTTP only, keep it on localhost or behind a tunnel and prefer a Unix socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerEndpoint {
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl FromStr for SignerEndpoint {
+    type Err = ClientError;
+
+    fn from_str(input: &str) -> ClientResult<Self> {
+        let parse_err = |why: &str| ClientError::Parse("signer endpoint".to_string(), why.into());
+
+        if let Some(path) = input.strip_prefix("unix:") {
+            if path.is_empty() {
+                return Err(parse_err("unix: needs a socket path"));
+            }
+            return Ok(Self::Unix(PathBuf::from(path)));
+        }
+
+        let Some(addr) = input.strip_prefix("http://") else {
+            return Err(parse_err("expected http://HOST:PORT or unix:PATH"));
+        };
+        let addr = addr.trim_end_matches('/');
+        let has_port = addr
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+        if addr.contains('/') || !has_port {
+            return Err(parse_err("expected http://HOST:PORT without a path"));
+        }
+
+        Ok(Self::Tcp(addr.to_string()))
+    }
+}
+
+impl fmt::Display for SignerEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "http://{}", addr),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Body of `POST /sign`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignRequest {
+    pub pubkey: String,
+    /// Base64 of the serialized transaction message.
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignResponse {
+    /// Base58.
+    pub signature: String,
+}
+
+/// Body of the `GET /pubkeys` response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PubkeysResponse {
+    pub pubkeys: Vec<String>,
+}
+
+/// Body of every response that is not a 200.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+/// An HTTP/1.1 request with a JSON body. Every connection carries one request.
+pub fn http_request(method: &str, path: &str, token: Option<&str>, body: &[u8]) -> Vec<u8> {
+    let mut head = format!(
+        "{} {} HTTP/1.1\r\nHost: signer\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        method,
+        path,
+        body.len()
+    );
+    if let Some(token) = token {
+        head.push_str(&format!("Authorization: Bearer {}\r\n", token));
+    }
+    head.push_str("\r\n");
+
+    [head.as_bytes(), body].concat()
+}
+
+pub fn http_response(status: u16, body: &[u8]) -> Vec<u8> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        500 => "Internal Server Error",
+        _ => "Error",
+    };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason,
+        body.len()
+    );
+
+    [head.as_bytes(), body].concat()
+}
+
+/// Status code and body of a response read until the server closed the connection.
+pub fn parse_http_response(raw: &[u8]) -> ClientResult<(u16, &[u8])> {
+    let parse_err = |why: &str| ClientError::Parse("signer response".to_string(), why.into());
+
+    let split = raw
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| parse_err("no end of headers"))?;
+    let head = std::str::from_utf8(&raw[..split]).map_err(|_| parse_err("headers not UTF-8"))?;
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| parse_err("no status code"))?;
+
+    Ok((status, &raw[split + 4..]))
+}
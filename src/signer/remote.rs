@@ -0,0 +1,381 @@
+use serde::de::DeserializeOwned;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{Signer, SignerError},
+};
+use std::{fmt, future::Future, time::Duration};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use zeroize::Zeroizing;
+
+use crate::{
+    error::{ClientError, ClientResult},
+    signer::{ErrorResponse, PubkeysResponse, SignRequest, SignResponse, SignerEndpoint, TxSigner},
+};
+
+/// Timeout of a whole signer request.
+pub const REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Signs by asking a signer server (see `SignerServer`), so the key never enters this process.
+///
+/// The async `sign` is for async code. Signing through the `Signer` trait blocks the calling
+/// thread until the signer answers, which a signer on the same host does in well under a
+/// millisecond. On a multi-threaded runtime the worker is handed over while it waits.
+pub struct RemoteSigner {
+    endpoint: SignerEndpoint,
+    token: Option<Zeroizing<String>>,
+    pubkey: Pubkey,
+    client: reqwest::Client,
+}
+
+impl RemoteSigner {
+    /// Connects to `endpoint` and signs with `pubkey`, or with the only key the server holds.
+    pub fn connect(
+        endpoint: SignerEndpoint,
+        token: Option<String>,
+        pubkey: Option<Pubkey>,
+    ) -> ClientResult<Self> {
+        // The server closes every connection, and pooled ones would be tied to one runtime
+        let client = reqwest::Client::builder()
+            .timeout(REMOTE_SIGNER_TIMEOUT)
+            .pool_max_idle_per_host(0)
+            .build()
+            .map_err(|err| ClientError::Other(format!("signer HTTP client: {}", err)))?;
+        let mut signer = Self {
+            endpoint,
+            token: token.map(Zeroizing::new),
+            pubkey: Pubkey::default(),
+            client,
+        };
+
+        let pubkeys = block_on(signer.pubkeys())?;
+        signer.pubkey = match (pubkey, pubkeys.as_slice()) {
+            (Some(pubkey), _) if pubkeys.contains(&pubkey) => pubkey,
+            (Some(pubkey), _) => {
+                return Err(ClientError::InvalidData(format!(
+                    "signer {} does not hold {}",
+                    signer.endpoint, pubkey
+                )));
+            }
+            (None, [pubkey]) => *pubkey,
+            (None, _) => {
+                return Err(ClientError::InvalidData(format!(
+                    "signer {} holds {} keys, set REMOTE_SIGNER_PUBKEY to pick one",
+                    signer.endpoint,
+                    pubkeys.len()
+                )));
+            }
+        };
+
+        Ok(signer)
+    }
+
+    /// Every pubkey the server signs for.
+    pub async fn pubkeys(&self) -> ClientResult<Vec<Pubkey>> {
+        let response: PubkeysResponse = self.call("GET", "/pubkeys", Vec::new()).await?;
+
+        response
+            .pubkeys
+            .iter()
+            .map(|pubkey| Ok(pubkey.parse()?))
+            .collect()
+    }
+
+    /// Signs `message` remotely, checking the signature before trusting it.
+    pub async fn sign(&self, message: &[u8]) -> ClientResult<Signature> {
+        let request = SignRequest {
+            pubkey: self.pubkey.to_string(),
+            message: base64::encode(message),
+        };
+        let response: SignResponse = self
+            .call("POST", "/sign", serde_json::to_vec(&request)?)
+            .await?;
+
+        let signature: Signature = response.signature.parse().map_err(|err| {
+            ClientError::Parse("signer signature".to_string(), format!("{}", err))
+        })?;
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(ClientError::SignError(format!(
+                "signer {} returned an invalid signature",
+                self.endpoint
+            )));
+        }
+
+        Ok(signature)
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        path: &str,
+        body: Vec<u8>,
+    ) -> ClientResult<T> {
+        let (status, body) = self
+            .exchange(method, path, body)
+            .await
+            .map_err(|err| ClientError::SignError(format!("signer {}: {}", self.endpoint, err)))?;
+
+        if status != 200 {
+            let error = serde_json::from_slice::<ErrorResponse>(&body)
+                .map(|response| response.error)
+                .unwrap_or_else(|_| String::from_utf8_lossy(&body).into_owned());
+            return Err(ClientError::SignError(format!(
+                "signer {} answered {}: {}",
+                self.endpoint, status, error
+            )));
+        }
+
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    // Status and body of the response
+    async fn exchange(
+        &self,
+        method: &str,
+        path: &str,
+        body: Vec<u8>,
+    ) -> Result<(u16, Vec<u8>), String> {
+        let token = self.token.as_ref().map(|token| token.as_str());
+
+        match &self.endpoint {
+            SignerEndpoint::Tcp(addr) => {
+                let method = reqwest::Method::from_bytes(method.as_bytes())
+                    .map_err(|err| err.to_string())?;
+                let mut request = self
+                    .client
+                    .request(method, format!("http://{}{}", addr, path))
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body);
+                if let Some(token) = token {
+                    request = request.bearer_auth(token);
+                }
+
+                let response = request.send().await.map_err(|err| err.to_string())?;
+                let status = response.status().as_u16();
+                let body = response.bytes().await.map_err(|err| err.to_string())?;
+                Ok((status, body.to_vec()))
+            }
+            #[cfg(unix)]
+            SignerEndpoint::Unix(path_on_disk) => {
+                use crate::signer::{http_request, parse_http_response};
+                use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+                let request = http_request(method, path, token, &body);
+                let raw = tokio::time::timeout(REMOTE_SIGNER_TIMEOUT, async {
+                    let mut stream = tokio::net::UnixStream::connect(path_on_disk).await?;
+                    stream.write_all(&request).await?;
+                    // The server closes the connection after its response
+                    let mut raw = Vec::new();
+                    stream.read_to_end(&mut raw).await?;
+                    Ok::<_, std::io::Error>(raw)
+                })
+                .await
+                .map_err(|_| "request timed out".to_string())?
+                .map_err(|err| err.to_string())?;
+
+                let (status, body) = parse_http_response(&raw).map_err(|err| err.to_string())?;
+                Ok((status, body.to_vec()))
+            }
+            #[cfg(not(unix))]
+            SignerEndpoint::Unix(_) => Err("unix sockets need a unix system".to_string()),
+        }
+    }
+}
+
+// Runs `future` to completion from sync code, inside or outside a runtime
+fn block_on<F: Future + Send>(future: F) -> F::Output
+where
+    F::Output: Send,
+{
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| handle.block_on(future))
+        }
+        // A current thread runtime cannot be blocked on from within, drive it on another thread
+        _ => std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .expect("Failed to build the signer runtime")
+                        .block_on(future)
+                })
+                .join()
+                .expect("signer thread panicked")
+        }),
+    }
+}
+
+impl fmt::Debug for RemoteSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteSigner")
+            .field("endpoint", &self.endpoint)
+            .field("pubkey", &self.pubkey)
+            .finish()
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        block_on(self.sign(message)).map_err(|err| SignerError::Custom(err.to_string()))
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+impl TxSigner for RemoteSigner {
+    fn location(&self) -> String {
+        self.endpoint.to_string()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use solana_sdk::{
+        hash::Hash,
+        message::{VersionedMessage, v0::Message},
+        signature::Keypair,
+        system_instruction,
+    };
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::signer::SignerServer;
+
+    const TOKEN: &str = "test-token";
+
+    // A signer server for a fresh key on its own socket
+    async fn serve(name: &str) -> (Keypair, SignerEndpoint) {
+        let keypair = Keypair::new();
+        let path = std::env::temp_dir().join(format!(
+            "pumpfun-signer-{}-{}.sock",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_file(&path);
+        let endpoint = SignerEndpoint::Unix(path.clone());
+
+        let server = SignerServer::new(
+            vec![Arc::new(keypair.insecure_clone())],
+            Some(TOKEN.to_string()),
+        )
+        .unwrap();
+        let serving = endpoint.clone();
+        tokio::spawn(async move { server.serve(&serving).await });
+        while !path.exists() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        (keypair, endpoint)
+    }
+
+    fn transfer_message(payer: &Pubkey) -> Vec<u8> {
+        let ix = system_instruction::transfer(payer, &Pubkey::new_unique(), 1);
+        let message = Message::try_compile(payer, &[ix], &[], Hash::new_unique()).unwrap();
+
+        VersionedMessage::V0(message).serialize()
+    }
+
+    fn assert_rejected<T: fmt::Debug>(result: ClientResult<T>, status: &str) {
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains(&format!("answered {}", status)), "{}", err);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn signs_transaction_message() {
+        let (keypair, endpoint) = serve("sign").await;
+        let signer = RemoteSigner::connect(endpoint, Some(TOKEN.to_string()), None).unwrap();
+        assert_eq!(signer.pubkey(), keypair.pubkey());
+
+        let message = transfer_message(&keypair.pubkey());
+        let signature = signer.sign(&message).await.unwrap();
+        assert!(signature.verify(keypair.pubkey().as_ref(), &message));
+        assert_eq!(signature, keypair.sign_message(&message));
+        // Through the blocking `Signer` trait as transactions are built
+        assert_eq!(signer.try_sign_message(&message).unwrap(), signature);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn signs_over_tcp() {
+        let keypair = Keypair::new();
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let endpoint = SignerEndpoint::Tcp(addr.to_string());
+        let server = SignerServer::new(
+            vec![Arc::new(keypair.insecure_clone())],
+            Some(TOKEN.to_string()),
+        )
+        .unwrap();
+        let serving = endpoint.clone();
+        tokio::spawn(async move { server.serve(&serving).await });
+        while tokio::net::TcpStream::connect(addr).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        assert_rejected(
+            RemoteSigner::connect(endpoint.clone(), Some("wrong".to_string()), None),
+            "401",
+        );
+        let signer = RemoteSigner::connect(endpoint, Some(TOKEN.to_string()), None).unwrap();
+        let message = transfer_message(&keypair.pubkey());
+        assert_eq!(
+            signer.sign(&message).await.unwrap(),
+            keypair.sign_message(&message)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn wrong_token_is_rejected() {
+        let (_, endpoint) = serve("token").await;
+
+        assert_rejected(
+            RemoteSigner::connect(endpoint.clone(), Some("wrong".to_string()), None),
+            "401",
+        );
+        assert_rejected(RemoteSigner::connect(endpoint, None, None), "401");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_not_needing_the_key_is_rejected() {
+        let (_, endpoint) = serve("foreign").await;
+        let signer = RemoteSigner::connect(endpoint, Some(TOKEN.to_string()), None).unwrap();
+
+        let message = transfer_message(&Pubkey::new_unique());
+        assert_rejected(signer.sign(&message).await, "403");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn non_message_bytes_are_rejected() {
+        let (keypair, endpoint) = serve("bytes").await;
+        let signer = RemoteSigner::connect(endpoint, Some(TOKEN.to_string()), None).unwrap();
+
+        assert_rejected(signer.sign(b"not a transaction").await, "400");
+        // A message with trailing bytes is not exactly a message either
+        let mut message = transfer_message(&keypair.pubkey());
+        message.push(0);
+        assert_rejected(signer.sign(&message).await, "400");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn unknown_pubkey_is_refused_at_connect() {
+        let (_, endpoint) = serve("pubkey").await;
+
+        assert!(
+            RemoteSigner::connect(
+                endpoint,
+                Some(TOKEN.to_string()),
+                Some(Pubkey::new_unique())
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use serde::Serialize;
+use solana_sdk::{message::VersionedMessage, pubkey::Pubkey};
+use std::{collections::HashMap, sync::Arc};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpListener,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    error::{ClientError, ClientResult},
+    signer::{
+        ErrorResponse, MAX_SIGNER_BODY, PubkeysResponse, REMOTE_SIGNER_TIMEOUT, SignRequest,
+        SignResponse, SignerEndpoint, TxSigner, http_response,
+    },
+};
+
+/// Request line and headers a request may have on top of its body.
+const MAX_SIGNER_HEAD: usize = 8 * 1024;
+
+/// Serves `RemoteSigner` requests with a set of keys, as a separate process holding them.
+///
+/// Only transaction messages that need the requested key's signature are signed. Without a
+/// token anyone who reaches the endpoint can sign, so TCP endpoints should have one.
+pub struct SignerServer {
+    signers: HashMap<Pubkey, Arc<dyn TxSigner>>,
+    token: Option<Zeroizing<String>>,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    token: Option<String>,
+    body: Vec<u8>,
+}
+
+type Rejection = (u16, String);
+
+impl SignerServer {
+    pub fn new(signers: Vec<Arc<dyn TxSigner>>, token: Option<String>) -> ClientResult<Self> {
+        if signers.is_empty() {
+            return Err(ClientError::InvalidInput("the signer has no keys"));
+        }
+
+        Ok(Self {
+            signers: signers
+                .into_iter()
+                .map(|signer| (signer.pubkey(), signer))
+                .collect(),
+            token: token.map(Zeroizing::new),
+        })
+    }
+
+    /// Every pubkey the server signs for, sorted.
+    pub fn pubkeys(&self) -> Vec<Pubkey> {
+        let mut pubkeys: Vec<Pubkey> = self.signers.keys().copied().collect();
+        pubkeys.sort();
+        pubkeys
+    }
+
+    /// Answers requests on `endpoint` until accepting a connection fails.
+    pub async fn serve(self, endpoint: &SignerEndpoint) -> ClientResult<()> {
+        let io_err = |err: std::io::Error| ClientError::Other(format!("{}: {}", endpoint, err));
+        let server = Arc::new(self);
+
+        match endpoint {
+            SignerEndpoint::Tcp(addr) => {
+                let listener = TcpListener::bind(addr).await.map_err(io_err)?;
+                loop {
+                    let (stream, _) = listener.accept().await.map_err(io_err)?;
+                    tokio::spawn(server.clone().handle(stream));
+                }
+            }
+            #[cfg(unix)]
+            SignerEndpoint::Unix(path) => {
+                use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+                // A socket left behind by a previous run fails the bind, any other file stays
+                if std::fs::metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+                    std::fs::remove_file(path).map_err(io_err)?;
+                }
+                let listener = tokio::net::UnixListener::bind(path).map_err(io_err)?;
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+                    .map_err(io_err)?;
+                loop {
+                    let (stream, _) = listener.accept().await.map_err(io_err)?;
+                    tokio::spawn(server.clone().handle(stream));
+                }
+            }
+            #[cfg(not(unix))]
+            SignerEndpoint::Unix(_) => {
+                Err(ClientError::InvalidInput("unix sockets need a unix system"))
+            }
+        }
+    }
+
+    async fn handle<S: AsyncRead + AsyncWrite + Unpin>(self: Arc<Self>, stream: S) {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut reader = BufReader::new(reader.take((MAX_SIGNER_HEAD + MAX_SIGNER_BODY) as u64));
+
+        let request = tokio::time::timeout(REMOTE_SIGNER_TIMEOUT, read_request(&mut reader))
+            .await
+            .unwrap_or_else(|_| Err((400, "request timed out".to_string())));
+        let (status, body) = match request.and_then(|request| self.respond(request)) {
+            Ok(body) => (200, body),
+            Err((status, error)) => (status, json_body(&ErrorResponse { error })),
+        };
+
+        let _ = writer.write_all(&http_response(status, &body)).await;
+        let _ = writer.shutdown().await;
+    }
+
+    fn respond(&self, request: Request) -> Result<Vec<u8>, Rejection> {
+        if let Some(token) = &self.token
+            && !request
+                .token
+                .is_some_and(|given| token_matches(&given, token))
+        {
+            return Err((401, "missing or wrong token".to_string()));
+        }
+
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/pubkeys") => Ok(json_body(&PubkeysResponse {
+                pubkeys: self.pubkeys().iter().map(Pubkey::to_string).collect(),
+            })),
+            ("POST", "/sign") => Ok(json_body(&self.sign(&request.body)?)),
+            _ => Err((404, format!("no {} {}", request.method, request.path))),
+        }
+    }
+
+    fn sign(&self, body: &[u8]) -> Result<SignResponse, Rejection> {
+        let bad_request = |why: String| (400, why);
+
+        let request: SignRequest =
+            serde_json::from_slice(body).map_err(|err| bad_request(err.to_string()))?;
+        let pubkey: Pubkey = request
+            .pubkey
+            .parse()
+            .map_err(|_| bad_request(format!("invalid pubkey {}", request.pubkey)))?;
+        let signer = self
+            .signers
+            .get(&pubkey)
+            .ok_or_else(|| (403, format!("no key for {}", pubkey)))?;
+        let message = base64::decode(&request.message)
+            .map_err(|err| bad_request(format!("message is not base64: {}", err)))?;
+
+        // Only exactly a transaction message that needs this key, never arbitrary bytes
+        let parsed: VersionedMessage = bincode::deserialize(&message)
+            .ok()
+            .filter(|parsed: &VersionedMessage| parsed.serialize() == message)
+            .ok_or_else(|| bad_request("not a transaction message".to_string()))?;
+        let required = parsed.header().num_required_signatures as usize;
+        if !parsed
+            .static_account_keys()
+            .iter()
+            .take(required)
+            .any(|key| *key == pubkey)
+        {
+            return Err((403, format!("the message needs no signature of {}", pubkey)));
+        }
+
+        let signature = signer
+            .try_sign_message(&message)
+            .map_err(|err| (500, err.to_string()))?;
+        println!("✍️ Signed for {}: {}", pubkey, signature);
+
+        Ok(SignResponse {
+            signature: signature.to_string(),
+        })
+    }
+}
+
+async fn read_request(
+    reader: &mut (impl AsyncBufReadExt + AsyncRead + Unpin),
+) -> Result<Request, Rejection> {
+    let bad_request = |why: &str| (400, why.to_string());
+    let mut line = String::new();
+
+    reader
+        .read_line(&mut line)
+        .await
+        .map_err(|_| bad_request("unreadable request line"))?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(bad_request("malformed request line"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut content_length = 0;
+    let mut token = None;
+    loop {
+        line.clear();
+        let read = reader
+            .read_line(&mut line)
+            .await
+            .map_err(|_| bad_request("unreadable header"))?;
+        let header = line.trim_end();
+        if read == 0 || header.is_empty() {
+            break;
+        }
+
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(bad_request("malformed header"));
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| bad_request("invalid Content-Length"))?
+            }
+            "authorization" => token = value.trim().strip_prefix("Bearer ").map(str::to_string),
+            _ => {}
+        }
+    }
+    if content_length > MAX_SIGNER_BODY {
+        return Err((413, format!("body is over {} bytes", MAX_SIGNER_BODY)));
+    }
+
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .await
+        .map_err(|_| bad_request("body shorter than Content-Length"))?;
+
+    Ok(Request {
+        method,
+        path,
+        token,
+        body,
+    })
+}
+
+fn json_body(value: &impl Serialize) -> Vec<u8> {
+    serde_json::to_vec(value).unwrap_or_default()
+}
+
+// Compares in constant time so the token cannot be guessed byte by byte
+fn token_matches(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use carbon_pumpfun_decoder::{accounts::global::Global, instructions::buy::Buy};
+use serde::Deserialize;
+use solana_sdk::{instruction::Instruction, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use std::{fs, path::Path};
+
+use crate::{
+    config::{TradingProfile, active_profile, bot_config, trading_profile},
+    error::{ClientError, ClientResult},
+    instructions::{
+        buy_ix::BuyExactInInstructionAccountsExt, pumpfun_accounts::buy_instruction_accounts,
+    },
+    monitor::NewToken,
+    sniper::{SnipeFilter, SnipeFilterConfig},
+};
+
+/// Sniper settings as loaded from `SNIPER_CONFIG_FILE`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SniperConfig {
+    /// SOL spent per snipe, fees included.
+    pub buy_sol: f64,
+    /// Tolerated price move between the create and our buy in percent, defaults to the slippage
+    /// of the profile.
+    #[serde(default)]
+    pub slippage_pct: Option<f64>,
+    /// Trading profile of the snipes, unset follows the active profile.
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Stop after this many buys, unset keeps sniping.
+    #[serde(default)]
+    pub max_buys: Option<usize>,
+    #[serde(default)]
+    pub filters: SnipeFilterConfig,
+}
+
+impl SniperConfig {
+    pub fn from_env() -> ClientResult<Self> {
+        let path = bot_config()?
+            .sniper_config_file
+            .as_deref()
+            .ok_or(ClientError::InvalidInput("SNIPER_CONFIG_FILE is not set"))?;
+
+        Self::load(path)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> ClientResult<Self> {
+        let path = path.as_ref();
+        let input = fs::read_to_string(path)
+            .map_err(|err| ClientError::Other(format!("{}: {}", path.display(), err)))?;
+
+        Self::from_json(&input)
+    }
+
+    pub fn from_json(input: &str) -> ClientResult<Self> {
+        let config: Self = serde_json::from_str(input)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Checks the buy settings and compiles the filters, reporting all problems at once.
+    pub fn validate(&self) -> ClientResult<()> {
+        let mut errors = Vec::new();
+        if !self.buy_sol.is_finite() || self.buy_sol <= 0.0 {
+            errors.push(format!("buy_sol must be positive, got {}", self.buy_sol));
+        }
+        if let Some(slippage) = self.slippage_pct
+            && !(0.0..100.0).contains(&slippage)
+        {
+            errors.push(format!(
+                "slippage_pct must be in [0, 100), got {}",
+                slippage
+            ));
+        }
+        if self.max_buys == Some(0) {
+            errors.push("max_buys must be at least 1".to_string());
+        }
+        match SnipeFilter::new(&self.filters) {
+            Err(ClientError::InvalidData(msg)) => errors.push(msg),
+            Err(err) => errors.push(err.to_string()),
+            Ok(_) => {}
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ClientError::InvalidData(format!(
+                "invalid sniper config: {}",
+                errors.join("; ")
+            )))
+        }
+    }
+
+    pub fn buy_lamports(&self) -> u64 {
+        (self.buy_sol * LAMPORTS_PER_SOL as f64) as u64
+    }
+
+    /// The pinned profile, or the active one at the time of the call.
+    pub fn profile(&self) -> ClientResult<TradingProfile> {
+        match &self.profile {
+            Some(name) => trading_profile(name),
+            None => active_profile(),
+        }
+    }
+
+    pub fn slippage_pct(&self) -> ClientResult<f64> {
+        match self.slippage_pct {
+            Some(slippage) => Ok(slippage),
+            None => Ok(self.profile()?.slippage_pct()),
+        }
+    }
+}
+
+/// Buys new tokens that pass the filters.
+#[derive(Debug, Clone)]
+pub struct Sniper {
+    pub config: SniperConfig,
+    pub filter: SnipeFilter,
+    /// On-chain config, for the curve of creates without a dev buy.
+    pub global: Global,
+    pub buys: usize,
+}
+
+impl Sniper {
+    pub fn new(config: SniperConfig, global: Global) -> ClientResult<Self> {
+        config.validate()?;
+        // An unknown profile name fails at startup instead of on the first snipe
+        config.profile()?;
+
+        Ok(Self {
+            filter: SnipeFilter::new(&config.filters)?,
+            config,
+            global,
+            buys: 0,
+        })
+    }
+
+    /// Whether `max_buys` is reached.
+    pub fn is_done(&self) -> bool {
+        self.config.max_buys.is_some_and(|max| self.buys >= max)
+    }
+
+    /// Token amount and max SOL cost of our buy, quoted on the curve the create left. The token
+    /// amount is cut by the slippage so the buy still fits if others buy first.
+    pub fn quote(&self, token: &NewToken) -> ClientResult<(u64, u64)> {
+        let max_sol_cost = self.config.buy_lamports();
+        let quoted = token.curve(&self.global).buy_exact_sol_in(max_sol_cost)?;
+        let slippage_bps = (self.config.slippage_pct()? * 100.0) as u128;
+        let token_amount = (quoted as u128 * (10_000 - slippage_bps) / 10_000) as u64;
+        if token_amount == 0 {
+            return Err(ClientError::BondingCurveError("buy gets no tokens"));
+        }
+
+        Ok((token_amount, max_sol_cost))
+    }
+
+    /// Idempotent ATA + buy instructions of `buyer` for `token`.
+    pub fn buy_instructions(&self, token: &NewToken, buyer: Pubkey) -> Result<Vec<Instruction>> {
+        let (amount, max_sol_cost) = self.quote(token)?;
+
+        let accounts = buy_instruction_accounts(&token.mint, &token.creator, &buyer);
+
+        Ok(vec![
+            accounts.get_create_idempotent_ata_ix(),
+            accounts.get_buy_ix(Buy {
+                amount,
+                max_sol_cost,
+            }),
+        ])
+    }
+
+    pub fn record_buy(&mut self) {
+        self.buys += 1;
+    }
+}
@@ -0,0 +1,184 @@
+use regex::Regex;
+use serde::Deserialize;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use std::collections::HashSet;
+
+use crate::{
+    error::{ClientError, ClientResult},
+    monitor::NewToken,
+};
+
+/// Which new tokens get sniped, as configured in the `filters` of the sniper config.
+///
+/// Every set filter has to pass, unset ones let everything through.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnipeFilterConfig {
+    /// Only tokens of these creators, as base58 pubkeys.
+    #[serde(default)]
+    pub creator_allow: Vec<String>,
+    /// Never tokens of these creators.
+    #[serde(default)]
+    pub creator_deny: Vec<String>,
+    #[serde(default)]
+    pub name_regex: Option<String>,
+    #[serde(default)]
+    pub symbol_regex: Option<String>,
+    /// Hosts the metadata URI may point to, subdomains included, e.g. `ipfs.io`.
+    #[serde(default)]
+    pub uri_domains: Vec<String>,
+    #[serde(default)]
+    pub min_dev_buy_sol: Option<f64>,
+    #[serde(default)]
+    pub max_dev_buy_sol: Option<f64>,
+}
+
+/// Compiled [`SnipeFilterConfig`].
+#[derive(Debug, Clone)]
+pub struct SnipeFilter {
+    pub creator_allow: HashSet<Pubkey>,
+    pub creator_deny: HashSet<Pubkey>,
+    pub name: Option<Regex>,
+    pub symbol: Option<Regex>,
+    pub uri_domains: Vec<String>,
+    pub min_dev_buy: Option<u64>,
+    pub max_dev_buy: Option<u64>,
+}
+
+impl SnipeFilter {
+    /// Compiles `config`, reporting all invalid entries at once.
+    pub fn new(config: &SnipeFilterConfig) -> ClientResult<Self> {
+        let mut errors = Vec::new();
+
+        let mut pubkeys = |name: &str, wallets: &[String]| {
+            wallets
+                .iter()
+                .filter_map(|wallet| match wallet.parse::<Pubkey>() {
+                    Ok(pubkey) => Some(pubkey),
+                    Err(_) => {
+                        errors.push(format!("{}: invalid pubkey {}", name, wallet));
+                        None
+                    }
+                })
+                .collect::<HashSet<_>>()
+        };
+        let creator_allow = pubkeys("creator_allow", &config.creator_allow);
+        let creator_deny = pubkeys("creator_deny", &config.creator_deny);
+
+        let mut regex = |name: &str, pattern: &Option<String>| {
+            pattern
+                .as_deref()
+                .and_then(|pattern| match Regex::new(pattern) {
+                    Ok(regex) => Some(regex),
+                    Err(err) => {
+                        errors.push(format!("{}: {}", name, err));
+                        None
+                    }
+                })
+        };
+        let name = regex("name_regex", &config.name_regex);
+        let symbol = regex("symbol_regex", &config.symbol_regex);
+
+        let mut lamports = |name: &str, sol: Option<f64>| {
+            sol.and_then(|sol| {
+                if sol.is_finite() && sol >= 0.0 {
+                    Some((sol * LAMPORTS_PER_SOL as f64) as u64)
+                } else {
+                    errors.push(format!(
+                        "{} must be a non-negative number, got {}",
+                        name, sol
+                    ));
+                    None
+                }
+            })
+        };
+        let min_dev_buy = lamports("min_dev_buy_sol", config.min_dev_buy_sol);
+        let max_dev_buy = lamports("max_dev_buy_sol", config.max_dev_buy_sol);
+        if let (Some(min), Some(max)) = (min_dev_buy, max_dev_buy)
+            && min > max
+        {
+            errors.push("min_dev_buy_sol is above max_dev_buy_sol".to_string());
+        }
+
+        if !errors.is_empty() {
+            return Err(ClientError::InvalidData(format!(
+                "invalid snipe filters: {}",
+                errors.join("; ")
+            )));
+        }
+
+        Ok(Self {
+            creator_allow,
+            creator_deny,
+            name,
+            symbol,
+            uri_domains: config
+                .uri_domains
+                .iter()
+                .map(|domain| domain.trim().trim_start_matches('.').to_lowercase())
+                .collect(),
+            min_dev_buy,
+            max_dev_buy,
+        })
+    }
+
+    /// `Err` says why `token` is skipped.
+    pub fn check(&self, token: &NewToken) -> Result<(), String> {
+        let creators = [token.creator, token.user];
+
+        if let Some(creator) = creators
+            .iter()
+            .find(|creator| self.creator_deny.contains(creator))
+        {
+            return Err(format!("creator {} is denied", creator));
+        }
+        if !self.creator_allow.is_empty()
+            && !creators
+                .iter()
+                .any(|creator| self.creator_allow.contains(creator))
+        {
+            return Err(format!("creator {} is not allowed", token.creator));
+        }
+
+        if let Some(name) = &self.name
+            && !name.is_match(&token.name)
+        {
+            return Err(format!("name {:?} does not match {}", token.name, name));
+        }
+        if let Some(symbol) = &self.symbol
+            && !symbol.is_match(&token.symbol)
+        {
+            return Err(format!(
+                "symbol {:?} does not match {}",
+                token.symbol, symbol
+            ));
+        }
+
+        if !self.uri_domains.is_empty() {
+            let host = uri_host(&token.uri).ok_or_else(|| format!("invalid URI {}", token.uri))?;
+            let allowed = self.uri_domains.iter().any(|domain| {
+                host == *domain
+                    || host
+                        .strip_suffix(domain.as_str())
+                        .is_some_and(|sub| sub.ends_with('.'))
+            });
+            if !allowed {
+                return Err(format!("URI host {} is not allowed", host));
+            }
+        }
+
+        let dev_buy = token.dev_buy_sol();
+        if self.min_dev_buy.is_some_and(|min| dev_buy < min) {
+            return Err(format!("dev buy of {} lamports is too small", dev_buy));
+        }
+        if self.max_dev_buy.is_some_and(|max| dev_buy > max) {
+            return Err(format!("dev buy of {} lamports is too large", dev_buy));
+        }
+
+        Ok(())
+    }
+}
+
+fn uri_host(uri: &str) -> Option<String> {
+    url::Url::parse(uri).ok()?.host_str().map(str::to_lowercase)
+}
@@ -0,0 +1,5 @@
+pub mod engine;
+pub mod filter;
+
+pub use engine::*;
+pub use filter::*;
@@ -0,0 +1,130 @@
+use solana_sdk::pubkey::Pubkey;
+use std::{collections::HashSet, time::Instant};
+
+use crate::{
+    error::ClientResult,
+    farm::WalletFarm,
+    monitor::TradeUpdate,
+    strategy::{SellRule, StrategyConfig},
+    utils::BondingCurveQuote,
+};
+
+/// Tokens we hold of one mint, plus what the strategy has seen since entry.
+#[derive(Debug, Clone)]
+pub struct Position {
+    pub mint: Pubkey,
+    /// Creator of the mint as seen in its trades, `None` until the first one.
+    pub creator: Option<Pubkey>,
+    pub token_amount: u64,
+    /// Lamports paid for `token_amount`, fees included.
+    pub cost_basis: u64,
+    pub opened_at: Instant,
+    /// Lamports of buys from wallets outside the farm since entry.
+    pub external_buy_volume: u64,
+    /// Curve after the last seen trade, `None` until the first one.
+    pub curve: Option<BondingCurveQuote>,
+}
+
+impl Position {
+    pub fn new(mint: Pubkey, token_amount: u64, cost_basis: u64) -> Self {
+        Self {
+            mint,
+            creator: None,
+            token_amount,
+            cost_basis,
+            opened_at: Instant::now(),
+            external_buy_volume: 0,
+            curve: None,
+        }
+    }
+
+    /// Takes `token_amount` sold tokens off the position, with their share of the cost basis.
+    pub fn reduce(&mut self, token_amount: u64) {
+        let token_amount = token_amount.min(self.token_amount);
+        if token_amount == 0 {
+            return;
+        }
+
+        let remaining = self.token_amount - token_amount;
+        self.cost_basis =
+            (self.cost_basis as u128 * remaining as u128 / self.token_amount as u128) as u64;
+        self.token_amount = remaining;
+    }
+
+    /// Lamports the whole position sells for on the last seen curve.
+    pub fn value(&self) -> Option<u64> {
+        self.curve?.sell_exact_tokens_in(self.token_amount).ok()
+    }
+}
+
+/// Why the strategy wants to sell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SellSignal {
+    pub mint: Pubkey,
+    pub reason: String,
+}
+
+/// Evaluates the configured [`SellRule`] against trades of open positions.
+#[derive(Debug, Clone)]
+pub struct Strategy {
+    pub rule: SellRule,
+    /// Wallets whose trades are not external activity.
+    pub excluded: HashSet<Pubkey>,
+}
+
+impl Strategy {
+    pub fn new(config: &StrategyConfig, farm: &WalletFarm) -> ClientResult<Self> {
+        config.validate()?;
+
+        let mut excluded = config
+            .exclude_wallets
+            .iter()
+            .map(|wallet| wallet.parse())
+            .collect::<Result<HashSet<Pubkey>, _>>()?;
+        if config.exclude_farm_wallets {
+            excluded.extend(farm.pubkeys());
+        }
+
+        Ok(Self {
+            rule: config.rule.clone(),
+            excluded,
+        })
+    }
+
+    pub fn is_external(&self, update: &TradeUpdate) -> bool {
+        !self.excluded.contains(&update.event.user)
+    }
+
+    /// Updates `position` with `update` and evaluates the rule.
+    pub fn on_trade(&self, position: &mut Position, update: &TradeUpdate) -> Option<SellSignal> {
+        if update.event.mint != position.mint {
+            return None;
+        }
+
+        position.curve = Some(BondingCurveQuote::from_trade_event(&update.event));
+        position.creator = Some(update.event.creator);
+
+        let external_buy = update.is_buy() && self.is_external(update);
+        if external_buy {
+            position.external_buy_volume = position
+                .external_buy_volume
+                .saturating_add(update.event.sol_amount);
+        }
+
+        self.signal(position, external_buy.then_some(update))
+    }
+
+    /// Evaluates the rule without a new trade, for time based rules.
+    pub fn on_tick(&self, position: &Position) -> Option<SellSignal> {
+        self.signal(position, None)
+    }
+
+    fn signal(&self, position: &Position, trade: Option<&TradeUpdate>) -> Option<SellSignal> {
+        self.rule
+            .evaluate(position, trade)
+            .map(|reason| SellSignal {
+                mint: position.mint,
+                reason,
+            })
+    }
+}
@@ -0,0 +1,391 @@
+use anyhow::Result;
+use serde::Deserialize;
+use solana_sdk::{instruction::Instruction, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+
+use crate::{
+    error::{ClientError, ClientResult},
+    instructions::{pumpfun_buy::TOKEN_TOTAL_SUPPLY, pumpfun_sell::PumpFunSell},
+    utils::BondingCurveQuote,
+};
+
+const TOKEN_DECIMALS: u32 = 6;
+
+/// Curve level at which a tranche is sold.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LadderTarget {
+    /// Market cap of the curve in SOL.
+    MarketCapSol(f64),
+    /// Price of one whole token in SOL.
+    PriceSol(f64),
+}
+
+impl LadderTarget {
+    /// Market cap in lamports the target corresponds to.
+    pub fn market_cap(&self) -> u64 {
+        let sol = match self {
+            Self::MarketCapSol(sol) => *sol,
+            Self::PriceSol(price) => {
+                *price * (TOKEN_TOTAL_SUPPLY / 10u64.pow(TOKEN_DECIMALS)) as f64
+            }
+        };
+
+        (sol * LAMPORTS_PER_SOL as f64) as u64
+    }
+}
+
+/// One rung of the ladder in config, e.g. `{"target": {"market_cap_sol": 60.0}, "percent": 25.0}`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LadderStep {
+    pub target: LadderTarget,
+    /// Share of the initial position sold at the target.
+    pub percent: f64,
+}
+
+impl LadderStep {
+    pub(crate) fn validate(steps: &[LadderStep], errors: &mut Vec<String>) {
+        for step in steps {
+            let (name, value) = match step.target {
+                LadderTarget::MarketCapSol(sol) => ("market_cap_sol", sol),
+                LadderTarget::PriceSol(price) => ("price_sol", price),
+            };
+            if !value.is_finite() || value <= 0.0 {
+                errors.push(format!("ladder: {} must be positive, got {}", name, value));
+            }
+            if !(step.percent > 0.0 && step.percent <= 100.0) {
+                errors.push(format!(
+                    "ladder: percent must be in (0, 100], got {}",
+                    step.percent
+                ));
+            }
+        }
+
+        let total: f64 = steps.iter().map(|step| step.percent).sum();
+        if total > 100.0 + f64::EPSILON {
+            errors.push(format!(
+                "ladder: percents add up to {}, more than 100",
+                total
+            ));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tranche {
+    /// Market cap in lamports that triggers the tranche.
+    pub market_cap: u64,
+    pub token_amount: u64,
+    pub sold: bool,
+}
+
+/// Tranches that became due on one curve update, sold together in one transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LadderSell {
+    pub mint: Pubkey,
+    pub token_amount: u64,
+    /// Indices into `SellLadder::tranches`, marked sold with `SellLadder::mark_sold`.
+    pub tranches: Vec<usize>,
+    pub market_cap: u64,
+    /// Set on the final tranche of a ladder that covers the whole position.
+    pub close_ata: bool,
+}
+
+impl LadderSell {
+    /// Sell instructions of `user` for a token of `creator`, quoted against `curve`.
+    pub fn instructions(
+        &self,
+        user: Pubkey,
+        creator: Pubkey,
+        curve: &BondingCurveQuote,
+        slippage_percentage: f64,
+    ) -> Result<Vec<Instruction>> {
+        PumpFunSell::get_sell_instructions_with_quote(
+            self.mint,
+            user,
+            creator,
+            self.token_amount,
+            curve,
+            slippage_percentage,
+            self.close_ata,
+        )
+    }
+}
+
+/// Splits a position into tranches that are sold as the curve reaches their targets.
+///
+/// The ATA stays open until the last tranche. If the percents add up to less than 100, the rest
+/// is left to the sell rule, which sells everything that remains and closes the ATA.
+#[derive(Debug, Clone)]
+pub struct SellLadder {
+    pub mint: Pubkey,
+    /// Tranches by ascending market cap.
+    pub tranches: Vec<Tranche>,
+    covers_position: bool,
+}
+
+impl SellLadder {
+    pub fn new(mint: Pubkey, token_amount: u64, steps: &[LadderStep]) -> ClientResult<Self> {
+        let mut errors = Vec::new();
+        LadderStep::validate(steps, &mut errors);
+        if !errors.is_empty() {
+            return Err(ClientError::InvalidData(format!(
+                "invalid sell ladder: {}",
+                errors.join("; ")
+            )));
+        }
+
+        let mut steps = steps.to_vec();
+        steps.sort_by_key(|step| step.target.market_cap());
+        let total_percent: f64 = steps.iter().map(|step| step.percent).sum();
+        let covers_position = !steps.is_empty() && (total_percent - 100.0).abs() < 1e-9;
+
+        let mut allocated = 0u64;
+        let mut tranches = Vec::with_capacity(steps.len());
+        for (idx, step) in steps.iter().enumerate() {
+            // The final tranche of a full ladder takes the rounding remainder as well
+            let tranche_tokens = if covers_position && idx + 1 == steps.len() {
+                token_amount - allocated
+            } else {
+                let bps = (step.percent * 100.0).round() as u128;
+                ((token_amount as u128 * bps / 10_000) as u64).min(token_amount - allocated)
+            };
+            allocated += tranche_tokens;
+
+            tranches.push(Tranche {
+                market_cap: step.target.market_cap(),
+                token_amount: tranche_tokens,
+                sold: false,
+            });
+        }
+
+        Ok(Self {
+            mint,
+            tranches,
+            covers_position,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tranches.is_empty()
+    }
+
+    /// Whether every tranche has been sold.
+    pub fn is_done(&self) -> bool {
+        self.tranches.iter().all(|tranche| tranche.sold)
+    }
+
+    /// Tokens of tranches that are not sold yet.
+    pub fn pending_tokens(&self) -> u64 {
+        self.tranches
+            .iter()
+            .filter(|tranche| !tranche.sold)
+            .map(|tranche| tranche.token_amount)
+            .sum()
+    }
+
+    /// Every unsold tranche whose target `curve` reached, as one sell. They stay unsold until
+    /// `mark_sold`, so a sell that does not land is tried again on the next update.
+    pub fn on_curve(&self, curve: &BondingCurveQuote) -> Option<LadderSell> {
+        let market_cap = curve.market_cap().ok()?;
+
+        let tranches: Vec<usize> = self
+            .tranches
+            .iter()
+            .enumerate()
+            .filter(|(_, tranche)| !tranche.sold && market_cap >= tranche.market_cap)
+            .map(|(idx, _)| idx)
+            .collect();
+        let token_amount = tranches
+            .iter()
+            .map(|idx| self.tranches[*idx].token_amount)
+            .sum();
+        if tranches.is_empty() || token_amount == 0 {
+            return None;
+        }
+
+        // The last sell of a full ladder takes every tranche that is left
+        let sells_rest = tranches.len() == self.tranches.iter().filter(|t| !t.sold).count();
+
+        Some(LadderSell {
+            mint: self.mint,
+            token_amount,
+            tranches,
+            market_cap,
+            close_ata: self.covers_position && sells_rest,
+        })
+    }
+
+    /// Marks the tranches of `sell` as sold once it landed.
+    pub fn mark_sold(&mut self, sell: &LadderSell) {
+        for idx in &sell.tranches {
+            if let Some(tranche) = self.tranches.get_mut(*idx) {
+                tranche.sold = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_SOL: u64 = 1_000_000_000;
+
+    fn step(market_cap_sol: f64, percent: f64) -> LadderStep {
+        LadderStep {
+            target: LadderTarget::MarketCapSol(market_cap_sol),
+            percent,
+        }
+    }
+
+    // Over the full supply the market cap is the virtual SOL reserves
+    fn curve_at(market_cap_sol: u64) -> BondingCurveQuote {
+        BondingCurveQuote {
+            virtual_sol_reserves: market_cap_sol * ONE_SOL,
+            virtual_token_reserves: TOKEN_TOTAL_SUPPLY,
+            ..BondingCurveQuote::initial()
+        }
+    }
+
+    fn amounts(ladder: &SellLadder) -> Vec<u64> {
+        ladder
+            .tranches
+            .iter()
+            .map(|tranche| tranche.token_amount)
+            .collect()
+    }
+
+    #[test]
+    fn tranches_are_sorted_by_market_cap() {
+        let ladder = SellLadder::new(
+            Pubkey::new_unique(),
+            1_000,
+            &[step(90.0, 50.0), step(60.0, 50.0)],
+        )
+        .unwrap();
+
+        let market_caps: Vec<u64> = ladder.tranches.iter().map(|t| t.market_cap).collect();
+        assert_eq!(market_caps, [60 * ONE_SOL, 90 * ONE_SOL]);
+    }
+
+    #[test]
+    fn full_ladder_tranches_sum_to_the_position() {
+        let ladder = SellLadder::new(
+            Pubkey::new_unique(),
+            1_000_003,
+            &[step(40.0, 33.33), step(60.0, 33.33), step(80.0, 33.34)],
+        )
+        .unwrap();
+
+        // floor(1_000_003 * 3333 / 10000) twice, the last one takes the remainder
+        assert_eq!(amounts(&ladder), [333_300, 333_300, 333_403]);
+        assert_eq!(amounts(&ladder).iter().sum::<u64>(), 1_000_003);
+        assert_eq!(ladder.pending_tokens(), 1_000_003);
+    }
+
+    #[test]
+    fn partial_ladder_leaves_the_rest() {
+        let ladder = SellLadder::new(
+            Pubkey::new_unique(),
+            999,
+            &[step(40.0, 25.0), step(60.0, 25.0)],
+        )
+        .unwrap();
+
+        assert_eq!(amounts(&ladder), [249, 249]);
+        let sell = ladder.on_curve(&curve_at(100)).unwrap();
+        assert_eq!(sell.token_amount, 498);
+        assert!(!sell.close_ata);
+    }
+
+    #[test]
+    fn invalid_steps_are_rejected() {
+        let mint = Pubkey::new_unique();
+
+        assert!(SellLadder::new(mint, 1_000, &[step(40.0, 60.0), step(60.0, 60.0)]).is_err());
+        assert!(SellLadder::new(mint, 1_000, &[step(40.0, 0.0)]).is_err());
+        assert!(SellLadder::new(mint, 1_000, &[step(-1.0, 10.0)]).is_err());
+    }
+
+    #[test]
+    fn on_curve_takes_every_tranche_reached() {
+        let ladder = SellLadder::new(
+            Pubkey::new_unique(),
+            1_000,
+            &[step(40.0, 20.0), step(60.0, 30.0), step(80.0, 50.0)],
+        )
+        .unwrap();
+
+        assert_eq!(ladder.on_curve(&curve_at(39)), None);
+        let sell = ladder.on_curve(&curve_at(65)).unwrap();
+        assert_eq!(sell.tranches, [0, 1]);
+        assert_eq!(sell.token_amount, 500);
+        assert_eq!(sell.market_cap, 65 * ONE_SOL);
+        assert!(!sell.close_ata);
+    }
+
+    #[test]
+    fn unsold_tranches_are_offered_again() {
+        let mut ladder = SellLadder::new(
+            Pubkey::new_unique(),
+            1_000,
+            &[step(40.0, 50.0), step(60.0, 50.0)],
+        )
+        .unwrap();
+
+        // A sell that did not land leaves the ladder as it was
+        let sell = ladder.on_curve(&curve_at(45)).unwrap();
+        assert_eq!(ladder.on_curve(&curve_at(45)), Some(sell.clone()));
+
+        ladder.mark_sold(&sell);
+        assert_eq!(ladder.on_curve(&curve_at(45)), None);
+        assert_eq!(ladder.pending_tokens(), 500);
+        assert!(!ladder.is_done());
+    }
+
+    #[test]
+    fn last_tranche_of_full_ladder_closes_the_ata() {
+        let mut ladder = SellLadder::new(
+            Pubkey::new_unique(),
+            1_001,
+            &[step(40.0, 50.0), step(60.0, 50.0)],
+        )
+        .unwrap();
+
+        let first = ladder.on_curve(&curve_at(40)).unwrap();
+        assert!(!first.close_ata);
+        ladder.mark_sold(&first);
+
+        let last = ladder.on_curve(&curve_at(70)).unwrap();
+        assert_eq!(last.tranches, [1]);
+        assert_eq!(first.token_amount + last.token_amount, 1_001);
+        assert!(last.close_ata);
+        ladder.mark_sold(&last);
+        assert!(ladder.is_done());
+        assert_eq!(ladder.on_curve(&curve_at(100)), None);
+    }
+
+    #[test]
+    fn one_sell_of_every_tranche_closes_the_ata() {
+        let ladder = SellLadder::new(
+            Pubkey::new_unique(),
+            1_000,
+            &[step(40.0, 50.0), step(60.0, 50.0)],
+        )
+        .unwrap();
+
+        let sell = ladder.on_curve(&curve_at(60)).unwrap();
+        assert_eq!(sell.token_amount, 1_000);
+        assert!(sell.close_ata);
+    }
+
+    #[test]
+    fn price_target_is_converted_to_market_cap() {
+        // 1e9 whole tokens at 1e-7 SOL each
+        assert_eq!(
+            LadderTarget::PriceSol(0.000_000_1).market_cap(),
+            100 * ONE_SOL
+        );
+    }
+}
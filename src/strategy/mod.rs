@@ -0,0 +1,7 @@
+pub mod engine;
+pub mod ladder;
+pub mod rules;
+
+pub use engine::*;
+pub use ladder::*;
+pub use rules::*;
@@ -0,0 +1,382 @@
+use serde::Deserialize;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use std::{fs, path::Path, time::Duration};
+
+use crate::{
+    config::{TradingProfile, active_profile},
+    error::{ClientError, ClientResult},
+    monitor::TradeUpdate,
+    strategy::{LadderStep, Position},
+};
+
+/// Condition that triggers the sell, evaluated on every trade of the position's mint.
+///
+/// In config each rule is a one-key object, e.g. `{"min_buy_sol": 0.5}` or
+/// `{"any": [{"take_profit": 2.0}, {"stop_loss": 0.3}]}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SellRule {
+    /// An external buy of at least this many SOL.
+    MinBuySol(f64),
+    /// External buys since entry add up to at least this many SOL.
+    CumulativeVolumeSol(f64),
+    /// Market cap of the curve in SOL.
+    MarketCapSol(f64),
+    VirtualSolReservesSol(f64),
+    /// Sell value of the position reaches this multiple of its cost basis.
+    TakeProfit(f64),
+    /// Sell value of the position dropped by this fraction of its cost basis, e.g. 0.3.
+    StopLoss(f64),
+    MaxHoldSecs(u64),
+    Any(Vec<SellRule>),
+    All(Vec<SellRule>),
+}
+
+impl SellRule {
+    /// Returns why the rule fired. `trade` is the external buy being processed, if any.
+    pub fn evaluate(&self, position: &Position, trade: Option<&TradeUpdate>) -> Option<String> {
+        match self {
+            Self::MinBuySol(sol) => trade
+                .filter(|trade| trade.event.sol_amount >= to_lamports(*sol))
+                .map(|trade| {
+                    format!(
+                        "buy of {} lamports by {}",
+                        trade.event.sol_amount, trade.event.user
+                    )
+                }),
+            Self::CumulativeVolumeSol(sol) => (position.external_buy_volume >= to_lamports(*sol))
+                .then(|| {
+                    format!(
+                        "external buy volume {} lamports",
+                        position.external_buy_volume
+                    )
+                }),
+            Self::MarketCapSol(sol) => {
+                let market_cap = position.curve?.market_cap().ok()?;
+                (market_cap >= to_lamports(*sol))
+                    .then(|| format!("market cap {} lamports", market_cap))
+            }
+            Self::VirtualSolReservesSol(sol) => {
+                let reserves = position.curve?.virtual_sol_reserves;
+                (reserves >= to_lamports(*sol))
+                    .then(|| format!("virtual SOL reserves {} lamports", reserves))
+            }
+            Self::TakeProfit(multiple) => {
+                let value = position.value()?;
+                (value as f64 >= position.cost_basis as f64 * multiple).then(|| {
+                    format!(
+                        "take profit: value {} vs cost {} lamports",
+                        value, position.cost_basis
+                    )
+                })
+            }
+            Self::StopLoss(fraction) => {
+                let value = position.value()?;
+                (value as f64 <= position.cost_basis as f64 * (1.0 - fraction)).then(|| {
+                    format!(
+                        "stop loss: value {} vs cost {} lamports",
+                        value, position.cost_basis
+                    )
+                })
+            }
+            Self::MaxHoldSecs(secs) => {
+                let held = position.opened_at.elapsed();
+                (held >= Duration::from_secs(*secs)).then(|| format!("held for {:?}", held))
+            }
+            Self::Any(rules) => rules.iter().find_map(|rule| rule.evaluate(position, trade)),
+            Self::All(rules) => {
+                if rules.is_empty() {
+                    return None;
+                }
+
+                rules
+                    .iter()
+                    .map(|rule| rule.evaluate(position, trade))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|reasons| reasons.join(" and "))
+            }
+        }
+    }
+
+    fn validate(&self, errors: &mut Vec<String>) {
+        let non_negative = |errors: &mut Vec<String>, name: &str, value: f64| {
+            if !value.is_finite() || value < 0.0 {
+                errors.push(format!(
+                    "{} must be a non-negative number, got {}",
+                    name, value
+                ));
+            }
+        };
+
+        match self {
+            Self::MinBuySol(sol) => non_negative(errors, "min_buy_sol", *sol),
+            Self::CumulativeVolumeSol(sol) => non_negative(errors, "cumulative_volume_sol", *sol),
+            Self::MarketCapSol(sol) => non_negative(errors, "market_cap_sol", *sol),
+            Self::VirtualSolReservesSol(sol) => {
+                non_negative(errors, "virtual_sol_reserves_sol", *sol)
+            }
+            Self::TakeProfit(multiple) => non_negative(errors, "take_profit", *multiple),
+            Self::StopLoss(fraction) => {
+                if !(0.0..=1.0).contains(fraction) {
+                    errors.push(format!(
+                        "stop_loss must be between 0 and 1, got {}",
+                        fraction
+                    ));
+                }
+            }
+            Self::MaxHoldSecs(_) => {}
+            Self::Any(rules) | Self::All(rules) => {
+                if rules.is_empty() {
+                    errors.push("any/all need at least one rule".to_string());
+                }
+                for rule in rules {
+                    rule.validate(errors);
+                }
+            }
+        }
+    }
+}
+
+fn default_exclude_farm_wallets() -> bool {
+    true
+}
+
+/// Sell strategy as loaded from the `sell_strategy_file` of a profile.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StrategyConfig {
+    pub rule: SellRule,
+    /// Trades of our own farm wallets never count as external activity.
+    #[serde(default = "default_exclude_farm_wallets")]
+    pub exclude_farm_wallets: bool,
+    /// Further wallets whose trades are ignored, as base58 pubkeys.
+    #[serde(default)]
+    pub exclude_wallets: Vec<String>,
+    /// Partial sells at rising targets before the rule sells the rest.
+    #[serde(default)]
+    pub ladder: Vec<LadderStep>,
+}
+
+impl Default for StrategyConfig {
+    /// Sell on the first buy by anyone outside the farm.
+    fn default() -> Self {
+        Self {
+            rule: SellRule::MinBuySol(0.0),
+            exclude_farm_wallets: true,
+            exclude_wallets: Vec::new(),
+            ladder: Vec::new(),
+        }
+    }
+}
+
+impl StrategyConfig {
+    /// Loads the strategy of the active profile.
+    pub fn from_env() -> ClientResult<Self> {
+        Self::from_profile(&active_profile()?)
+    }
+
+    /// Loads the `sell_strategy_file` of `profile` if set, otherwise the default strategy.
+    pub fn from_profile(profile: &TradingProfile) -> ClientResult<Self> {
+        match profile.sell_strategy_file.as_deref() {
+            Some(path) => Self::load(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> ClientResult<Self> {
+        let path = path.as_ref();
+        let input = fs::read_to_string(path)
+            .map_err(|err| ClientError::Other(format!("{}: {}", path.display(), err)))?;
+
+        Self::from_json(&input)
+    }
+
+    pub fn from_json(input: &str) -> ClientResult<Self> {
+        let config: Self = serde_json::from_str(input)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Checks every rule, excluded wallet and ladder step, reporting all problems at once.
+    pub fn validate(&self) -> ClientResult<()> {
+        let mut errors = Vec::new();
+        self.rule.validate(&mut errors);
+        LadderStep::validate(&self.ladder, &mut errors);
+        for wallet in &self.exclude_wallets {
+            if wallet.parse::<Pubkey>().is_err() {
+                errors.push(format!("exclude_wallets: invalid pubkey {}", wallet));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ClientError::InvalidData(format!(
+                "invalid sell strategy: {}",
+                errors.join("; ")
+            )))
+        }
+    }
+}
+
+fn to_lamports(sol: f64) -> u64 {
+    (sol * LAMPORTS_PER_SOL as f64) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Signature;
+    use std::time::Instant;
+
+    use crate::{
+        instructions::types::TradeEventTemp, monitor::TradeSide, utils::BondingCurveQuote,
+    };
+
+    const ONE_SOL: u64 = 1_000_000_000;
+    const TOKENS: u64 = 10_000_000_000_000;
+
+    /// Position on the initial curve whose cost basis is `cost_multiple` of its value.
+    fn position(cost_multiple: f64) -> Position {
+        let mut position = Position::new(Pubkey::new_unique(), TOKENS, 0);
+        position.curve = Some(BondingCurveQuote::initial());
+        position.cost_basis = (position.value().unwrap() as f64 * cost_multiple) as u64;
+        position
+    }
+
+    fn buy(position: &Position, sol_amount: u64) -> TradeUpdate {
+        TradeUpdate {
+            side: TradeSide::Buy,
+            signature: Signature::default(),
+            slot: 0,
+            fee_payer: Pubkey::new_unique(),
+            event: TradeEventTemp {
+                mint: position.mint,
+                sol_amount,
+                token_amount: 0,
+                is_buy: true,
+                user: Pubkey::new_unique(),
+                timestamp: 0,
+                virtual_sol_reserves: 0,
+                virtual_token_reserves: 0,
+                real_sol_reserves: 0,
+                real_token_reserves: 0,
+                fee_recipient: Pubkey::default(),
+                fee_basis_points: 0,
+                fee: 0,
+                creator: Pubkey::default(),
+                creator_fee_basis_points: 0,
+                creator_fee: 0,
+                track_volume: false,
+                total_unclaimed_tokens: 0,
+                total_claimed_tokens: 0,
+                current_sol_volume: 0,
+                last_update_timestamp: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn take_profit_fires_at_the_multiple() {
+        let rule = SellRule::TakeProfit(2.0);
+
+        assert!(rule.evaluate(&position(0.5), None).is_some());
+        assert!(rule.evaluate(&position(0.6), None).is_none());
+    }
+
+    #[test]
+    fn stop_loss_fires_after_the_drop() {
+        let rule = SellRule::StopLoss(0.3);
+
+        assert!(rule.evaluate(&position(1.5), None).is_some());
+        assert!(rule.evaluate(&position(1.2), None).is_none());
+    }
+
+    #[test]
+    fn value_rules_wait_for_a_curve() {
+        let mut position = position(0.5);
+        position.curve = None;
+
+        assert!(
+            SellRule::TakeProfit(2.0)
+                .evaluate(&position, None)
+                .is_none()
+        );
+        assert!(SellRule::StopLoss(0.3).evaluate(&position, None).is_none());
+    }
+
+    #[test]
+    fn max_hold_fires_once_held_long_enough() {
+        let mut position = position(1.0);
+        let rule = SellRule::MaxHoldSecs(60);
+        assert!(rule.evaluate(&position, None).is_none());
+
+        position.opened_at = Instant::now() - Duration::from_secs(61);
+        assert!(
+            rule.evaluate(&position, None)
+                .unwrap()
+                .starts_with("held for")
+        );
+    }
+
+    #[test]
+    fn min_buy_needs_a_large_enough_trade() {
+        let position = position(1.0);
+        let rule = SellRule::MinBuySol(0.5);
+
+        assert!(rule.evaluate(&position, None).is_none());
+        assert!(
+            rule.evaluate(&position, Some(&buy(&position, ONE_SOL / 4)))
+                .is_none()
+        );
+        assert!(
+            rule.evaluate(&position, Some(&buy(&position, ONE_SOL / 2)))
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn any_fires_on_the_first_matching_rule() {
+        let rule = SellRule::Any(vec![SellRule::TakeProfit(2.0), SellRule::StopLoss(0.3)]);
+
+        let reason = rule.evaluate(&position(1.5), None).unwrap();
+        assert!(reason.starts_with("stop loss"));
+        assert!(rule.evaluate(&position(1.0), None).is_none());
+    }
+
+    #[test]
+    fn all_needs_every_rule_and_joins_the_reasons() {
+        let mut position = position(0.5);
+        let rule = SellRule::All(vec![SellRule::TakeProfit(2.0), SellRule::MaxHoldSecs(60)]);
+        assert!(rule.evaluate(&position, None).is_none());
+
+        position.opened_at = Instant::now() - Duration::from_secs(61);
+        let reason = rule.evaluate(&position, None).unwrap();
+        assert!(reason.starts_with("take profit"));
+        assert!(reason.contains(" and held for"));
+
+        assert!(
+            SellRule::All(Vec::new())
+                .evaluate(&position, None)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn nested_rules_parse_from_json() {
+        let config = StrategyConfig::from_json(
+            r#"{"rule": {"any": [{"take_profit": 2.0}, {"all": [{"stop_loss": 0.3}, {"max_hold_secs": 30}]}]}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.rule,
+            SellRule::Any(vec![
+                SellRule::TakeProfit(2.0),
+                SellRule::All(vec![SellRule::StopLoss(0.3), SellRule::MaxHoldSecs(30)]),
+            ])
+        );
+        assert!(StrategyConfig::from_json(r#"{"rule": {"stop_loss": 1.5}}"#).is_err());
+    }
+}
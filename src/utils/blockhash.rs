@@ -1,39 +1,187 @@
+use futures::StreamExt;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{commitment_config::CommitmentConfig, hash::Hash};
-use std::sync::Arc;
-use tokio::time::{Duration, sleep};
+use solana_sdk::{clock::MAX_PROCESSING_AGE, commitment_config::CommitmentConfig, hash::Hash};
+use std::{collections::HashMap, str::FromStr, sync::Arc, time::Instant};
+use tokio::{
+    sync::watch,
+    task::JoinHandle,
+    time::{Duration, sleep},
+};
+use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient};
+use yellowstone_grpc_proto::geyser::{
+    CommitmentLevel, SubscribeRequest, SubscribeRequestFilterBlocksMeta, SubscribeUpdateBlockMeta,
+    subscribe_update::UpdateOneof,
+};
 
 use once_cell::sync::Lazy;
-use std::sync::Mutex;
 
-static GLOBAL_CURRENT_HASH: Lazy<Mutex<Hash>> = Lazy::new(|| Mutex::new(Hash::default()));
+use crate::{
+    config::{bot_config, grpc_endpoint, grpc_token},
+    error::{ClientError, ClientResult},
+};
 
-fn set_slot(value: Hash) {
-    let mut slot = GLOBAL_CURRENT_HASH.lock().unwrap();
-    *slot = value;
+pub const BLOCKHASH_POLL_MS: u64 = 400;
+const BLOCKHASH_RETRY_MS: u64 = 200;
+const GRPC_RECONNECT_SEC: u64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachedBlockhash {
+    pub blockhash: Hash,
+    pub last_valid_block_height: u64,
+    pub received_at: Instant,
+}
+
+impl CachedBlockhash {
+    pub fn age(&self) -> Duration {
+        self.received_at.elapsed()
+    }
+}
+
+static BLOCKHASH_CACHE: Lazy<watch::Sender<Option<CachedBlockhash>>> =
+    Lazy::new(|| watch::channel(None).0);
+
+fn set_blockhash(blockhash: Hash, last_valid_block_height: u64) {
+    BLOCKHASH_CACHE.send_if_modified(|cached| {
+        // Never go back to an older blockhash, e.g. from a lagging RPC node
+        if cached.is_some_and(|cached| cached.last_valid_block_height > last_valid_block_height) {
+            return false;
+        }
+
+        *cached = Some(CachedBlockhash {
+            blockhash,
+            last_valid_block_height,
+            received_at: Instant::now(),
+        });
+        true
+    });
 }
 
+/// Latest cached blockhash, `None` until the refresher got the first one.
+pub fn latest_blockhash() -> Option<CachedBlockhash> {
+    *BLOCKHASH_CACHE.borrow()
+}
+
+/// Waits until the refresher has fetched a blockhash.
+pub async fn wait_for_blockhash() -> CachedBlockhash {
+    let mut receiver = BLOCKHASH_CACHE.subscribe();
+    let cached = receiver
+        .wait_for(Option::is_some)
+        .await
+        .expect("blockhash cache sender is static");
+
+    cached.expect("checked by wait_for")
+}
+
+/// Latest blockhash if it is younger than `max_age`.
+pub fn fresh_blockhash(max_age: Duration) -> ClientResult<CachedBlockhash> {
+    match latest_blockhash() {
+        Some(cached) if cached.age() <= max_age => Ok(cached),
+        Some(cached) => Err(ClientError::Other(format!(
+            "cached blockhash {} is {:?} old",
+            cached.blockhash,
+            cached.age()
+        ))),
+        None => Err(ClientError::Other("no blockhash cached yet".to_string())),
+    }
+}
+
+/// Latest cached blockhash, `Hash::default()` until the first one is fetched.
 pub fn get_slot() -> Hash {
-    let slot = GLOBAL_CURRENT_HASH.lock().unwrap();
-    *slot
+    latest_blockhash()
+        .map(|cached| cached.blockhash)
+        .unwrap_or_default()
 }
 
-/// Continuously retries fetching the latest blockhash until successful.
+/// Keeps the cache updated by polling the RPC. Runs forever.
 pub async fn recent_blockhash_handler(rpc_client: Arc<RpcClient>) {
     loop {
+        // Processed blockhashes can belong to a skipped fork
         match rpc_client
-            .get_latest_blockhash_with_commitment(CommitmentConfig::processed())
+            .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
             .await
         {
-            Ok((latest_blockhash, _)) => {
-                set_slot(latest_blockhash);
-                break; // Exit the loop once we get a valid blockhash
+            Ok((latest_blockhash, last_valid_block_height)) => {
+                set_blockhash(latest_blockhash, last_valid_block_height);
+                sleep(Duration::from_millis(BLOCKHASH_POLL_MS)).await;
             }
-            Err(e) => {
-                sleep(Duration::from_millis(200)).await;
+            Err(err) => {
+                eprintln!("Failed to fetch latest blockhash: {}", err);
+                sleep(Duration::from_millis(BLOCKHASH_RETRY_MS)).await;
             }
         }
     }
+}
+
+fn cache_block_meta(block_meta: &SubscribeUpdateBlockMeta) -> ClientResult<()> {
+    let blockhash = Hash::from_str(&block_meta.blockhash)
+        .map_err(|err| ClientError::Parse("block meta blockhash".to_string(), err.to_string()))?;
+    let block_height = block_meta
+        .block_height
+        .ok_or_else(|| {
+            ClientError::InvalidData(format!("slot {} has no block height", block_meta.slot))
+        })?
+        .block_height;
 
-    sleep(Duration::from_millis(500)).await;
-}
\ No newline at end of file
+    set_blockhash(blockhash, block_height + MAX_PROCESSING_AGE as u64);
+    Ok(())
+}
+
+async fn stream_block_meta(endpoint: &str, x_token: &str) -> ClientResult<()> {
+    let subscribe_err = |err: &dyn std::fmt::Display| {
+        ClientError::Subscribe("blocks meta".to_string(), err.to_string())
+    };
+
+    let mut client = GeyserGrpcClient::build_from_shared(endpoint.to_string())
+        .map_err(|err| subscribe_err(&err))?
+        .x_token(Some(x_token.to_string()))
+        .map_err(|err| subscribe_err(&err))?
+        .tls_config(ClientTlsConfig::new().with_enabled_roots())
+        .map_err(|err| subscribe_err(&err))?
+        .connect()
+        .await
+        .map_err(|err| subscribe_err(&err))?;
+
+    let request = SubscribeRequest {
+        blocks_meta: HashMap::from([(
+            "blockhash".to_string(),
+            SubscribeRequestFilterBlocksMeta {},
+        )]),
+        commitment: Some(CommitmentLevel::Confirmed as i32),
+        ..Default::default()
+    };
+
+    let mut stream = client
+        .subscribe_once(request)
+        .await
+        .map_err(|err| subscribe_err(&err))?;
+
+    while let Some(update) = stream.next().await {
+        let update = update.map_err(|err| subscribe_err(&err))?;
+        if let Some(UpdateOneof::BlockMeta(block_meta)) = update.update_oneof {
+            cache_block_meta(&block_meta)?;
+        }
+    }
+
+    Err(ClientError::ChannelClosed)
+}
+
+/// Keeps the cache updated from the Yellowstone blocks meta stream. Runs forever and
+/// reconnects when the stream drops.
+pub async fn grpc_blockhash_handler(endpoint: String, x_token: String) {
+    loop {
+        if let Err(err) = stream_block_meta(&endpoint, &x_token).await {
+            eprintln!("Blocks meta stream failed: {}, reconnecting...", err);
+        }
+
+        sleep(Duration::from_secs(GRPC_RECONNECT_SEC)).await;
+    }
+}
+
+/// Spawns the blockhash refresher, streaming from gRPC when `BLOCKHASH_FROM_GRPC` is set.
+pub fn spawn_blockhash_refresher(rpc_client: Arc<RpcClient>) -> ClientResult<JoinHandle<()>> {
+    Ok(if bot_config()?.blockhash_from_grpc {
+        tokio::spawn(grpc_blockhash_handler(grpc_endpoint()?, grpc_token()?))
+    } else {
+        tokio::spawn(recent_blockhash_handler(rpc_client))
+    })
+}
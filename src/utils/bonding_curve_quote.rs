@@ -0,0 +1,521 @@
+use carbon_pumpfun_decoder::accounts::global::Global;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    error::{ClientError, ClientResult},
+    instructions::{
+        pumpfun_buy::{
+            INITIAL_REAL_TOKEN_RESERVES, INITIAL_VIRTUAL_SOL_RESERVES,
+            INITIAL_VIRTUAL_TOKEN_RESERVES, PUMPFUN_CREATOR_FEE_BASIS_POINTS,
+            PUMPFUN_FEE_BASIS_POINTS, TOKEN_TOTAL_SUPPLY,
+        },
+        types::{BondingCurveAccount, TradeEventTemp},
+    },
+};
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+const OVERFLOW: ClientError = ClientError::BondingCurveError("arithmetic overflow");
+
+/// Snapshot of a bonding curve plus the fees it charges, quoting trades with the same integer
+/// math as the pump.fun program.
+///
+/// Amounts paid by the trader are rounded up and amounts received are rounded down, so a quote
+/// never promises more than the program will give.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BondingCurveQuote {
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub complete: bool,
+    pub fee_basis_points: u64,
+    /// Zero while the curve has no creator.
+    pub creator_fee_basis_points: u64,
+}
+
+impl BondingCurveQuote {
+    pub fn new(curve: &BondingCurveAccount, global: &Global) -> Self {
+        Self {
+            virtual_sol_reserves: curve.virtual_sol_reserves,
+            virtual_token_reserves: curve.virtual_token_reserves,
+            real_sol_reserves: curve.real_sol_reserves,
+            real_token_reserves: curve.real_token_reserves,
+            complete: curve.complete,
+            fee_basis_points: global.fee_basis_points,
+            creator_fee_basis_points: if curve.creator == Pubkey::default() {
+                0
+            } else {
+                global.creator_fee_basis_points
+            },
+        }
+    }
+
+    /// Curve of a token that is created in the same transaction, with the default fees.
+    pub fn initial() -> Self {
+        Self {
+            virtual_sol_reserves: INITIAL_VIRTUAL_SOL_RESERVES,
+            virtual_token_reserves: INITIAL_VIRTUAL_TOKEN_RESERVES,
+            real_sol_reserves: 0,
+            real_token_reserves: INITIAL_REAL_TOKEN_RESERVES,
+            complete: false,
+            fee_basis_points: PUMPFUN_FEE_BASIS_POINTS,
+            creator_fee_basis_points: PUMPFUN_CREATOR_FEE_BASIS_POINTS,
+        }
+    }
+
+    /// Curve of a token that is created in the same transaction, with the initial reserves and
+    /// fees of `global`.
+    pub fn initial_with(global: &Global) -> Self {
+        Self {
+            virtual_sol_reserves: global.initial_virtual_sol_reserves,
+            virtual_token_reserves: global.initial_virtual_token_reserves,
+            real_sol_reserves: 0,
+            real_token_reserves: global.initial_real_token_reserves,
+            complete: false,
+            fee_basis_points: global.fee_basis_points,
+            creator_fee_basis_points: global.creator_fee_basis_points,
+        }
+    }
+
+    /// Curve state right after the trade in `event`.
+    pub fn from_trade_event(event: &TradeEventTemp) -> Self {
+        Self {
+            virtual_sol_reserves: event.virtual_sol_reserves,
+            virtual_token_reserves: event.virtual_token_reserves,
+            real_sol_reserves: event.real_sol_reserves,
+            real_token_reserves: event.real_token_reserves,
+            complete: false,
+            fee_basis_points: event.fee_basis_points,
+            creator_fee_basis_points: event.creator_fee_basis_points,
+        }
+    }
+
+    fn ensure_active(&self) -> ClientResult<()> {
+        if self.complete {
+            return Err(ClientError::BondingCurveError("bonding curve is complete"));
+        }
+
+        Ok(())
+    }
+
+    fn total_fee_bps(&self) -> ClientResult<u128> {
+        (self.fee_basis_points as u128)
+            .checked_add(self.creator_fee_basis_points as u128)
+            .ok_or(OVERFLOW)
+    }
+
+    // Each fee is rounded up on its own, so the sum can exceed the exact fee by this much
+    fn fee_rounding_slack(&self) -> u128 {
+        1 + (self.creator_fee_basis_points > 0) as u128
+    }
+
+    /// Market cap in lamports at the current price, over the full token supply.
+    pub fn market_cap(&self) -> ClientResult<u64> {
+        to_u64(
+            mul(
+                self.virtual_sol_reserves as u128,
+                TOKEN_TOTAL_SUPPLY as u128,
+            )?
+            .checked_div(self.virtual_token_reserves as u128)
+            .ok_or(ClientError::BondingCurveError("division by zero"))?,
+        )
+    }
+
+    /// Protocol + creator fee on `amount`, each rounded up.
+    pub fn fee(&self, amount: u64) -> ClientResult<u64> {
+        let protocol_fee = ceil_div(
+            mul(amount as u128, self.fee_basis_points as u128)?,
+            BPS_DENOMINATOR,
+        )?;
+        let creator_fee = ceil_div(
+            mul(amount as u128, self.creator_fee_basis_points as u128)?,
+            BPS_DENOMINATOR,
+        )?;
+
+        to_u64(protocol_fee.checked_add(creator_fee).ok_or(OVERFLOW)?)
+    }
+
+    /// Tokens bought with at most `sol_amount` lamports, fees included (rounded down).
+    pub fn buy_exact_sol_in(&self, sol_amount: u64) -> ClientResult<u64> {
+        self.ensure_active()?;
+
+        // Largest curve cost whose fees still fit into `sol_amount`
+        let budget = match (sol_amount as u128).checked_sub(self.fee_rounding_slack()) {
+            Some(available) => mul(available, BPS_DENOMINATOR)?
+                .checked_div(
+                    BPS_DENOMINATOR
+                        .checked_add(self.total_fee_bps()?)
+                        .ok_or(OVERFLOW)?,
+                )
+                .ok_or(OVERFLOW)?,
+            None => 0,
+        };
+        if budget == 0 {
+            return Ok(0);
+        }
+
+        // The program charges `floor(t * vsr / (vtr - t)) + 1`, keep that within `budget`
+        let vsr = self.virtual_sol_reserves as u128;
+        let vtr = self.virtual_token_reserves as u128;
+        let tokens = mul(budget, vtr)?
+            .saturating_sub(1)
+            .checked_div(vsr.checked_add(budget).ok_or(OVERFLOW)?)
+            .ok_or(OVERFLOW)?;
+
+        to_u64(tokens.min(self.real_token_reserves as u128))
+    }
+
+    // Lamports the curve takes for exactly `token_amount`, without fees
+    fn buy_curve_cost(&self, token_amount: u64) -> ClientResult<u64> {
+        self.ensure_active()?;
+
+        if token_amount > self.real_token_reserves {
+            return Err(ClientError::BondingCurveError(
+                "not enough tokens left on the curve",
+            ));
+        }
+
+        let remaining = (self.virtual_token_reserves as u128)
+            .checked_sub(token_amount as u128)
+            .filter(|remaining| *remaining > 0)
+            .ok_or(ClientError::BondingCurveError(
+                "buy exceeds virtual token reserves",
+            ))?;
+
+        to_u64(
+            mul(token_amount as u128, self.virtual_sol_reserves as u128)?
+                .checked_div(remaining)
+                .ok_or(OVERFLOW)?
+                .checked_add(1)
+                .ok_or(OVERFLOW)?,
+        )
+    }
+
+    /// Lamports needed to buy exactly `token_amount`, fees included (rounded up).
+    pub fn buy_exact_tokens_out(&self, token_amount: u64) -> ClientResult<u64> {
+        let sol_cost = self.buy_curve_cost(token_amount)?;
+
+        sol_cost.checked_add(self.fee(sol_cost)?).ok_or(OVERFLOW)
+    }
+
+    /// Curve state after buying exactly `token_amount`, for quoting trades that land right
+    /// after it, e.g. the buys of a bundle.
+    pub fn after_buy(&self, token_amount: u64) -> ClientResult<Self> {
+        // Fees are paid out, only the curve cost stays in the reserves
+        let sol_cost = self.buy_curve_cost(token_amount)?;
+
+        Ok(Self {
+            virtual_sol_reserves: self
+                .virtual_sol_reserves
+                .checked_add(sol_cost)
+                .ok_or(OVERFLOW)?,
+            virtual_token_reserves: self.virtual_token_reserves - token_amount,
+            real_sol_reserves: self
+                .real_sol_reserves
+                .checked_add(sol_cost)
+                .ok_or(OVERFLOW)?,
+            real_token_reserves: self.real_token_reserves - token_amount,
+            // The program completes the curve once the last real token is bought
+            complete: self.real_token_reserves == token_amount,
+            ..*self
+        })
+    }
+
+    // Lamports the curve pays out for exactly `token_amount`, before fees
+    fn sell_curve_output(&self, token_amount: u64) -> ClientResult<u64> {
+        self.ensure_active()?;
+
+        to_u64(
+            mul(token_amount as u128, self.virtual_sol_reserves as u128)?
+                .checked_div(
+                    (self.virtual_token_reserves as u128)
+                        .checked_add(token_amount as u128)
+                        .ok_or(OVERFLOW)?,
+                )
+                .ok_or(ClientError::BondingCurveError("division by zero"))?,
+        )
+    }
+
+    /// Lamports received for selling `token_amount`, after fees (rounded down).
+    pub fn sell_exact_tokens_in(&self, token_amount: u64) -> ClientResult<u64> {
+        let sol_out = self.sell_curve_output(token_amount)?;
+
+        Ok(sol_out.saturating_sub(self.fee(sol_out)?))
+    }
+
+    /// Curve state after selling exactly `token_amount`, the counterpart of `after_buy`.
+    pub fn after_sell(&self, token_amount: u64) -> ClientResult<Self> {
+        // Fees are taken from the output, all of it leaves the reserves
+        let sol_out = self.sell_curve_output(token_amount)?;
+        if sol_out > self.real_sol_reserves {
+            return Err(ClientError::BondingCurveError(
+                "not enough SOL left on the curve",
+            ));
+        }
+
+        Ok(Self {
+            virtual_sol_reserves: self.virtual_sol_reserves - sol_out,
+            virtual_token_reserves: self
+                .virtual_token_reserves
+                .checked_add(token_amount)
+                .ok_or(OVERFLOW)?,
+            real_sol_reserves: self.real_sol_reserves - sol_out,
+            real_token_reserves: self
+                .real_token_reserves
+                .checked_add(token_amount)
+                .ok_or(OVERFLOW)?,
+            ..*self
+        })
+    }
+
+    /// Tokens to sell to receive at least `sol_amount` lamports after fees (rounded up).
+    pub fn sell_exact_sol_out(&self, sol_amount: u64) -> ClientResult<u64> {
+        self.ensure_active()?;
+
+        // Gross curve output whose fees still leave `sol_amount`
+        let denominator = BPS_DENOMINATOR
+            .checked_sub(self.total_fee_bps()?)
+            .filter(|denominator| *denominator > 0)
+            .ok_or(ClientError::BondingCurveError("fees exceed 100%"))?;
+        let gross = ceil_div(
+            mul(
+                (sol_amount as u128)
+                    .checked_add(self.fee_rounding_slack())
+                    .ok_or(OVERFLOW)?,
+                BPS_DENOMINATOR,
+            )?,
+            denominator,
+        )?;
+
+        if gross > self.real_sol_reserves as u128 {
+            return Err(ClientError::BondingCurveError(
+                "not enough SOL left on the curve",
+            ));
+        }
+
+        // floor(t * vsr / (vtr + t)) >= gross  <=>  t >= gross * vtr / (vsr - gross)
+        let vsr = self.virtual_sol_reserves as u128;
+        let remaining = vsr
+            .checked_sub(gross)
+            .filter(|remaining| *remaining > 0)
+            .ok_or(ClientError::BondingCurveError(
+                "sell exceeds virtual SOL reserves",
+            ))?;
+
+        to_u64(ceil_div(
+            mul(gross, self.virtual_token_reserves as u128)?,
+            remaining,
+        )?)
+    }
+}
+
+fn mul(a: u128, b: u128) -> ClientResult<u128> {
+    a.checked_mul(b).ok_or(OVERFLOW)
+}
+
+fn ceil_div(a: u128, b: u128) -> ClientResult<u128> {
+    if b == 0 {
+        return Err(ClientError::BondingCurveError("division by zero"));
+    }
+
+    Ok(a.div_ceil(b))
+}
+
+fn to_u64(value: u128) -> ClientResult<u64> {
+    u64::try_from(value).map_err(|_| OVERFLOW)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_SOL: u64 = 1_000_000_000;
+    // 1M tokens with 6 decimals
+    const ONE_M_TOKENS: u64 = 1_000_000_000_000;
+
+    fn is_error(result: ClientResult<impl std::fmt::Debug>, expected: &str) -> bool {
+        matches!(result, Err(ClientError::BondingCurveError(msg)) if msg == expected)
+    }
+
+    #[test]
+    fn fees_round_up_each_part() {
+        let curve = BondingCurveQuote::initial();
+
+        // ceil(95 / 10000) + ceil(5 / 10000)
+        assert_eq!(curve.fee(1).unwrap(), 2);
+        assert_eq!(curve.fee(10_000).unwrap(), 100);
+        // ceil(950095 / 10000) + ceil(50005 / 10000) = 96 + 6
+        assert_eq!(curve.fee(10_001).unwrap(), 102);
+        assert_eq!(curve.fee(ONE_SOL).unwrap(), 10_000_000);
+
+        let no_creator = BondingCurveQuote {
+            creator_fee_basis_points: 0,
+            ..curve
+        };
+        assert_eq!(no_creator.fee(10_001).unwrap(), 96);
+    }
+
+    #[test]
+    fn buy_exact_tokens_out_rounds_up() {
+        let curve = BondingCurveQuote::initial();
+
+        // floor(1e12 * 30e9 / (1.073e15 - 1e12)) + 1 = 27_985_075, plus 279_852 in fees
+        assert_eq!(
+            curve.buy_exact_tokens_out(ONE_M_TOKENS).unwrap(),
+            28_264_927
+        );
+    }
+
+    #[test]
+    fn buy_exact_sol_in_stays_within_budget() {
+        let curve = BondingCurveQuote::initial();
+
+        // budget = floor((1e9 - 2) * 10000 / 10100) = 990_099_007
+        // tokens = floor((budget * 1.073e15 - 1) / (30e9 + budget))
+        let tokens = curve.buy_exact_sol_in(ONE_SOL).unwrap();
+        assert_eq!(tokens, 34_281_150_062_509);
+        assert_eq!(curve.buy_exact_tokens_out(tokens).unwrap(), 999_999_998);
+
+        assert_eq!(curve.buy_exact_sol_in(2).unwrap(), 0);
+        assert_eq!(curve.buy_exact_sol_in(0).unwrap(), 0);
+    }
+
+    #[test]
+    fn buy_is_capped_by_real_reserves() {
+        let curve = BondingCurveQuote::initial();
+
+        assert_eq!(
+            curve.buy_exact_sol_in(1_000 * ONE_SOL).unwrap(),
+            INITIAL_REAL_TOKEN_RESERVES
+        );
+        assert!(is_error(
+            curve.buy_exact_tokens_out(INITIAL_REAL_TOKEN_RESERVES + 1),
+            "not enough tokens left on the curve"
+        ));
+    }
+
+    #[test]
+    fn after_buy_moves_the_curve_cost_only() {
+        let curve = BondingCurveQuote::initial()
+            .after_buy(ONE_M_TOKENS)
+            .unwrap();
+
+        assert_eq!(curve.virtual_sol_reserves, 30_027_985_075);
+        assert_eq!(curve.virtual_token_reserves, 1_072_000_000_000_000);
+        assert_eq!(curve.real_sol_reserves, 27_985_075);
+        assert_eq!(curve.real_token_reserves, 792_100_000_000_000);
+        assert!(!curve.complete);
+
+        let last = BondingCurveQuote::initial()
+            .after_buy(INITIAL_REAL_TOKEN_RESERVES)
+            .unwrap();
+        assert!(last.complete);
+        assert!(is_error(
+            last.buy_exact_sol_in(ONE_SOL),
+            "bonding curve is complete"
+        ));
+    }
+
+    #[test]
+    fn sell_exact_tokens_in_rounds_down() {
+        let curve = BondingCurveQuote::initial()
+            .after_buy(ONE_M_TOKENS)
+            .unwrap();
+
+        // floor(1e12 * 30_027_985_075 / 1.073e15) = 27_985_074, minus 279_852 in fees
+        assert_eq!(
+            curve.sell_exact_tokens_in(ONE_M_TOKENS).unwrap(),
+            27_705_222
+        );
+    }
+
+    #[test]
+    fn after_sell_undoes_a_buy_but_the_rounding() {
+        let curve = BondingCurveQuote::initial()
+            .after_buy(ONE_M_TOKENS)
+            .unwrap()
+            .after_sell(ONE_M_TOKENS)
+            .unwrap();
+
+        // The +1 of the buy stays on the curve
+        assert_eq!(curve.virtual_sol_reserves, INITIAL_VIRTUAL_SOL_RESERVES + 1);
+        assert_eq!(curve.virtual_token_reserves, INITIAL_VIRTUAL_TOKEN_RESERVES);
+        assert_eq!(curve.real_sol_reserves, 1);
+        assert_eq!(curve.real_token_reserves, INITIAL_REAL_TOKEN_RESERVES);
+
+        assert!(is_error(
+            BondingCurveQuote::initial().after_sell(ONE_M_TOKENS),
+            "not enough SOL left on the curve"
+        ));
+    }
+
+    #[test]
+    fn sell_exact_sol_out_covers_the_amount() {
+        let curve = BondingCurveQuote::initial()
+            .after_buy(ONE_M_TOKENS)
+            .unwrap();
+
+        // gross = ceil((1e7 + 2) * 10000 / 9900) = 10_101_013
+        // tokens = ceil(gross * 1.072e15 / (30_027_985_075 - gross))
+        let tokens = curve.sell_exact_sol_out(10_000_000).unwrap();
+        assert_eq!(tokens, 360_727_821_909);
+        assert!(curve.sell_exact_tokens_in(tokens).unwrap() >= 10_000_000);
+    }
+
+    #[test]
+    fn market_cap_over_total_supply() {
+        // floor(30e9 * 1e15 / 1.073e15)
+        assert_eq!(
+            BondingCurveQuote::initial().market_cap().unwrap(),
+            27_958_993_476
+        );
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        let curve = BondingCurveQuote {
+            virtual_sol_reserves: u64::MAX,
+            virtual_token_reserves: 1,
+            ..BondingCurveQuote::initial()
+        };
+        assert!(is_error(curve.market_cap(), "arithmetic overflow"));
+
+        let curve = BondingCurveQuote {
+            virtual_sol_reserves: u64::MAX,
+            ..BondingCurveQuote::initial()
+        };
+        assert!(is_error(
+            curve.after_buy(ONE_M_TOKENS),
+            "arithmetic overflow"
+        ));
+
+        let curve = BondingCurveQuote {
+            fee_basis_points: u64::MAX,
+            ..BondingCurveQuote::initial()
+        };
+        assert!(is_error(curve.fee(u64::MAX), "arithmetic overflow"));
+    }
+
+    #[test]
+    fn zero_reserves_are_an_error() {
+        let empty = BondingCurveQuote {
+            virtual_sol_reserves: 0,
+            virtual_token_reserves: 0,
+            real_sol_reserves: 0,
+            real_token_reserves: 0,
+            ..BondingCurveQuote::initial()
+        };
+
+        assert!(is_error(empty.market_cap(), "division by zero"));
+        assert!(is_error(empty.sell_exact_tokens_in(0), "division by zero"));
+        assert!(is_error(
+            BondingCurveQuote {
+                real_token_reserves: INITIAL_VIRTUAL_TOKEN_RESERVES,
+                ..BondingCurveQuote::initial()
+            }
+            .buy_exact_tokens_out(INITIAL_VIRTUAL_TOKEN_RESERVES),
+            "buy exceeds virtual token reserves"
+        ));
+    }
+}
@@ -1,29 +1,137 @@
 use solana_sdk::{
+    address_lookup_table::AddressLookupTableAccount,
     hash::Hash,
     instruction::Instruction,
     message::{VersionedMessage, v0::Message},
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signer::Signer,
+    system_instruction,
     transaction::VersionedTransaction,
 };
 
-use crate::config::{PRIVATE_KEY, PUBKEY};
+use crate::{
+    config::tx_signer,
+    error::{ClientError, ClientResult},
+};
+
+/// What keeps the transaction valid: a recent blockhash or a durable nonce.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxLifetime {
+    Blockhash(Hash),
+    /// The advance instruction is prepended and the nonce takes the place of the blockhash.
+    Nonce {
+        nonce_account: Pubkey,
+        authority: Pubkey,
+        nonce: Hash,
+    },
+}
+
+impl TxLifetime {
+    pub fn blockhash(&self) -> Hash {
+        match self {
+            Self::Blockhash(blockhash) => *blockhash,
+            Self::Nonce { nonce, .. } => *nonce,
+        }
+    }
+
+    /// The advance instruction of a nonce, which must come first in the transaction.
+    pub fn nonce_ix(&self) -> Option<Instruction> {
+        match self {
+            Self::Blockhash(_) => None,
+            Self::Nonce {
+                nonce_account,
+                authority,
+                ..
+            } => Some(system_instruction::advance_nonce_account(
+                nonce_account,
+                authority,
+            )),
+        }
+    }
+}
+
+impl From<Hash> for TxLifetime {
+    fn from(blockhash: Hash) -> Self {
+        Self::Blockhash(blockhash)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxEncoding {
+    Base58,
+    Base64,
+}
 
-pub fn build_and_sign(
-    mut ixs: Vec<Instruction>,
-    recent_blockhash: Hash,
-    nonce_ix: Option<Instruction>,
-) -> String {
-    // If there's a nonce instruction, insert it at the start of the instruction list
-    if let Some(nonce_instruction) = nonce_ix {
-        ixs.insert(0, nonce_instruction);
+impl TxEncoding {
+    /// Value of the `encoding` field in `sendTransaction` / `sendBundle`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Base58 => "base58",
+            Self::Base64 => "base64",
+        }
     }
 
-    let message = Message::try_compile(&PUBKEY, &ixs, &[], recent_blockhash)
-        .expect("Failed to compile message");
-    let versioned_message = VersionedMessage::V0(message);
-    let txn = VersionedTransaction::try_new(versioned_message, &[&PRIVATE_KEY])
-        .expect("Failed to create transaction");
+    pub fn encode(&self, txn: &VersionedTransaction) -> ClientResult<String> {
+        let serialized_tx = bincode::serialize(txn).map_err(|err| {
+            ClientError::Parse(
+                "Failed to serialize transaction".to_string(),
+                err.to_string(),
+            )
+        })?;
+
+        Ok(match self {
+            Self::Base58 => bs58::encode(serialized_tx).into_string(),
+            Self::Base64 => bs64::encode(&serialized_tx),
+        })
+    }
+}
+
+/// Compiles `ixs` into a v0 transaction paid by `payer` and signed by `payer` and
+/// `extra_signers`, e.g. the mint keypair of a create.
+pub fn try_build_and_sign(
+    payer: &dyn Signer,
+    extra_signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    lifetime: &TxLifetime,
+) -> ClientResult<VersionedTransaction> {
+    let ixs: Vec<Instruction> = lifetime
+        .nonce_ix()
+        .into_iter()
+        .chain(ixs.iter().cloned())
+        .collect();
+
+    let message = Message::try_compile(&payer.pubkey(), &ixs, lookup_tables, lifetime.blockhash())
+        .map_err(|err| ClientError::CompileError(err.to_string()))?;
+
+    // The payer signs first, a signer passed twice would be rejected
+    let mut signers: Vec<&dyn Signer> = vec![payer];
+    for signer in extra_signers {
+        if signers.iter().all(|s| s.pubkey() != signer.pubkey()) {
+            signers.push(*signer);
+        }
+    }
+
+    let txn = VersionedTransaction::try_new(VersionedMessage::V0(message), &signers)
+        .map_err(|err| ClientError::SignError(err.to_string()))?;
+
+    let size = bincode::serialized_size(&txn).map_err(|err| {
+        ClientError::Parse(
+            "Failed to serialize transaction".to_string(),
+            err.to_string(),
+        )
+    })? as usize;
+    if size > PACKET_DATA_SIZE {
+        return Err(ClientError::TransactionTooLarge(size));
+    }
+
+    Ok(txn)
+}
 
-    let serialized_tx = bincode::serialize(&txn).expect("Failed to serialize transaction");
+/// Builds and signs with `tx_signer` as the only signer, base64 encoded.
+pub fn build_and_sign(ixs: Vec<Instruction>, lifetime: &TxLifetime) -> ClientResult<String> {
+    let txn = try_build_and_sign(&*tx_signer()?, &[], &ixs, &[], lifetime)?;
 
-    bs64::encode(&serialized_tx)
+    TxEncoding::Base64.encode(&txn)
 }
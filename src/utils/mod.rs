@@ -1,11 +1,13 @@
 pub mod blockhash;
+pub mod bonding_curve_quote;
 pub mod build_and_sign;
+pub mod nonce;
 pub mod parse;
-pub mod swap_quote;
 pub mod utils;
 
 pub use blockhash::*;
+pub use bonding_curve_quote::*;
 pub use build_and_sign::*;
+pub use nonce::*;
 pub use parse::*;
-pub use swap_quote::*;
 pub use utils::*;
@@ -0,0 +1,196 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    instruction::Instruction,
+    nonce::{
+        State,
+        state::{Data, Versions},
+    },
+    pubkey::Pubkey,
+    signer::Signer,
+    system_instruction, system_program,
+};
+use std::sync::Arc;
+
+use crate::{
+    error::{ClientError, ClientResult},
+    service::FanOutGuard,
+    utils::{TxLifetime, try_build_and_sign},
+};
+
+/// Seed of the nonce account derived from each wallet, see [`NonceManager::nonce_address`].
+pub const NONCE_SEED: &str = "pumpfun-nonce";
+
+/// Initialized durable nonce account as read from RPC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonceAccount {
+    pub address: Pubkey,
+    pub authority: Pubkey,
+    /// Stored durable nonce, used in place of the recent blockhash.
+    pub nonce: Hash,
+    pub lamports_per_signature: u64,
+}
+
+impl NonceAccount {
+    /// Guard for fanning out versions that all consume this nonce.
+    pub fn fan_out_guard(&self) -> FanOutGuard {
+        FanOutGuard::Nonce {
+            nonce_account: self.address,
+            authority: self.authority,
+            nonce_hash: self.nonce,
+        }
+    }
+
+    /// Transactions signed with this lifetime stay valid until the nonce is advanced.
+    pub fn lifetime(&self) -> TxLifetime {
+        TxLifetime::Nonce {
+            nonce_account: self.address,
+            authority: self.authority,
+            nonce: self.nonce,
+        }
+    }
+}
+
+/// Creates and reads durable nonce accounts, one per wallet.
+#[derive(Clone)]
+pub struct NonceManager {
+    pub rpc_client: Arc<RpcClient>,
+}
+
+impl NonceManager {
+    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
+        Self { rpc_client }
+    }
+
+    /// Nonce account of `authority`, derived with [`NONCE_SEED`] so no extra keypair is stored.
+    pub fn nonce_address(authority: &Pubkey) -> Pubkey {
+        Pubkey::create_with_seed(authority, NONCE_SEED, &system_program::id())
+            .expect("nonce seed is short enough")
+    }
+
+    /// Reads the nonce account at `address`.
+    pub async fn fetch(&self, address: &Pubkey) -> ClientResult<NonceAccount> {
+        let account = self.get_account(address).await?.ok_or_else(|| {
+            ClientError::InvalidData(format!("nonce account {} not found", address))
+        })?;
+
+        Self::parse(address, &account)
+    }
+
+    // `None` only when the RPC node has no such account
+    async fn get_account(&self, address: &Pubkey) -> ClientResult<Option<Account>> {
+        Ok(self
+            .rpc_client
+            .get_multiple_accounts(&[*address])
+            .await?
+            .pop()
+            .flatten())
+    }
+
+    fn parse(address: &Pubkey, account: &Account) -> ClientResult<NonceAccount> {
+        if account.owner != system_program::id() {
+            return Err(ClientError::InvalidData(format!(
+                "{} is not a nonce account",
+                address
+            )));
+        }
+
+        let versions: Versions = bincode::deserialize(&account.data).map_err(|err| {
+            ClientError::Parse(format!("nonce account {}", address), err.to_string())
+        })?;
+
+        match versions.state() {
+            State::Initialized(data) => Ok(Self::from_data(*address, data)),
+            State::Uninitialized => Err(ClientError::InvalidData(format!(
+                "nonce account {} is not initialized",
+                address
+            ))),
+        }
+    }
+
+    /// Reads the nonce account derived from `authority`.
+    pub async fn fetch_for(&self, authority: &Pubkey) -> ClientResult<NonceAccount> {
+        self.fetch(&Self::nonce_address(authority)).await
+    }
+
+    /// Returns the nonce account of `authority`, creating it first if it does not exist yet.
+    /// `authority` pays the rent.
+    pub async fn ensure_nonce_account(&self, authority: &dyn Signer) -> ClientResult<NonceAccount> {
+        let address = Self::nonce_address(&authority.pubkey());
+
+        // Only a missing or empty account is set up, RPC errors and foreign accounts are not
+        // worth paying for a transaction that fails
+        let existing = self.get_account(&address).await?;
+        if let Some(account) = &existing
+            && (!account.data.is_empty() || account.owner != system_program::id())
+        {
+            return Self::parse(&address, account);
+        }
+
+        let rent = self
+            .rpc_client
+            .get_minimum_balance_for_rent_exemption(State::size())
+            .await?;
+        let mut ixs = system_instruction::create_nonce_account_with_seed(
+            &authority.pubkey(),
+            &address,
+            &authority.pubkey(),
+            NONCE_SEED,
+            &authority.pubkey(),
+            rent,
+        );
+        // Creating an address that already holds SOL fails, so it is allocated in place and
+        // topped up to the rent instead
+        if let Some(account) = existing {
+            ixs[0] = system_instruction::allocate_with_seed(
+                &address,
+                &authority.pubkey(),
+                NONCE_SEED,
+                State::size() as u64,
+                &system_program::id(),
+            );
+            if account.lamports < rent {
+                ixs.insert(
+                    0,
+                    system_instruction::transfer(
+                        &authority.pubkey(),
+                        &address,
+                        rent - account.lamports,
+                    ),
+                );
+            }
+        }
+
+        let blockhash = self.rpc_client.get_latest_blockhash().await?;
+        let txn = try_build_and_sign(authority, &[], &ixs, &[], &TxLifetime::Blockhash(blockhash))?;
+
+        let signature = self.rpc_client.send_and_confirm_transaction(&txn).await?;
+        println!("Created nonce account {} ({})", address, signature);
+
+        self.fetch(&address).await
+    }
+
+    /// Advances the nonce of `authority` on its own, invalidating every transaction signed
+    /// with the current value.
+    pub async fn advance(&self, authority: &dyn Signer) -> ClientResult<NonceAccount> {
+        let nonce_account = self.fetch_for(&authority.pubkey()).await?;
+        let ixs: Vec<Instruction> = nonce_account.lifetime().nonce_ix().into_iter().collect();
+
+        let blockhash = self.rpc_client.get_latest_blockhash().await?;
+        let txn = try_build_and_sign(authority, &[], &ixs, &[], &TxLifetime::Blockhash(blockhash))?;
+
+        self.rpc_client.send_and_confirm_transaction(&txn).await?;
+
+        self.fetch(&nonce_account.address).await
+    }
+
+    fn from_data(address: Pubkey, data: &Data) -> NonceAccount {
+        NonceAccount {
+            address,
+            authority: data.authority,
+            nonce: data.blockhash(),
+            lamports_per_signature: data.get_lamports_per_signature(),
+        }
+    }
+}